@@ -17,11 +17,22 @@ use crossbeam_channel as cb;
 use glib;
 
 use modules::config::{load_panels_from_path, ControlsCompat, PanelConfig};
-use modules::hyprland::{send_hyprland_snapshot, spawn_hyprland_poller, AppMsg};
-use modules::ui::{WorkspacesUi, TaskbarUi, TrayUi, instantiate_module};
+use modules::compositor::Compositor;
+use modules::hyprland::AppMsg;
+use modules::dwl::spawn_dwl_status_reader;
+use modules::app_info::warm_app_info;
+use modules::ui::{WorkspacesUi, TaskbarUi, TrayUi, SwayModeUi, ScratchpadUi, DwlTagsUi, ModuleUis, instantiate_module};
 use modules::tray::spawn_sni_watcher;
 use modules::theme::load_user_css_if_exists;
 use modules::controls::{ControlsUi, ControlsMsg};
+use modules::pinned::PinnedUi;
+use modules::menu_start::MenuStartUi;
+use modules::mpris::spawn_mpris_watcher;
+use modules::playerctl::PlayerctlUi;
+use modules::system_stats::spawn_system_sampler;
+use modules::system_ui::ResourceUis;
+use modules::network_stats::spawn_network_sampler;
+use modules::network_ui::NetworkUi;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -145,8 +156,14 @@ fn try_build_ui(app: &gtk::Application) -> anyhow::Result<()> {
 
     let (app_sender, app_receiver) = cb::unbounded::<AppMsg>();
     let (controls_sender, controls_receiver) = cb::unbounded::<ControlsMsg>();
-    spawn_hyprland_poller(app_sender.clone());
+    let compositor = Compositor::detect();
+    compositor.spawn_listener(app_sender.clone());
     spawn_sni_watcher(app_sender.clone());
+    spawn_mpris_watcher(app_sender.clone());
+    spawn_system_sampler(app_sender.clone());
+    spawn_network_sampler(app_sender.clone());
+    spawn_dwl_status_reader(app_sender.clone());
+    warm_app_info();
 
     let hypr_snapshot_sender = app_sender.clone();
 
@@ -291,6 +308,7 @@ fn try_build_ui(app: &gtk::Application) -> anyhow::Result<()> {
                     win_app_rx,
                     win_controls_rx,
                     controls_sender.clone(),
+                    compositor,
                 );
                 window.present();
                 if let Ok(mut ws) = windows.try_borrow_mut() {
@@ -298,7 +316,7 @@ fn try_build_ui(app: &gtk::Application) -> anyhow::Result<()> {
                 }
             }
 
-            send_hyprland_snapshot(&hypr_snapshot_sender);
+            compositor.send_snapshot(&hypr_snapshot_sender);
         }
     };
 
@@ -374,6 +392,21 @@ fn try_build_ui(app: &gtk::Application) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Add the `urgent` class to the panel window for a few seconds.
+fn flash_urgent(window: &glib::WeakRef<gtk::ApplicationWindow>) {
+    let window = match window.upgrade() {
+        Some(w) => w,
+        None => return,
+    };
+    window.add_css_class("urgent");
+    let weak = window.downgrade();
+    glib::timeout_add_local_once(Duration::from_secs(3), move || {
+        if let Some(window) = weak.upgrade() {
+            window.remove_css_class("urgent");
+        }
+    });
+}
+
 fn build_panel_window(
     app: &gtk::Application,
     display: &gdk::Display,
@@ -385,6 +418,7 @@ fn build_panel_window(
     receiver: cb::Receiver<AppMsg>,
     controls_receiver: cb::Receiver<ControlsMsg>,
     controls_sender: cb::Sender<ControlsMsg>,
+    compositor: Compositor,
 ) -> gtk::ApplicationWindow {
     let window = gtk::ApplicationWindow::builder()
         .application(app)
@@ -515,7 +549,14 @@ fn build_panel_window(
     } else {
         panel.monitor.clone()
     };
-    let workspaces_ui = WorkspacesUi::new(panel.hyprland_workspaces.clone(), workspaces_monitor_name);
+    let workspaces_config = match compositor {
+        Compositor::Hyprland => panel.hyprland_workspaces.clone(),
+        Compositor::Sway => panel.sway_workspaces.clone(),
+        Compositor::Niri => panel.niri_workspaces.clone(),
+        Compositor::Wlr | Compositor::None => panel.workspaces.clone(),
+    };
+    let num_ws = workspaces_config.num_ws;
+    let workspaces_ui = WorkspacesUi::new(workspaces_config, workspaces_monitor_name.clone(), compositor);
     let workspaces_ui_for_update = workspaces_ui.clone();
 
     let has_tray = panel
@@ -530,16 +571,90 @@ fn build_panel_window(
         .iter()
         .chain(panel.modules_center.iter())
         .chain(panel.modules_right.iter())
-        .any(|m| m == "hyprland-taskbar");
+        .any(|m| {
+            m == "hyprland-taskbar" || m == "sway-taskbar" || m == "niri-taskbar" || m == "taskbar"
+        });
 
+    let taskbar_config = match compositor {
+        Compositor::Hyprland => panel.hyprland_taskbar.clone(),
+        Compositor::Sway => panel.sway_taskbar.clone(),
+        Compositor::Niri => panel.niri_taskbar.clone(),
+        Compositor::Wlr | Compositor::None => panel.taskbar.clone(),
+    };
     let taskbar_ui = if has_taskbar {
-        Some(TaskbarUi::new())
+        Some(TaskbarUi::new(taskbar_config, workspaces_monitor_name.clone(), compositor, num_ws))
     } else {
         None
     };
 
     let taskbar_ui_for_update = taskbar_ui.clone();
 
+    let has_pinned = panel
+        .modules_left
+        .iter()
+        .chain(panel.modules_center.iter())
+        .chain(panel.modules_right.iter())
+        .any(|m| m == "pinned");
+    let pinned_ui = if has_pinned {
+        Some(PinnedUi::new(panel.pinned.clone(), compositor))
+    } else {
+        None
+    };
+    let pinned_ui_for_update = pinned_ui.clone();
+
+    let has_playerctl = panel
+        .modules_left
+        .iter()
+        .chain(panel.modules_center.iter())
+        .chain(panel.modules_right.iter())
+        .any(|m| m == "playerctl");
+    let playerctl_ui = if has_playerctl {
+        Some(PlayerctlUi::new(panel.playerctl.clone()))
+    } else {
+        None
+    };
+    let playerctl_ui_for_update = playerctl_ui.clone();
+
+    let resource_uis = ResourceUis::new(panel);
+    let resource_uis_for_update = resource_uis.clone();
+
+    let has_network = panel
+        .modules_left
+        .iter()
+        .chain(panel.modules_center.iter())
+        .chain(panel.modules_right.iter())
+        .any(|m| m == "network");
+    let network_ui = if has_network {
+        Some(NetworkUi::new(panel.network.clone()))
+    } else {
+        None
+    };
+    let network_ui_for_update = network_ui.clone();
+
+    // Upstream places the start menu with `menu-start: left|right`; it can also be listed as
+    // a module.
+    let menu_start_position = panel.menu_start.as_str();
+    let menu_start_in_modules = panel
+        .modules_left
+        .iter()
+        .chain(panel.modules_center.iter())
+        .chain(panel.modules_right.iter())
+        .any(|m| m == "menu-start");
+    let menu_start_ui = if menu_start_in_modules || menu_start_position == "left" || menu_start_position == "right" {
+        Some(MenuStartUi::new(panel.menu_start_settings.clone()))
+    } else {
+        None
+    };
+
+    let sway_mode_ui = SwayModeUi::new();
+    let sway_mode_ui_for_update = sway_mode_ui.clone();
+
+    let scratchpad_ui = ScratchpadUi::new();
+    let scratchpad_ui_for_update = scratchpad_ui.clone();
+
+    let dwl_tags_ui = DwlTagsUi::new(panel.dwl_tags.clone(), panel.output.clone());
+    let dwl_tags_ui_for_update = dwl_tags_ui.clone();
+
     // Create controls UI if needed.
     // Upstream config uses `controls: "left|right|off"` and `controls-settings: {...}`.
     let controls_position: Option<String> = match &panel.controls {
@@ -575,6 +690,9 @@ fn build_panel_window(
 
     let controls_ui_for_update = controls_ui.clone();
 
+    let urgent_flash = panel.urgent_flash;
+    let window_for_flash = window.downgrade();
+
     let update_source_id = {
         let id = glib::timeout_add_local(Duration::from_millis(200), move || {
         // Process messages safely
@@ -589,26 +707,81 @@ fn build_panel_window(
                     workspaces,
                     active_id,
                 } => {
+                    if let Some(taskbar) = taskbar_ui_for_update.as_ref() {
+                        taskbar.set_workspaces(&workspaces);
+                    }
                     workspaces_ui_for_update.set_workspaces(workspaces, active_id);
                 }
                 AppMsg::HyprActiveWindowAddress(addr) => {
+                    workspaces_ui_for_update.set_active_address(addr.clone());
                     if let Some(taskbar) = taskbar_ui_for_update.as_ref() {
                         taskbar.set_active_address(addr);
                     }
                 }
                 AppMsg::HyprClients { clients } => {
+                    if let Some(pinned) = pinned_ui_for_update.as_ref() {
+                        pinned.set_clients(clients.clone());
+                    }
+                    workspaces_ui_for_update.set_clients(clients.clone());
                     if let Some(taskbar) = taskbar_ui_for_update.as_ref() {
                         taskbar.set_clients(clients);
                     }
                 }
+                AppMsg::UrgentWindow(address) => {
+                    workspaces_ui_for_update.mark_urgent(address.clone());
+                    if let Some(taskbar) = taskbar_ui_for_update.as_ref() {
+                        taskbar.mark_urgent(address);
+                    }
+                    if urgent_flash {
+                        flash_urgent(&window_for_flash);
+                    }
+                }
+                AppMsg::UrgentWindows(addresses) => {
+                    let newly_urgent = workspaces_ui_for_update.set_urgent(addresses.clone());
+                    if let Some(taskbar) = taskbar_ui_for_update.as_ref() {
+                        taskbar.set_urgent(addresses);
+                    }
+                    if urgent_flash && newly_urgent {
+                        flash_urgent(&window_for_flash);
+                    }
+                }
+                AppMsg::HyprSpecialWorkspaces(open) => {
+                    workspaces_ui_for_update.set_special_workspaces(&open);
+                }
+                AppMsg::SwayMode(mode) => {
+                    sway_mode_ui_for_update.set_mode(&mode);
+                }
+                AppMsg::SwayScratchpad { clients } => {
+                    scratchpad_ui_for_update.set_clients(clients);
+                }
+                AppMsg::DwlStatus(outputs) => {
+                    dwl_tags_ui_for_update.set_status(&outputs);
+                }
                 AppMsg::TrayItemsChanged(items) => {
                     tray_ui_for_update.set_items(items);
                 }
                 AppMsg::TrayIconUpdated { item, icon } => {
                     tray_ui_for_update.update_item_icon(&item, &icon);
                 }
+                AppMsg::SystemStats(sample) => {
+                    resource_uis_for_update.set_sample(&sample);
+                }
+                AppMsg::Network(sample) => {
+                    if let Some(network) = network_ui_for_update.as_ref() {
+                        network.set_sample(&sample);
+                    }
+                }
+                AppMsg::MprisPlayers(players) => {
+                    if let Some(playerctl) = playerctl_ui_for_update.as_ref() {
+                        playerctl.set_players(players);
+                    }
+                }
             }
         }
+
+        if let Some(taskbar) = taskbar_ui_for_update.as_ref() {
+            taskbar.apply_order_change();
+        }
         
         // Process controls messages with error handling
         if let Ok(msg) = controls_receiver.try_recv() {
@@ -655,6 +828,27 @@ fn build_panel_window(
         }
     });
 
+    if let Some(menu_start) = menu_start_ui.as_ref() {
+        if !menu_start_in_modules && menu_start_position == "left" {
+            left.append(&menu_start.widget());
+        }
+    }
+
+    let module_uis = ModuleUis {
+        tray: Some(tray_ui.clone()),
+        workspaces: Some(workspaces_ui.clone()),
+        taskbar: taskbar_ui.clone(),
+        controls: controls_ui.clone(),
+        sway_mode: Some(sway_mode_ui.clone()),
+        scratchpad: Some(scratchpad_ui.clone()),
+        dwl_tags: Some(dwl_tags_ui.clone()),
+        pinned: pinned_ui.clone(),
+        menu_start: menu_start_ui.clone(),
+        playerctl: playerctl_ui.clone(),
+        resources: Some(resource_uis.clone()),
+        network: network_ui.clone(),
+    };
+
     for m in &panel.modules_left {
         if m == "tray" {
             continue;
@@ -663,14 +857,7 @@ fn build_panel_window(
             // Upstream-style controls are placed by `panel.controls`; avoid duplicating.
             continue;
         }
-        left.append(&instantiate_module(
-            panel,
-            m,
-            Some(&tray_ui),
-            Some(&workspaces_ui),
-            taskbar_ui.as_ref(),
-            controls_ui.as_ref(),
-        ));
+        left.append(&instantiate_module(panel, m, &module_uis));
     }
     for m in &panel.modules_center {
         if m == "tray" {
@@ -679,14 +866,7 @@ fn build_panel_window(
         if m == "controls" {
             continue;
         }
-        center.append(&instantiate_module(
-            panel,
-            m,
-            Some(&tray_ui),
-            Some(&workspaces_ui),
-            taskbar_ui.as_ref(),
-            controls_ui.as_ref(),
-        ));
+        center.append(&instantiate_module(panel, m, &module_uis));
     }
     for m in &panel.modules_right {
         if m == "tray" {
@@ -695,20 +875,19 @@ fn build_panel_window(
         if m == "controls" {
             continue;
         }
-        right.append(&instantiate_module(
-            panel,
-            m,
-            Some(&tray_ui),
-            Some(&workspaces_ui),
-            taskbar_ui.as_ref(),
-            controls_ui.as_ref(),
-        ));
+        right.append(&instantiate_module(panel, m, &module_uis));
     }
 
     if has_tray {
         right.append(&tray_ui.widget());
     }
 
+    if let Some(menu_start) = menu_start_ui.as_ref() {
+        if !menu_start_in_modules && menu_start_position == "right" {
+            right.append(&menu_start.widget());
+        }
+    }
+
     // Place controls widget based on upstream-style `controls` value.
     if let Some(controls) = controls_ui.as_ref() {
         match controls_position.as_deref() {
@@ -0,0 +1,400 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+
+/// The parts of a `.desktop` file the panel uses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppEntry {
+    /// Desktop file id, without the `.desktop` suffix (e.g. `org.gnome.Nautilus`).
+    pub id: String,
+    /// `Name`, localized when a matching `Name[xx]` exists.
+    pub name: String,
+    /// `Icon`: a theme icon name or an absolute path.
+    pub icon: String,
+    pub exec: String,
+    pub startup_wm_class: String,
+    pub path: PathBuf,
+}
+
+/// Maps window classes / app ids / pids to desktop entries.
+///
+/// The index is built once from the `applications/` directories under the XDG data dirs;
+/// lookups are cached per window, misses included, so repeated taskbar updates don't
+/// rescan the index or reread `/proc`.
+#[derive(Debug, Default)]
+pub struct AppInfoResolver {
+    entries: Vec<AppEntry>,
+    by_id: HashMap<String, usize>,
+    by_wm_class: HashMap<String, usize>,
+    by_exec: HashMap<String, usize>,
+    cache: HashMap<(String, String, i32), Option<usize>>,
+}
+
+impl AppInfoResolver {
+    /// Index `$XDG_DATA_HOME` and `$XDG_DATA_DIRS` (plus the Flatpak export dirs), using the
+    /// locale from `LC_ALL` / `LC_MESSAGES` / `LANG`.
+    pub fn from_env() -> Self {
+        Self::from_dirs(&data_dirs(), &locale_variants(&current_locale()))
+    }
+
+    /// Index `<dir>/applications` for each data dir. Earlier dirs win for duplicate ids,
+    /// like the XDG lookup order.
+    pub fn from_dirs(dirs: &[PathBuf], locales: &[String]) -> Self {
+        let mut resolver = Self::default();
+        for dir in dirs {
+            let apps = dir.join("applications");
+            resolver.index_dir(&apps, &apps, locales);
+        }
+        resolver
+    }
+
+    fn index_dir(&mut self, root: &Path, dir: &Path, locales: &[String]) {
+        let read = match fs::read_dir(dir) {
+            Ok(r) => r,
+            Err(_) => return,
+        };
+        for entry in read.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                self.index_dir(root, &path, locales);
+                continue;
+            }
+            if path.extension().and_then(|e| e.to_str()) != Some("desktop") {
+                continue;
+            }
+            // Subdirectories become `-`-separated id prefixes (`kde/foo.desktop` -> `kde-foo`).
+            let id = match path.strip_prefix(root).ok().and_then(|p| p.to_str()) {
+                Some(rel) => rel.trim_end_matches(".desktop").replace('/', "-"),
+                None => continue,
+            };
+            if self.by_id.contains_key(&id.to_lowercase()) {
+                continue;
+            }
+            let text = match fs::read_to_string(&path) {
+                Ok(t) => t,
+                Err(_) => continue,
+            };
+            if let Some(app) = parse_desktop_entry(&text, &id, &path, locales) {
+                self.insert(app);
+            }
+        }
+    }
+
+    fn insert(&mut self, app: AppEntry) {
+        let idx = self.entries.len();
+        self.by_id.insert(app.id.to_lowercase(), idx);
+        if !app.startup_wm_class.is_empty() {
+            self.by_wm_class
+                .entry(app.startup_wm_class.to_lowercase())
+                .or_insert(idx);
+        }
+        if let Some(bin) = exec_basename(&app.exec) {
+            self.by_exec.entry(bin).or_insert(idx);
+        }
+        self.entries.push(app);
+    }
+
+    /// Find the desktop entry for a window. `pid` (0 when unknown) is only used when the
+    /// class and initial class don't match anything.
+    pub fn resolve(&mut self, class: &str, initial_class: &str, pid: i32) -> Option<&AppEntry> {
+        let key = (class.to_string(), initial_class.to_string(), pid);
+        let idx = match self.cache.get(&key) {
+            Some(cached) => *cached,
+            None => {
+                let found = self
+                    .lookup_class(class)
+                    .or_else(|| self.lookup_class(initial_class))
+                    .or_else(|| self.lookup_pid(pid));
+                self.cache.insert(key, found);
+                found
+            }
+        };
+        self.entries.get(idx?)
+    }
+
+    fn lookup_class(&self, class: &str) -> Option<usize> {
+        let class = class.trim();
+        if class.is_empty() {
+            return None;
+        }
+        let lower = class.to_lowercase();
+
+        // Electron/Chromium apps usually set StartupWMClass to their window class.
+        if let Some(idx) = self.by_wm_class.get(&lower) {
+            return Some(*idx);
+        }
+
+        // Desktop id equal to the class / app id (covers Flatpak and reverse-DNS ids).
+        let dashed = lower.replace([' ', '_'], "-");
+        for candidate in [lower.as_str(), dashed.as_str()] {
+            if let Some(idx) = self.by_id.get(candidate) {
+                return Some(*idx);
+            }
+        }
+
+        // `nautilus` -> `org.gnome.Nautilus`; the reverse-DNS tail must match exactly.
+        let suffix = format!(".{}", lower);
+        let mut tail_matches = self.by_id.iter().filter(|(id, _)| id.ends_with(&suffix));
+        if let (Some((_, idx)), None) = (tail_matches.next(), tail_matches.next()) {
+            return Some(*idx);
+        }
+
+        // And the other way round: class `org.mozilla.firefox`, desktop id `firefox`.
+        if let Some(last) = lower.rsplit('.').next() {
+            if last != lower {
+                if let Some(idx) = self.by_id.get(last) {
+                    return Some(*idx);
+                }
+            }
+        }
+
+        self.by_exec.get(&lower).copied()
+    }
+
+    fn lookup_pid(&self, pid: i32) -> Option<usize> {
+        if pid <= 0 {
+            return None;
+        }
+
+        // Sandboxed Flatpak apps: the app id is in /.flatpak-info inside their mount ns.
+        let info = PathBuf::from(format!("/proc/{}/root/.flatpak-info", pid));
+        if let Ok(text) = fs::read_to_string(info) {
+            let app_id = text
+                .lines()
+                .skip_while(|l| l.trim() != "[Application]")
+                .find_map(|l| l.trim().strip_prefix("name="));
+            if let Some(idx) = app_id.and_then(|id| self.by_id.get(&id.to_lowercase())) {
+                return Some(*idx);
+            }
+        }
+
+        let exe = fs::read_link(format!("/proc/{}/exe", pid)).ok()?;
+        let bin = exe.file_name()?.to_str()?.to_lowercase();
+        self.by_exec
+            .get(&bin)
+            .or_else(|| self.by_id.get(&bin))
+            .copied()
+    }
+}
+
+fn parse_desktop_entry(text: &str, id: &str, path: &Path, locales: &[String]) -> Option<AppEntry> {
+    let mut in_entry = false;
+    let mut fields: HashMap<&str, &str> = HashMap::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_entry || line.starts_with('#') {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            fields.entry(key.trim()).or_insert(value.trim());
+        }
+    }
+
+    if fields.get("Type").copied().unwrap_or("Application") != "Application" {
+        return None;
+    }
+    let name = locales
+        .iter()
+        .find_map(|l| fields.get(format!("Name[{}]", l).as_str()))
+        .or_else(|| fields.get("Name"))?;
+
+    Some(AppEntry {
+        id: id.to_string(),
+        name: name.to_string(),
+        icon: fields.get("Icon").unwrap_or(&"").to_string(),
+        exec: fields.get("Exec").unwrap_or(&"").to_string(),
+        startup_wm_class: fields.get("StartupWMClass").unwrap_or(&"").to_string(),
+        path: path.to_path_buf(),
+    })
+}
+
+/// Lowercased basename of the program in an `Exec` line, skipping `env VAR=...` prefixes.
+fn exec_basename(exec: &str) -> Option<String> {
+    let mut args = exec.split_whitespace().filter(|a| !a.contains('='));
+    let mut program = args.next()?;
+    if program == "env" {
+        program = args.next()?;
+    }
+    let bin = Path::new(program.trim_matches('"')).file_name()?.to_str()?;
+    Some(bin.to_lowercase())
+}
+
+fn data_dirs() -> Vec<PathBuf> {
+    let mut out = Vec::new();
+    match std::env::var_os("XDG_DATA_HOME") {
+        Some(home) if !home.is_empty() => out.push(PathBuf::from(home)),
+        _ => {
+            if let Some(home) = dirs::home_dir() {
+                out.push(home.join(".local/share"));
+            }
+        }
+    }
+    if let Some(home) = dirs::home_dir() {
+        out.push(home.join(".local/share/flatpak/exports/share"));
+    }
+    let system = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    out.extend(system.split(':').filter(|d| !d.is_empty()).map(PathBuf::from));
+    out.push(PathBuf::from("/var/lib/flatpak/exports/share"));
+
+    let mut seen = Vec::new();
+    out.retain(|d| {
+        let new = !seen.contains(d);
+        seen.push(d.clone());
+        new
+    });
+    out
+}
+
+fn current_locale() -> String {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|v| std::env::var(v).ok())
+        .find(|v| !v.is_empty())
+        .unwrap_or_default()
+}
+
+/// `de_DE.UTF-8@euro` -> `de_DE@euro`, `de_DE`, `de@euro`, `de` (desktop entry spec order).
+pub fn locale_variants(locale: &str) -> Vec<String> {
+    let (rest, modifier) = match locale.split_once('@') {
+        Some((r, m)) => (r, Some(m)),
+        None => (locale, None),
+    };
+    let rest = rest.split('.').next().unwrap_or("");
+    let (lang, country) = match rest.split_once('_') {
+        Some((l, c)) => (l, Some(c)),
+        None => (rest, None),
+    };
+    if lang.is_empty() || lang == "C" || lang == "POSIX" {
+        return Vec::new();
+    }
+
+    let mut variants = Vec::new();
+    if let (Some(c), Some(m)) = (country, modifier) {
+        variants.push(format!("{}_{}@{}", lang, c, m));
+    }
+    if let Some(c) = country {
+        variants.push(format!("{}_{}", lang, c));
+    }
+    if let Some(m) = modifier {
+        variants.push(format!("{}@{}", lang, m));
+    }
+    variants.push(lang.to_string());
+    variants
+}
+
+fn shared() -> &'static Mutex<AppInfoResolver> {
+    static RESOLVER: OnceLock<Mutex<AppInfoResolver>> = OnceLock::new();
+    RESOLVER.get_or_init(|| Mutex::new(AppInfoResolver::from_env()))
+}
+
+/// Build the process-wide index in the background, so the first taskbar update doesn't
+/// wait for the data dirs to be scanned.
+pub fn warm_app_info() {
+    thread::spawn(|| {
+        shared();
+    });
+}
+
+/// Resolve a window through the process-wide resolver (indexed on first use).
+pub fn resolve_app(class: &str, initial_class: &str, pid: i32) -> Option<AppEntry> {
+    let mut resolver = shared().lock().ok()?;
+    resolver.resolve(class, initial_class, pid).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A fresh data dir under the system temp dir; the caller writes `applications/` into it.
+    fn data_dir() -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "nwg-panel-app-info-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("applications")).unwrap();
+        dir
+    }
+
+    fn write_entry(dir: &Path, rel: &str, body: &str) {
+        let path = dir.join("applications").join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, format!("[Desktop Entry]\nType=Application\n{body}")).unwrap();
+    }
+
+    #[test]
+    fn resolves_by_wm_class_id_tail_and_exec() {
+        let dir = data_dir();
+        write_entry(&dir, "code.desktop", "Name=Visual Studio Code\nExec=/usr/share/code/code %F\nStartupWMClass=Code\n");
+        write_entry(&dir, "org.gnome.Nautilus.desktop", "Name=Files\nExec=nautilus --new-window\nIcon=org.gnome.Nautilus\n");
+        write_entry(&dir, "firefox.desktop", "Name=Firefox\nExec=env MOZ_ENABLE_WAYLAND=1 firefox %u\n");
+        write_entry(&dir, "kde/konsole.desktop", "Name=Konsole\nExec=konsole\n");
+        let mut resolver = AppInfoResolver::from_dirs(&[dir], &[]);
+
+        let id = |r: &mut AppInfoResolver, class: &str| r.resolve(class, "", 0).map(|a| a.id.clone());
+        assert_eq!(id(&mut resolver, "Code").as_deref(), Some("code"));
+        assert_eq!(id(&mut resolver, "nautilus").as_deref(), Some("org.gnome.Nautilus"));
+        assert_eq!(id(&mut resolver, "org.mozilla.firefox").as_deref(), Some("firefox"));
+        assert_eq!(id(&mut resolver, "konsole").as_deref(), Some("kde-konsole"));
+        // Falls back to the initial class.
+        assert_eq!(
+            resolver.resolve("", "org.gnome.Nautilus", 0).map(|a| a.name.as_str()),
+            Some("Files")
+        );
+    }
+
+    #[test]
+    fn earlier_dirs_win_and_non_applications_are_skipped() {
+        let user = data_dir();
+        let system = data_dir();
+        write_entry(&user, "foot.desktop", "Name=My Foot\nExec=foot\n");
+        write_entry(&system, "foot.desktop", "Name=Foot\nExec=foot\n");
+        let link = system.join("applications/site.desktop");
+        fs::write(link, "[Desktop Entry]\nType=Link\nName=Site\nURL=https://example.org\n").unwrap();
+        let mut resolver = AppInfoResolver::from_dirs(&[user, system], &[]);
+
+        assert_eq!(resolver.resolve("foot", "", 0).map(|a| a.name.as_str()), Some("My Foot"));
+        assert!(resolver.resolve("site", "", 0).is_none());
+    }
+
+    #[test]
+    fn names_are_localized() {
+        let dir = data_dir();
+        write_entry(&dir, "files.desktop", "Name=Files\nName[de]=Dateien\nName[de_AT]=Dateien (AT)\nExec=files\n");
+        let mut resolver = AppInfoResolver::from_dirs(&[dir.clone()], &locale_variants("de_DE.UTF-8"));
+        assert_eq!(resolver.resolve("files", "", 0).map(|a| a.name.as_str()), Some("Dateien"));
+        let mut resolver = AppInfoResolver::from_dirs(&[dir], &locale_variants("de_AT.UTF-8@euro"));
+        assert_eq!(resolver.resolve("files", "", 0).map(|a| a.name.as_str()), Some("Dateien (AT)"));
+    }
+
+    #[test]
+    fn misses_are_cached() {
+        let dir = data_dir();
+        write_entry(&dir, "foot.desktop", "Name=Foot\nExec=foot\n");
+        let mut resolver = AppInfoResolver::from_dirs(&[dir], &[]);
+
+        assert!(resolver.resolve("unknown-app", "", 0).is_none());
+        assert!(resolver.resolve("unknown-app", "", 0).is_none());
+        assert_eq!(resolver.cache.get(&("unknown-app".to_string(), String::new(), 0)), Some(&None));
+        assert_eq!(resolver.cache.len(), 1);
+    }
+
+    #[test]
+    fn locale_variants_follow_the_spec_order() {
+        assert_eq!(locale_variants("de_DE.UTF-8@euro"), vec!["de_DE@euro", "de_DE", "de@euro", "de"]);
+        assert_eq!(locale_variants("fr"), vec!["fr"]);
+        assert!(locale_variants("C.UTF-8").is_empty());
+    }
+}
@@ -0,0 +1,152 @@
+use crossbeam_channel as cb;
+use gtk4 as gtk;
+use gtk::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::config::BrightnessSliderConfig;
+use super::controls::ControlsUi;
+
+/// Polled values are ignored this long after the user moved the slider, so a reading taken
+/// before the change doesn't snap it back.
+const USER_CHANGE_GRACE: Duration = Duration::from_secs(2);
+
+/// Upstream `brightness-slider`: an icon and an inline scale in the bar, using the same
+/// `light`/`brightnessctl` backend as the controls popover.
+#[derive(Clone)]
+pub struct BrightnessSliderUi {
+    root: gtk::Box,
+    image: gtk::Image,
+    scale: gtk::Scale,
+    value: gtk::Label,
+    updating: Rc<Cell<bool>>,
+    last_user_change: Rc<Cell<Option<Instant>>>,
+}
+
+impl BrightnessSliderUi {
+    pub fn new(config: BrightnessSliderConfig) -> Self {
+        let root = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        root.set_widget_name(&config.root_css_name);
+
+        let image = gtk::Image::from_icon_name("display-brightness-medium-symbolic");
+        image.set_pixel_size(config.icon_size);
+        root.append(&image);
+
+        let step = config.step_size.max(1) as f64;
+        let scale = gtk::Scale::with_range(gtk::Orientation::Horizontal, 0.0, 100.0, step);
+        scale.set_widget_name(&config.css_name);
+        scale.set_draw_value(false);
+        scale.set_width_request(config.slider_width);
+        scale.set_valign(gtk::Align::Center);
+        root.append(&scale);
+
+        let value = gtk::Label::new(None);
+        value.set_visible(config.show_values);
+        value.set_width_chars(4);
+        root.append(&value);
+
+        let ui = Self {
+            root,
+            image,
+            scale,
+            value,
+            updating: Rc::new(Cell::new(false)),
+            last_user_change: Rc::new(Cell::new(None)),
+        };
+
+        // Setting brightness forks a process; while dragging only the newest value matters.
+        let (set_tx, set_rx) = cb::unbounded::<i32>();
+        thread::spawn(move || {
+            while let Ok(mut value) = set_rx.recv() {
+                while let Ok(newer) = set_rx.try_recv() {
+                    value = newer;
+                }
+                let _ = ControlsUi::set_brightness(value);
+            }
+        });
+
+        let ui_for_change = ui.clone();
+        ui.scale.connect_value_changed(move |scale| {
+            if ui_for_change.updating.get() {
+                return;
+            }
+            let v = scale.value().round() as i32;
+            ui_for_change.last_user_change.set(Some(Instant::now()));
+            ui_for_change.show_value(v);
+            let _ = set_tx.send(v);
+        });
+
+        // Scrolling over the icon steps like the scale's arrow keys.
+        let scroll = gtk::EventControllerScroll::new(gtk::EventControllerScrollFlags::VERTICAL);
+        let scale_for_scroll = ui.scale.clone();
+        scroll.connect_scroll(move |_, _, dy| {
+            let delta = if dy < 0.0 { step } else { -step };
+            scale_for_scroll.set_value((scale_for_scroll.value() + delta).clamp(0.0, 100.0));
+            glib::Propagation::Stop
+        });
+        ui.image.add_controller(scroll);
+
+        // Insensitive until the first reading, and for as long as neither `light` nor
+        // `brightnessctl` works.
+        ui.root.set_sensitive(false);
+        let (value_tx, value_rx) = cb::unbounded::<Option<i32>>();
+        let interval = Duration::from_secs(config.interval.max(1) as u64);
+        thread::spawn(move || loop {
+            let value = ControlsUi::get_brightness().ok();
+            if value_tx.send(value).is_err() {
+                return;
+            }
+            thread::sleep(interval);
+        });
+
+        let ui_for_poll = ui.clone();
+        let source = glib::timeout_add_local(Duration::from_millis(200), move || {
+            while let Ok(value) = value_rx.try_recv() {
+                ui_for_poll.set_brightness(value);
+            }
+            glib::ControlFlow::Continue
+        });
+        let source = Rc::new(RefCell::new(Some(source)));
+        ui.root.connect_destroy(move |_| {
+            if let Ok(mut source) = source.try_borrow_mut() {
+                if let Some(id) = source.take() {
+                    id.remove();
+                }
+            }
+        });
+
+        ui
+    }
+
+    pub fn widget(&self) -> gtk::Widget {
+        self.root.clone().upcast()
+    }
+
+    fn show_value(&self, value: i32) {
+        self.value.set_text(&format!("{}%", value));
+        self.image.set_icon_name(Some(&ControlsUi::brightness_icon_name(value)));
+        self.root.set_tooltip_text(Some(&format!("Brightness: {}%", value)));
+    }
+
+    fn set_brightness(&self, value: Option<i32>) {
+        let value = match value {
+            Some(v) => v,
+            None => {
+                self.root.set_sensitive(false);
+                self.value.set_text("");
+                self.root.set_tooltip_text(Some("Brightness: no backend (light or brightnessctl)"));
+                return;
+            }
+        };
+        self.root.set_sensitive(true);
+        if self.last_user_change.get().is_some_and(|t| t.elapsed() < USER_CHANGE_GRACE) {
+            return;
+        }
+        self.updating.set(true);
+        self.scale.set_value(value as f64);
+        self.updating.set(false);
+        self.show_value(value);
+    }
+}
@@ -0,0 +1,131 @@
+use super::hyprland::{hyprctl_dispatch_close_address, hyprctl_dispatch_focus_address, hyprctl_dispatch_workspace};
+use super::hyprland::hyprctl_dispatch_move_address_to_workspace;
+use super::hyprland::{hyprctl_dispatch_move_active_to_workspace, send_hyprland_snapshot, spawn_hyprland_poller, AppMsg};
+use super::niri::{niri_close_window, niri_focus_window, niri_focus_workspace, niri_move_focused_to_workspace};
+use super::niri::{niri_move_window_to_workspace, send_niri_snapshot, spawn_niri_listener};
+use super::sway::{send_sway_snapshot, spawn_sway_listener, swaymsg_move_con_to_workspace};
+use super::sway::{swaymsg_close_con, swaymsg_focus_con, swaymsg_move_focused_to_workspace, swaymsg_workspace};
+use super::wlr::{send_wlr_snapshot, spawn_wlr_listener, wlr_protocols_available};
+use super::wlr::{wlr_activate_or_minimize, wlr_activate_workspace, wlr_close};
+use crossbeam_channel as cb;
+
+/// The compositor backend the panel talks to.
+///
+/// Workspace/taskbar widgets are shared between backends; this decides which IPC the
+/// click handlers dispatch to and which CSS name prefix the widgets use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compositor {
+    #[default]
+    Hyprland,
+    Sway,
+    Niri,
+    /// Any wlroots-style compositor, via wlr-foreign-toplevel + ext-workspace.
+    Wlr,
+    /// Nothing we can talk to: workspace/taskbar widgets stay empty.
+    None,
+}
+
+impl Compositor {
+    /// Detect the running compositor from its IPC environment variables.
+    /// Other Wayland compositors get the generic protocol backend if they advertise its globals.
+    pub fn detect() -> Self {
+        if std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() {
+            return Compositor::Hyprland;
+        }
+        if std::env::var_os("SWAYSOCK").is_some() {
+            return Compositor::Sway;
+        }
+        if std::env::var_os("NIRI_SOCKET").is_some() {
+            return Compositor::Niri;
+        }
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() && wlr_protocols_available() {
+            return Compositor::Wlr;
+        }
+        eprintln!("No supported compositor IPC or toplevel/workspace protocol found; workspaces and taskbar stay empty");
+        Compositor::None
+    }
+
+    /// Prefix used for widget names (e.g. `hyprland-workspaces`, `sway-workspaces`).
+    pub fn css_prefix(self) -> &'static str {
+        match self {
+            Compositor::Hyprland => "hyprland",
+            Compositor::Sway => "sway",
+            Compositor::Niri => "niri",
+            Compositor::Wlr => "wlr",
+            Compositor::None => "none",
+        }
+    }
+
+    /// Start the background thread that forwards compositor events as `AppMsg`s.
+    pub fn spawn_listener(self, sender: cb::Sender<AppMsg>) {
+        match self {
+            Compositor::Hyprland => spawn_hyprland_poller(sender),
+            Compositor::Sway => spawn_sway_listener(sender),
+            Compositor::Niri => spawn_niri_listener(sender),
+            Compositor::Wlr => spawn_wlr_listener(sender),
+            Compositor::None => {}
+        }
+    }
+
+    pub fn send_snapshot(self, sender: &cb::Sender<AppMsg>) {
+        match self {
+            Compositor::Hyprland => send_hyprland_snapshot(sender),
+            Compositor::Sway => send_sway_snapshot(sender),
+            Compositor::Niri => send_niri_snapshot(sender),
+            Compositor::Wlr => send_wlr_snapshot(sender),
+            Compositor::None => {}
+        }
+    }
+
+    pub fn dispatch_workspace(self, id: i32) -> anyhow::Result<()> {
+        match self {
+            Compositor::Hyprland => hyprctl_dispatch_workspace(id),
+            Compositor::Sway => swaymsg_workspace(id),
+            Compositor::Niri => niri_focus_workspace(id),
+            Compositor::Wlr => wlr_activate_workspace(id),
+            Compositor::None => anyhow::bail!("No compositor backend"),
+        }
+    }
+
+    /// Move the focused window to workspace `id`; `silent` keeps the current workspace focused.
+    pub fn move_active_to_workspace(self, id: i32, silent: bool) -> anyhow::Result<()> {
+        match self {
+            Compositor::Hyprland => hyprctl_dispatch_move_active_to_workspace(id, silent),
+            Compositor::Sway => swaymsg_move_focused_to_workspace(id, silent),
+            Compositor::Niri => niri_move_focused_to_workspace(id, silent),
+            Compositor::Wlr => anyhow::bail!("Moving windows between workspaces is not supported by wlr-foreign-toplevel"),
+            Compositor::None => anyhow::bail!("No compositor backend"),
+        }
+    }
+
+    /// Move a window to workspace `id` without following it.
+    pub fn move_address_to_workspace(self, address: &str, id: i32) -> anyhow::Result<()> {
+        match self {
+            Compositor::Hyprland => hyprctl_dispatch_move_address_to_workspace(address, id),
+            Compositor::Sway => swaymsg_move_con_to_workspace(address, id),
+            Compositor::Niri => niri_move_window_to_workspace(address, id),
+            Compositor::Wlr => anyhow::bail!("Moving windows between workspaces is not supported by wlr-foreign-toplevel"),
+            Compositor::None => anyhow::bail!("No compositor backend"),
+        }
+    }
+
+    pub fn focus_address(self, address: &str) -> anyhow::Result<()> {
+        match self {
+            Compositor::Hyprland => hyprctl_dispatch_focus_address(address),
+            Compositor::Sway => swaymsg_focus_con(address),
+            Compositor::Niri => niri_focus_window(address),
+            Compositor::Wlr => wlr_activate_or_minimize(address),
+            Compositor::None => anyhow::bail!("No compositor backend"),
+        }
+    }
+
+    pub fn close_address(self, address: &str) -> anyhow::Result<()> {
+        match self {
+            Compositor::Hyprland => hyprctl_dispatch_close_address(address),
+            Compositor::Sway => swaymsg_close_con(address),
+            Compositor::Niri => niri_close_window(address),
+            Compositor::Wlr => wlr_close(address),
+            Compositor::None => anyhow::bail!("No compositor backend"),
+        }
+    }
+}
@@ -50,8 +50,74 @@ pub struct PanelConfig {
     #[serde(default, rename = "hyprland-workspaces")]
     pub hyprland_workspaces: HyprlandWorkspacesConfig,
 
+    #[serde(default, rename = "sway-workspaces")]
+    pub sway_workspaces: HyprlandWorkspacesConfig,
+
+    #[serde(default, rename = "niri-workspaces")]
+    pub niri_workspaces: HyprlandWorkspacesConfig,
+
+    #[serde(default)]
+    pub workspaces: HyprlandWorkspacesConfig,
+
+    #[serde(default, rename = "hyprland-taskbar")]
+    pub hyprland_taskbar: TaskbarConfig,
+
+    #[serde(default, rename = "sway-taskbar")]
+    pub sway_taskbar: TaskbarConfig,
+
+    #[serde(default, rename = "niri-taskbar")]
+    pub niri_taskbar: TaskbarConfig,
+
+    #[serde(default)]
+    pub taskbar: TaskbarConfig,
+
     #[serde(default)]
     pub clock: ClockConfig,
+
+    #[serde(default, rename = "dwl-tags")]
+    pub dwl_tags: DwlTagsConfig,
+
+    #[serde(default)]
+    pub pinned: PinnedConfig,
+
+    #[serde(default)]
+    pub playerctl: PlayerctlConfig,
+
+    #[serde(default)]
+    pub cpu: CpuConfig,
+
+    #[serde(default)]
+    pub memory: MemoryConfig,
+
+    #[serde(default)]
+    pub disk: DiskConfig,
+
+    #[serde(default)]
+    pub temperature: TemperatureConfig,
+
+    #[serde(default)]
+    pub network: NetworkConfig,
+
+    #[serde(default)]
+    pub openweather: OpenWeatherConfig,
+
+    #[serde(default, rename = "brightness-slider")]
+    pub brightness_slider: BrightnessSliderConfig,
+
+    #[serde(default, rename = "random-wallpaper")]
+    pub random_wallpaper: RandomWallpaperConfig,
+
+    /// Upstream placement of the start menu: `left`, `right` or `off`. It can also be
+    /// listed as `menu-start` in a modules list.
+    #[serde(default = "default_menu_start", rename = "menu-start")]
+    pub menu_start: String,
+
+    #[serde(default, rename = "menu-start-settings")]
+    pub menu_start_settings: MenuStartConfig,
+
+    /// Briefly add the `urgent` CSS class to the panel window when a window wants attention.
+    #[serde(default, rename = "urgent-flash")]
+    pub urgent_flash: bool,
 }
 
 #[derive(Debug, Deserialize, Clone, Default)]
@@ -63,7 +129,7 @@ pub enum ControlsCompat {
     Settings(ControlsConfig),
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Deserialize, Clone)]
 pub struct ClockConfig {
     #[serde(default = "default_clock_format")]
     pub format: String,
@@ -76,9 +142,596 @@ pub struct ClockConfig {
 
     #[serde(default, rename = "root-css-name")]
     pub root_css_name: String,
+
+    /// strftime format for the tooltip; empty disables it.
+    #[serde(default = "default_clock_tooltip_format", rename = "tooltip-format")]
+    pub tooltip_format: String,
+
+    /// Shell commands. An empty `on-left-click` opens the calendar popover instead.
+    #[serde(default, rename = "on-left-click")]
+    pub on_left_click: String,
+
+    #[serde(default, rename = "on-middle-click")]
+    pub on_middle_click: String,
+
+    #[serde(default, rename = "on-right-click")]
+    pub on_right_click: String,
+
+    #[serde(default, rename = "on-scroll-up")]
+    pub on_scroll_up: String,
+
+    #[serde(default, rename = "on-scroll-down")]
+    pub on_scroll_down: String,
+
+    /// Extra IANA time zones (e.g. `America/New_York`) for the tooltip and popover.
+    #[serde(default, rename = "time-zones")]
+    pub time_zones: Vec<String>,
+
+    #[serde(default = "default_clock_time_zone_format", rename = "time-zone-format")]
+    pub time_zone_format: String,
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self {
+            format: default_clock_format(),
+            interval: default_clock_interval(),
+            css_name: String::new(),
+            root_css_name: String::new(),
+            tooltip_format: default_clock_tooltip_format(),
+            on_left_click: String::new(),
+            on_middle_click: String::new(),
+            on_right_click: String::new(),
+            on_scroll_up: String::new(),
+            on_scroll_down: String::new(),
+            time_zones: Vec::new(),
+            time_zone_format: default_clock_time_zone_format(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct DwlTagsConfig {
+    #[serde(default = "default_dwl_tag_names", rename = "tag-names")]
+    pub tag_names: Vec<String>,
+
+    #[serde(default = "default_true", rename = "show-layout")]
+    pub show_layout: bool,
+
+    #[serde(default = "default_true", rename = "show-title")]
+    pub show_title: bool,
+
+    #[serde(default = "default_dwl_title_limit", rename = "title-limit")]
+    pub title_limit: usize,
+
+    /// Shell command run when a tag is clicked; `{tag}` (1-based) and `{mask}` are substituted.
+    #[serde(default, rename = "tag-command")]
+    pub tag_command: String,
+}
+
+impl Default for DwlTagsConfig {
+    fn default() -> Self {
+        Self {
+            tag_names: default_dwl_tag_names(),
+            show_layout: true,
+            show_title: true,
+            title_limit: default_dwl_title_limit(),
+            tag_command: String::new(),
+        }
+    }
+}
+
+/// `pinned` launchers: desktop ids from `apps`, then the shared `nwg-pin-cache`.
+#[derive(Debug, Deserialize, Clone)]
+pub struct PinnedConfig {
+    /// Desktop entry ids, with or without the `.desktop` suffix.
+    #[serde(default)]
+    pub apps: Vec<String>,
+
+    #[serde(default = "default_true", rename = "use-pin-cache")]
+    pub use_pin_cache: bool,
+
+    #[serde(default = "default_image_size", rename = "icon-size")]
+    pub icon_size: i32,
+
+    #[serde(default, rename = "show-names")]
+    pub show_names: bool,
+}
+
+impl Default for PinnedConfig {
+    fn default() -> Self {
+        Self {
+            apps: Vec::new(),
+            use_pin_cache: true,
+            icon_size: default_image_size(),
+            show_names: false,
+        }
+    }
+}
+
+/// Upstream `brightness-slider` block, shown inline in the bar.
+#[derive(Debug, Deserialize, Clone)]
+pub struct BrightnessSliderConfig {
+    #[serde(default = "default_true", rename = "show-values")]
+    pub show_values: bool,
+
+    #[serde(default = "default_image_size", rename = "icon-size")]
+    pub icon_size: i32,
+
+    /// Seconds between brightness reads.
+    #[serde(default = "default_brightness_slider_interval")]
+    pub interval: u32,
+
+    #[serde(default = "default_brightness_slider_step", rename = "step-size")]
+    pub step_size: u32,
+
+    #[serde(default = "default_brightness_slider_width", rename = "slider-width")]
+    pub slider_width: i32,
+
+    #[serde(default = "default_brightness_slider_root_css", rename = "root-css-name")]
+    pub root_css_name: String,
+
+    #[serde(default = "default_brightness_slider_css", rename = "css-name")]
+    pub css_name: String,
+}
+
+impl Default for BrightnessSliderConfig {
+    fn default() -> Self {
+        Self {
+            show_values: true,
+            icon_size: default_image_size(),
+            interval: default_brightness_slider_interval(),
+            step_size: default_brightness_slider_step(),
+            slider_width: default_brightness_slider_width(),
+            root_css_name: default_brightness_slider_root_css(),
+            css_name: default_brightness_slider_css(),
+        }
+    }
+}
+
+/// Upstream `random-wallpaper` block; only the local source is supported.
+#[derive(Debug, Deserialize, Clone)]
+pub struct RandomWallpaperConfig {
+    /// Directory the images are picked from.
+    #[serde(default = "default_wallpaper_local_path", rename = "local-path")]
+    pub local_path: String,
+
+    /// Where "Save" copies the current image; empty hides the button.
+    #[serde(default = "default_wallpaper_save_path", rename = "save-path")]
+    pub save_path: String,
+
+    /// Run through `sh -c`; `{path}` becomes the shell-quoted image path.
+    #[serde(default = "default_wallpaper_command")]
+    pub command: String,
+
+    #[serde(default = "default_true", rename = "refresh-on-startup")]
+    pub refresh_on_startup: bool,
+
+    /// Minutes between automatic changes; 0 disables.
+    #[serde(default)]
+    pub interval: u64,
+
+    #[serde(default = "default_wallpaper_icon")]
+    pub icon: String,
+
+    #[serde(default = "default_image_size", rename = "icon-size")]
+    pub icon_size: i32,
+
+    #[serde(default = "default_wallpaper_preview_width", rename = "preview-width")]
+    pub preview_width: i32,
+}
+
+impl Default for RandomWallpaperConfig {
+    fn default() -> Self {
+        Self {
+            local_path: default_wallpaper_local_path(),
+            save_path: default_wallpaper_save_path(),
+            command: default_wallpaper_command(),
+            refresh_on_startup: true,
+            interval: 0,
+            icon: default_wallpaper_icon(),
+            icon_size: default_image_size(),
+            preview_width: default_wallpaper_preview_width(),
+        }
+    }
+}
+
+/// Upstream `openweather` block. The module itself needs the `openweather` cargo feature.
+#[derive(Debug, Deserialize, Clone)]
+pub struct OpenWeatherConfig {
+    #[serde(default)]
+    pub appid: String,
+
+    #[serde(default)]
+    pub lat: f64,
+
+    #[serde(default)]
+    pub long: f64,
+
+    #[serde(default = "default_openweather_lang")]
+    pub lang: String,
+
+    /// `metric`, `imperial` or `standard`.
+    #[serde(default = "default_openweather_units")]
+    pub units: String,
+
+    /// Seconds between fetches; cached responses younger than this are reused.
+    #[serde(default = "default_openweather_interval")]
+    pub interval: u64,
+
+    /// Overrides the location name from the API.
+    #[serde(default, rename = "loc-name")]
+    pub loc_name: String,
+
+    #[serde(default, rename = "show-name")]
+    pub show_name: bool,
+
+    #[serde(default = "default_image_size", rename = "icon-size")]
+    pub icon_size: i32,
+
+    #[serde(default = "default_openweather_popup_icon_size", rename = "popup-icon-size")]
+    pub popup_icon_size: i32,
+
+    /// API root; `weather` and `forecast` are appended. Point it at a stub server for testing.
+    #[serde(default = "default_openweather_base_url", rename = "base-url")]
+    pub base_url: String,
+
+    /// Connection timeout in seconds.
+    #[serde(default = "default_openweather_timeout", rename = "ow-timeout")]
+    pub timeout: u32,
+}
+
+impl Default for OpenWeatherConfig {
+    fn default() -> Self {
+        Self {
+            appid: String::new(),
+            lat: 0.0,
+            long: 0.0,
+            lang: default_openweather_lang(),
+            units: default_openweather_units(),
+            interval: default_openweather_interval(),
+            loc_name: String::new(),
+            show_name: false,
+            icon_size: default_image_size(),
+            popup_icon_size: default_openweather_popup_icon_size(),
+            base_url: default_openweather_base_url(),
+            timeout: default_openweather_timeout(),
+        }
+    }
+}
+
+/// `network` module. `format` placeholders: `{ifname}`, `{ip}`, `{ipv6}`, `{down}`, `{up}`,
+/// `{down_total}`, `{up_total}`.
+#[derive(Debug, Deserialize, Clone)]
+pub struct NetworkConfig {
+    /// Interface name, or a prefix ending in `*`; empty follows the default route.
+    #[serde(default)]
+    pub interface: String,
+
+    #[serde(default = "default_network_format")]
+    pub format: String,
+
+    #[serde(default = "default_network_format_disconnected", rename = "format-disconnected")]
+    pub format_disconnected: String,
+
+    #[serde(default = "default_true", rename = "show-icon")]
+    pub show_icon: bool,
+
+    #[serde(default = "default_network_icon_wired", rename = "icon-wired")]
+    pub icon_wired: String,
+
+    #[serde(default = "default_network_icon_wireless", rename = "icon-wireless")]
+    pub icon_wireless: String,
+
+    #[serde(default = "default_network_icon_disconnected", rename = "icon-disconnected")]
+    pub icon_disconnected: String,
+
+    #[serde(default = "default_image_size", rename = "icon-size")]
+    pub icon_size: i32,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            interface: String::new(),
+            format: default_network_format(),
+            format_disconnected: default_network_format_disconnected(),
+            show_icon: true,
+            icon_wired: default_network_icon_wired(),
+            icon_wireless: default_network_icon_wireless(),
+            icon_disconnected: default_network_icon_disconnected(),
+            icon_size: default_image_size(),
+        }
+    }
+}
+
+/// `cpu` module. `format` placeholders: `{usage}`.
+#[derive(Debug, Deserialize, Clone)]
+pub struct CpuConfig {
+    #[serde(default = "default_cpu_format")]
+    pub format: String,
+
+    #[serde(default)]
+    pub icon: String,
+
+    #[serde(default = "default_image_size", rename = "icon-size")]
+    pub icon_size: i32,
+
+    /// A small bar per core next to the total.
+    #[serde(default, rename = "per-core")]
+    pub per_core: bool,
+
+    #[serde(default)]
+    pub sparkline: bool,
+
+    /// Samples kept in the sparkline.
+    #[serde(default = "default_sparkline_length", rename = "sparkline-length")]
+    pub sparkline_length: usize,
+
+    #[serde(default = "default_sparkline_width", rename = "sparkline-width")]
+    pub sparkline_width: i32,
+}
+
+impl Default for CpuConfig {
+    fn default() -> Self {
+        Self {
+            format: default_cpu_format(),
+            icon: String::new(),
+            icon_size: default_image_size(),
+            per_core: false,
+            sparkline: false,
+            sparkline_length: default_sparkline_length(),
+            sparkline_width: default_sparkline_width(),
+        }
+    }
+}
+
+/// `memory` module. Placeholders: `{used}`, `{total}`, `{available}`, `{percent}`,
+/// `{swap_used}`.
+#[derive(Debug, Deserialize, Clone)]
+pub struct MemoryConfig {
+    #[serde(default = "default_memory_format")]
+    pub format: String,
+
+    #[serde(default)]
+    pub icon: String,
+
+    #[serde(default = "default_image_size", rename = "icon-size")]
+    pub icon_size: i32,
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self {
+            format: default_memory_format(),
+            icon: String::new(),
+            icon_size: default_image_size(),
+        }
+    }
+}
+
+/// `disk` module; `format` is repeated for each mount. Placeholders: `{mount}`, `{used}`,
+/// `{free}`, `{total}`, `{percent}`.
+#[derive(Debug, Deserialize, Clone)]
+pub struct DiskConfig {
+    #[serde(default = "default_disk_format")]
+    pub format: String,
+
+    #[serde(default)]
+    pub icon: String,
+
+    #[serde(default = "default_image_size", rename = "icon-size")]
+    pub icon_size: i32,
+
+    #[serde(default = "default_disk_mounts")]
+    pub mounts: Vec<String>,
+}
+
+impl Default for DiskConfig {
+    fn default() -> Self {
+        Self {
+            format: default_disk_format(),
+            icon: String::new(),
+            icon_size: default_image_size(),
+            mounts: default_disk_mounts(),
+        }
+    }
+}
+
+/// `temperature` module. Placeholders: `{temp}` (°C), `{sensor}`.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TemperatureConfig {
+    #[serde(default = "default_temperature_format")]
+    pub format: String,
+
+    #[serde(default)]
+    pub icon: String,
+
+    #[serde(default = "default_image_size", rename = "icon-size")]
+    pub icon_size: i32,
+
+    /// Substring of the sensor name (`coretemp/Package id 0`, `thermal/x86_pkg_temp`, ...);
+    /// empty picks the CPU package sensor.
+    #[serde(default)]
+    pub sensor: String,
+
+    /// °C at which the module gets the `critical` class; 0 uses the sensor's own limit.
+    #[serde(default)]
+    pub critical: f64,
+}
+
+impl Default for TemperatureConfig {
+    fn default() -> Self {
+        Self {
+            format: default_temperature_format(),
+            icon: String::new(),
+            icon_size: default_image_size(),
+            sensor: String::new(),
+            critical: 0.0,
+        }
+    }
+}
+
+/// Upstream `playerctl` block.
+#[derive(Debug, Deserialize, Clone)]
+pub struct PlayerctlConfig {
+    /// `left` or `right` of the track label.
+    #[serde(default = "default_playerctl_buttons_position", rename = "buttons-position")]
+    pub buttons_position: String,
+
+    #[serde(default = "default_image_size", rename = "icon-size")]
+    pub icon_size: i32,
+
+    /// Longest track text shown; longer text scrolls (or is cut when `scroll` is off).
+    #[serde(default = "default_playerctl_chars")]
+    pub chars: usize,
+
+    #[serde(default = "default_true")]
+    pub scroll: bool,
+
+    /// Cover art size in the popover.
+    #[serde(default = "default_playerctl_art_size", rename = "art-size")]
+    pub art_size: i32,
+
+    #[serde(default, rename = "button-css-name")]
+    pub button_css_name: String,
+
+    #[serde(default, rename = "label-css-name")]
+    pub label_css_name: String,
+}
+
+impl Default for PlayerctlConfig {
+    fn default() -> Self {
+        Self {
+            buttons_position: default_playerctl_buttons_position(),
+            icon_size: default_image_size(),
+            chars: default_playerctl_chars(),
+            scroll: true,
+            art_size: default_playerctl_art_size(),
+            button_css_name: String::new(),
+            label_css_name: String::new(),
+        }
+    }
+}
+
+/// Upstream `menu-start-settings` block.
+#[derive(Debug, Deserialize, Clone)]
+pub struct MenuStartConfig {
+    /// Theme icon name or absolute path for the button.
+    #[serde(default = "default_menu_start_icon")]
+    pub icon: String,
+
+    #[serde(default)]
+    pub label: String,
+
+    /// Run this instead of opening the built-in menu (e.g. `nwg-drawer`).
+    #[serde(default, rename = "launcher-command")]
+    pub launcher_command: String,
+
+    #[serde(default = "default_image_size", rename = "icon-size-button")]
+    pub icon_size_button: i32,
+
+    #[serde(default = "default_menu_start_icon_size_large", rename = "icon-size-large")]
+    pub icon_size_large: i32,
+
+    #[serde(default = "default_menu_start_width")]
+    pub width: i32,
+
+    #[serde(default = "default_menu_start_height")]
+    pub height: i32,
+
+    /// Power buttons; an empty command hides the button.
+    #[serde(default = "default_cmd_lock", rename = "cmd-lock")]
+    pub cmd_lock: String,
+
+    #[serde(default = "default_cmd_logout", rename = "cmd-logout")]
+    pub cmd_logout: String,
+
+    #[serde(default = "default_cmd_restart", rename = "cmd-restart")]
+    pub cmd_restart: String,
+
+    #[serde(default = "default_cmd_shutdown", rename = "cmd-shutdown")]
+    pub cmd_shutdown: String,
+}
+
+impl Default for MenuStartConfig {
+    fn default() -> Self {
+        Self {
+            icon: default_menu_start_icon(),
+            label: String::new(),
+            launcher_command: String::new(),
+            icon_size_button: default_image_size(),
+            icon_size_large: default_menu_start_icon_size_large(),
+            width: default_menu_start_width(),
+            height: default_menu_start_height(),
+            cmd_lock: default_cmd_lock(),
+            cmd_logout: default_cmd_logout(),
+            cmd_restart: default_cmd_restart(),
+            cmd_shutdown: default_cmd_shutdown(),
+        }
+    }
+}
+
+/// Upstream `hyprland-taskbar` block.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TaskbarConfig {
+    /// Show windows from every output instead of only the panel's monitor.
+    #[serde(default, rename = "all-outputs")]
+    pub all_outputs: bool,
+
+    #[serde(default = "default_true", rename = "show-app-icon")]
+    pub show_app_icon: bool,
+
+    #[serde(default = "default_true", rename = "show-app-name")]
+    pub show_app_name: bool,
+
+    #[serde(default = "default_taskbar_name_max_len", rename = "name-max-len")]
+    pub name_max_len: usize,
+
+    #[serde(default = "default_image_size", rename = "image-size")]
+    pub image_size: i32,
+
+    #[serde(default, rename = "workspaces-spacing")]
+    pub workspaces_spacing: i32,
+
+    #[serde(default, rename = "task-padding")]
+    pub task_padding: i32,
+
+    #[serde(default = "default_true", rename = "mark-xwayland")]
+    pub mark_xwayland: bool,
+
+    /// Text angle on vertical panels; 90/270 stack the tasks vertically instead.
+    #[serde(default)]
+    pub angle: f64,
+
+    /// Task order: `workspace`, `open-time`, `app` or `manual` (drag tasks to reorder).
+    #[serde(default = "default_taskbar_sort_by", rename = "sort-by")]
+    pub sort_by: String,
+
+    /// Collapse windows of the same app into one button with a count.
+    #[serde(default, rename = "group-apps")]
+    pub group_apps: bool,
+}
+
+impl Default for TaskbarConfig {
+    fn default() -> Self {
+        Self {
+            all_outputs: false,
+            show_app_icon: true,
+            show_app_name: true,
+            name_max_len: default_taskbar_name_max_len(),
+            image_size: default_image_size(),
+            workspaces_spacing: 0,
+            task_padding: 0,
+            mark_xwayland: true,
+            angle: 0.0,
+            sort_by: default_taskbar_sort_by(),
+            group_apps: false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
 #[allow(dead_code)]
 pub struct HyprlandWorkspacesConfig {
     #[serde(default = "default_num_ws")]
@@ -119,11 +772,76 @@ pub struct HyprlandWorkspacesConfig {
     
     #[serde(default = "default_true")]
     pub mark_xwayland: bool,
+
+    #[serde(default)]
+    pub show_window_icons: bool,
+
+    /// Show Hyprland special (scratchpad) workspaces as buttons after the regular ones.
+    #[serde(default)]
+    pub show_special: bool,
+
+    /// Show the name of the special workspace open on this panel's monitor.
+    #[serde(default = "default_true")]
+    pub show_special_indicator: bool,
+
+    /// Switch workspaces with the scroll wheel.
+    #[serde(default = "default_true")]
+    pub scroll_switch: bool,
+
+    /// Which workspaces scrolling cycles through: `all` (the shown buttons), `existing`
+    /// or `monitor` (existing ones on this panel's monitor).
+    #[serde(default = "default_scroll_mode")]
+    pub scroll_mode: String,
+
+    #[serde(default)]
+    pub scroll_wrap: bool,
+
+    /// Minimum time between two scroll-triggered switches.
+    #[serde(default = "default_scroll_debounce_ms")]
+    pub scroll_debounce_ms: u64,
+
+    /// Middle/right click on a workspace button: `focus`, `movetoworkspace`,
+    /// `movetoworkspacesilent`, or a shell command (`{id}` is replaced).
+    #[serde(default)]
+    pub middle_click: String,
+
+    #[serde(default)]
+    pub right_click: String,
     
     #[serde(default)]
     pub angle: f64,
 }
 
+impl Default for HyprlandWorkspacesConfig {
+    fn default() -> Self {
+        Self {
+            num_ws: default_num_ws(),
+            show_icon: true,
+            show_inactive_workspaces: true,
+            show_workspaces_from_all_outputs: true,
+            image_size: default_image_size(),
+            show_workspaces: true,
+            show_name: true,
+            name_length: default_name_length(),
+            show_empty: true,
+            mark_content: true,
+            show_names: true,
+            mark_floating: true,
+            mark_xwayland: true,
+            show_window_icons: false,
+            show_special: false,
+            show_special_indicator: true,
+            scroll_switch: true,
+            scroll_mode: default_scroll_mode(),
+            scroll_wrap: false,
+            scroll_debounce_ms: default_scroll_debounce_ms(),
+            middle_click: String::new(),
+            right_click: String::new(),
+            angle: 0.0,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[allow(dead_code)]
 pub struct HyprWorkspaceRule {
@@ -135,8 +853,56 @@ fn default_true() -> bool { true }
 fn default_num_ws() -> usize { 10 }
 fn default_image_size() -> i32 { 16 }
 fn default_name_length() -> usize { 40 }
+fn default_taskbar_name_max_len() -> usize { 24 }
+fn default_taskbar_sort_by() -> String { "workspace".to_string() }
+fn default_scroll_mode() -> String { "all".to_string() }
+fn default_scroll_debounce_ms() -> u64 { 200 }
+fn default_dwl_tag_names() -> Vec<String> { (1..=9).map(|i| i.to_string()).collect() }
+fn default_dwl_title_limit() -> usize { 55 }
 fn default_clock_format() -> String { "%H:%M".to_string() }
 fn default_clock_interval() -> u32 { 1 }
+fn default_clock_tooltip_format() -> String { "%A, %e %B %Y".to_string() }
+fn default_clock_time_zone_format() -> String { "%H:%M %Z".to_string() }
+fn default_brightness_slider_interval() -> u32 { 10 }
+fn default_brightness_slider_step() -> u32 { 1 }
+fn default_brightness_slider_width() -> i32 { 100 }
+fn default_brightness_slider_root_css() -> String { "brightness-module".to_string() }
+fn default_brightness_slider_css() -> String { "brightness-slider".to_string() }
+fn default_wallpaper_local_path() -> String { "~/Pictures/Wallpapers".to_string() }
+fn default_wallpaper_save_path() -> String { "~/Pictures/Wallpapers/saved".to_string() }
+fn default_wallpaper_command() -> String { "exec swaybg -m fill -i {path}".to_string() }
+fn default_wallpaper_icon() -> String { "preferences-desktop-wallpaper-symbolic".to_string() }
+fn default_wallpaper_preview_width() -> i32 { 320 }
+fn default_openweather_lang() -> String { "en".to_string() }
+fn default_openweather_units() -> String { "metric".to_string() }
+fn default_openweather_interval() -> u64 { 1800 }
+fn default_openweather_popup_icon_size() -> i32 { 24 }
+fn default_openweather_base_url() -> String { "https://api.openweathermap.org/data/2.5".to_string() }
+fn default_openweather_timeout() -> u32 { 10 }
+fn default_network_format() -> String { "↓{down} ↑{up}".to_string() }
+fn default_network_format_disconnected() -> String { "Disconnected".to_string() }
+fn default_network_icon_wired() -> String { "network-wired-symbolic".to_string() }
+fn default_network_icon_wireless() -> String { "network-wireless-symbolic".to_string() }
+fn default_network_icon_disconnected() -> String { "network-offline-symbolic".to_string() }
+fn default_cpu_format() -> String { "{usage}%".to_string() }
+fn default_sparkline_length() -> usize { 30 }
+fn default_sparkline_width() -> i32 { 48 }
+fn default_memory_format() -> String { "{used}/{total}".to_string() }
+fn default_disk_format() -> String { "{mount} {free}".to_string() }
+fn default_disk_mounts() -> Vec<String> { vec!["/".to_string()] }
+fn default_temperature_format() -> String { "{temp}°C".to_string() }
+fn default_playerctl_buttons_position() -> String { "left".to_string() }
+fn default_playerctl_chars() -> usize { 30 }
+fn default_playerctl_art_size() -> i32 { 128 }
+fn default_menu_start() -> String { "off".to_string() }
+fn default_menu_start_icon() -> String { "view-app-grid-symbolic".to_string() }
+fn default_menu_start_icon_size_large() -> i32 { 32 }
+fn default_menu_start_width() -> i32 { 300 }
+fn default_menu_start_height() -> i32 { 400 }
+fn default_cmd_lock() -> String { "loginctl lock-session".to_string() }
+fn default_cmd_logout() -> String { "loginctl terminate-session \"$XDG_SESSION_ID\"".to_string() }
+fn default_cmd_restart() -> String { "systemctl reboot".to_string() }
+fn default_cmd_shutdown() -> String { "systemctl -i poweroff".to_string() }
 fn default_layer() -> String { "bottom".to_string() }
 fn default_position() -> String { "top".to_string() }
 fn default_exclusive_zone() -> bool { true }
@@ -170,3 +936,41 @@ fn default_controls_components() -> Vec<String> {
 fn default_icon_size() -> i32 { 16 }
 
 fn default_interval() -> u32 { 1 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_workspaces_block_uses_serde_defaults() {
+        let panels: Vec<PanelConfig> = serde_json::from_str(r#"[{"name": "panel-top"}]"#).unwrap();
+        let ws = &panels[0].sway_workspaces;
+        assert!(ws.show_workspaces);
+        assert!(ws.show_icon);
+        assert_eq!(ws.num_ws, 10);
+        assert!(ws.scroll_switch);
+        assert_eq!(ws.scroll_debounce_ms, 200);
+        assert_eq!(ws.scroll_mode, "all");
+    }
+
+    #[test]
+    fn partial_workspaces_block_keeps_other_defaults() {
+        let panels: Vec<PanelConfig> =
+            serde_json::from_str(r#"[{"hyprland-workspaces": {"scroll_wrap": true}}]"#).unwrap();
+        let ws = &panels[0].hyprland_workspaces;
+        assert!(ws.scroll_wrap);
+        assert!(ws.scroll_switch);
+        assert_eq!(ws.scroll_debounce_ms, 200);
+    }
+
+    #[test]
+    fn missing_clock_block_matches_an_empty_one() {
+        let panels: Vec<PanelConfig> = serde_json::from_str(r#"[{}, {"clock": {}}]"#).unwrap();
+        let (missing, empty) = (&panels[0].clock, &panels[1].clock);
+        assert_eq!(missing.format, empty.format);
+        assert_eq!(missing.interval, empty.interval);
+        assert_eq!(missing.tooltip_format, empty.tooltip_format);
+        assert_eq!(missing.time_zone_format, empty.time_zone_format);
+        assert!(!missing.tooltip_format.is_empty());
+    }
+}
@@ -329,7 +329,7 @@ impl ControlsUi {
         popover
     }
 
-    fn set_brightness(value: i32) -> Result<()> {
+    pub fn set_brightness(value: i32) -> Result<()> {
         let v = value.clamp(0, 100);
         // Prefer `light` if available
         if Command::new("light")
@@ -406,7 +406,7 @@ impl ControlsUi {
         }
     }
 
-    fn get_brightness() -> Result<i32> {
+    pub fn get_brightness() -> Result<i32> {
         // Try light command first
         if let Ok(output) = Command::new("light")
             .arg("-G")
@@ -444,9 +444,9 @@ impl ControlsUi {
                 }
             }
         }
-        
-        // Fallback to a default value
-        Ok(50)
+
+        // The controls popover falls back to 50%; the brightness slider goes insensitive.
+        anyhow::bail!("Neither light nor brightnessctl reported a brightness")
     }
 
     fn get_volume() -> Result<(i32, bool)> {
@@ -654,7 +654,7 @@ impl ControlsUi {
         }
     }
 
-    fn brightness_icon_name(value: i32) -> String {
+    pub fn brightness_icon_name(value: i32) -> String {
         if value > 70 {
             "display-brightness-high-symbolic".to_string()
         } else if value > 30 {
@@ -0,0 +1,130 @@
+use super::dwl_ipc::dwl_ipc_set_tags;
+use super::hyprland::AppMsg;
+use anyhow::Context;
+use crossbeam_channel as cb;
+use std::collections::HashMap;
+use std::io::{BufRead, IsTerminal};
+use std::thread;
+
+/// Tags are a `u32` bitmask in both the status stream and dwl-ipc.
+pub const MAX_TAGS: usize = 32;
+
+/// Per-output state from dwl's status stream.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DwlOutput {
+    pub name: String,
+    pub selected_monitor: bool,
+    pub occupied: u32,
+    pub selected: u32,
+    pub urgent: u32,
+    pub layout: String,
+    pub title: String,
+    pub appid: String,
+}
+
+impl DwlOutput {
+    /// Apply one `<output> <key> <value...>` line (the output name already stripped).
+    fn apply(&mut self, key: &str, value: &str) -> bool {
+        match key {
+            "selmon" => self.selected_monitor = value.trim() == "1",
+            "title" => self.title = value.to_string(),
+            "appid" => self.appid = value.to_string(),
+            "layout" => self.layout = value.to_string(),
+            "tags" => {
+                // occupied selected client-tags urgent
+                let masks: Vec<u32> = value
+                    .split_whitespace()
+                    .filter_map(|v| v.parse().ok())
+                    .collect();
+                if masks.len() < 4 {
+                    return false;
+                }
+                self.occupied = masks[0];
+                self.selected = masks[1];
+                self.urgent = masks[3];
+            }
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Parse dwl's status lines into per-output state.
+#[derive(Debug, Default)]
+pub struct DwlStatusParser {
+    outputs: HashMap<String, DwlOutput>,
+}
+
+impl DwlStatusParser {
+    /// Feed one line; returns true when it changed any output.
+    pub fn feed(&mut self, line: &str) -> bool {
+        let mut parts = line.splitn(3, ' ');
+        let (output, key) = match (parts.next(), parts.next()) {
+            (Some(o), Some(k)) if !o.is_empty() => (o, k),
+            _ => return false,
+        };
+        let value = parts.next().unwrap_or("");
+        let entry = self
+            .outputs
+            .entry(output.to_string())
+            .or_insert_with(|| DwlOutput {
+                name: output.to_string(),
+                ..Default::default()
+            });
+        entry.apply(key, value)
+    }
+
+    pub fn outputs(&self) -> Vec<DwlOutput> {
+        let mut outputs: Vec<DwlOutput> = self.outputs.values().cloned().collect();
+        outputs.sort_by(|a, b| a.name.cmp(&b.name));
+        outputs
+    }
+}
+
+/// Read dwl's status stream from stdin (as set up by `dwl -s nwg-panel-rs`).
+///
+/// Only started when stdin is not a terminal. Once started, stdin is always drained so
+/// dwl never blocks on a full pipe, even if no panel shows `dwl-tags`.
+pub fn spawn_dwl_status_reader(sender: cb::Sender<AppMsg>) {
+    if std::io::stdin().is_terminal() {
+        return;
+    }
+
+    thread::spawn(move || {
+        let stdin = std::io::stdin();
+        let mut parser = DwlStatusParser::default();
+        for line in stdin.lock().lines() {
+            let line = match line {
+                Ok(l) => l,
+                Err(_) => break,
+            };
+            // dwl prints `layout` last for each output; push a full update at that point.
+            let is_layout = line.split(' ').nth(1) == Some("layout");
+            if parser.feed(&line) && is_layout {
+                let _ = sender.send(AppMsg::DwlStatus(parser.outputs()));
+            }
+        }
+    });
+}
+
+/// View tag `tag` (0-based) on `output` (dwl's selected output if empty): natively via
+/// dwl-ipc when dwl offers it, otherwise through `command` with `{tag}` (1-based) and
+/// `{mask}` substituted.
+pub fn dwl_view_tag(command: &str, output: &str, tag: usize) -> anyhow::Result<()> {
+    let mask = u32::try_from(tag)
+        .ok()
+        .and_then(|t| 1u32.checked_shl(t))
+        .with_context(|| format!("dwl has at most {MAX_TAGS} tags"))?;
+    let native_err = match dwl_ipc_set_tags(output, mask) {
+        Ok(()) => return Ok(()),
+        Err(err) => err,
+    };
+    if command.trim().is_empty() {
+        return Err(native_err);
+    }
+    let command = command
+        .replace("{tag}", &(tag + 1).to_string())
+        .replace("{mask}", &mask.to_string());
+    std::process::Command::new("sh").args(["-c", &command]).spawn()?;
+    Ok(())
+}
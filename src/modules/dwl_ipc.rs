@@ -0,0 +1,186 @@
+use anyhow::Context;
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use wayland_client::globals::{registry_queue_init, GlobalListContents};
+use wayland_client::protocol::wl_output::{self, WlOutput};
+use wayland_client::protocol::wl_registry::{self, WlRegistry};
+use wayland_client::{Connection, Dispatch, EventQueue, Proxy, QueueHandle};
+
+/// Bindings generated from `protocols/dwl-ipc-unstable-v2.xml` (dwl's `ipc` patch).
+#[allow(dead_code, non_camel_case_types, non_upper_case_globals, unused_imports, clippy::all)]
+mod protocol {
+    use wayland_client;
+    use wayland_client::protocol::*;
+
+    pub mod __interfaces {
+        use wayland_client::backend as wayland_backend;
+        use wayland_client::protocol::__interfaces::*;
+        wayland_scanner::generate_interfaces!("protocols/dwl-ipc-unstable-v2.xml");
+    }
+    use self::__interfaces::*;
+
+    wayland_scanner::generate_client_code!("protocols/dwl-ipc-unstable-v2.xml");
+}
+
+use protocol::zdwl_ipc_manager_v2::{self, ZdwlIpcManagerV2};
+use protocol::zdwl_ipc_output_v2::{self, ZdwlIpcOutputV2};
+
+#[derive(Debug, Clone, Default)]
+struct OutputInfo {
+    name: String,
+    /// dwl's selected monitor.
+    active: bool,
+}
+
+/// Output state by `wl_output` protocol id, shared with the dispatch thread.
+type Outputs = Arc<Mutex<HashMap<u32, OutputInfo>>>;
+
+/// The dwl-ipc connection: one `zdwl_ipc_output_v2` per output present at startup.
+struct DwlIpc {
+    conn: Connection,
+    handles: HashMap<u32, ZdwlIpcOutputV2>,
+    outputs: Outputs,
+}
+
+/// `None` when the compositor doesn't offer dwl-ipc; set up on the first tag click.
+static DWL_IPC: OnceLock<Option<DwlIpc>> = OnceLock::new();
+
+struct DwlIpcState {
+    outputs: Outputs,
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for DwlIpcState {
+    fn event(
+        _: &mut Self,
+        _: &WlRegistry,
+        _: wl_registry::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<WlOutput, ()> for DwlIpcState {
+    fn event(
+        state: &mut Self,
+        output: &WlOutput,
+        event: wl_output::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let wl_output::Event::Name { name } = event {
+            if let Ok(mut outputs) = state.outputs.lock() {
+                outputs.entry(output.id().protocol_id()).or_default().name = name;
+            }
+        }
+    }
+}
+
+impl Dispatch<ZdwlIpcManagerV2, ()> for DwlIpcState {
+    fn event(
+        _: &mut Self,
+        _: &ZdwlIpcManagerV2,
+        _: zdwl_ipc_manager_v2::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+/// User data: the protocol id of the `wl_output` the handle was created for.
+impl Dispatch<ZdwlIpcOutputV2, u32> for DwlIpcState {
+    fn event(
+        state: &mut Self,
+        _: &ZdwlIpcOutputV2,
+        event: zdwl_ipc_output_v2::Event,
+        output_id: &u32,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        // Tags, title and layout come from the status stream; only the selected output matters here.
+        if let zdwl_ipc_output_v2::Event::Active { active } = event {
+            if let Ok(mut outputs) = state.outputs.lock() {
+                outputs.entry(*output_id).or_default().active = active != 0;
+            }
+        }
+    }
+}
+
+fn connect() -> anyhow::Result<(DwlIpc, EventQueue<DwlIpcState>, DwlIpcState)> {
+    let conn = Connection::connect_to_env().context("Failed to connect to the Wayland display")?;
+    let (globals, mut queue) = registry_queue_init::<DwlIpcState>(&conn)?;
+    let qh = queue.handle();
+    let manager: ZdwlIpcManagerV2 = globals
+        .bind(&qh, 1..=1, ())
+        .context("Compositor has no zdwl_ipc_manager_v2")?;
+
+    let wl_outputs: Vec<(u32, u32)> = globals.contents().with_list(|list| {
+        list.iter()
+            .filter(|g| g.interface == "wl_output")
+            .map(|g| (g.name, g.version))
+            .collect()
+    });
+    let outputs: Outputs = Arc::default();
+    let mut handles = HashMap::new();
+    for (name, version) in wl_outputs {
+        // Output names need wl_output v4.
+        let output = globals.registry().bind::<WlOutput, _, _>(name, version.min(4), &qh, ());
+        let id = output.id().protocol_id();
+        handles.insert(id, manager.get_output(&output, &qh, id));
+    }
+
+    // Output names and the initial `active` events.
+    let mut state = DwlIpcState { outputs: outputs.clone() };
+    queue.roundtrip(&mut state)?;
+    Ok((DwlIpc { conn, handles, outputs }, queue, state))
+}
+
+/// Connect on a thread that then keeps dispatching, so dwl's events never pile up on the
+/// socket.
+fn start() -> Option<DwlIpc> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let (ipc, mut queue, mut state) = match connect() {
+            Ok(parts) => parts,
+            Err(err) => {
+                eprintln!("dwl-ipc: not available, using tag-command: {err:#}");
+                let _ = tx.send(None);
+                return;
+            }
+        };
+        let _ = tx.send(Some(ipc));
+        loop {
+            if let Err(err) = queue.blocking_dispatch(&mut state) {
+                eprintln!("dwl-ipc: connection lost: {err}");
+                return;
+            }
+        }
+    });
+    rx.recv().ok().flatten()
+}
+
+/// Show the tags in `mask` on the dwl output named `output`, or on dwl's selected output
+/// when `output` is empty.
+pub fn dwl_ipc_set_tags(output: &str, mask: u32) -> anyhow::Result<()> {
+    let ipc = DWL_IPC
+        .get_or_init(start)
+        .as_ref()
+        .context("dwl-ipc is not available")?;
+    let id = {
+        let outputs = ipc.outputs.lock().map_err(|_| anyhow::anyhow!("dwl-ipc state poisoned"))?;
+        outputs
+            .iter()
+            .find(|(_, o)| if output.is_empty() { o.active } else { o.name == output })
+            .map(|(id, _)| *id)
+            .with_context(|| format!("Unknown dwl output '{output}'"))?
+    };
+    let handle = ipc.handles.get(&id).context("Output appeared after dwl-ipc was set up")?;
+    handle.set_tags(mask, 0);
+    ipc.conn.flush()?;
+    Ok(())
+}
@@ -1,16 +1,18 @@
 use anyhow::Context;
 use hyprlang::Hyprland;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use crate::modules::config::HyprWorkspaceRule;
 
 pub struct HyprConfig {
     hyprland: Hyprland,
+    path: Option<PathBuf>,
 }
 
 impl Clone for HyprConfig {
     fn clone(&self) -> Self {
         Self {
             hyprland: Hyprland::new(),
+            path: self.path.clone(),
         }
     }
 }
@@ -19,6 +21,7 @@ impl HyprConfig {
     pub fn new() -> Self {
         Self {
             hyprland: Hyprland::new(),
+            path: None,
         }
     }
 
@@ -38,6 +41,9 @@ impl HyprConfig {
 
         for config_path in &config_paths {
             if config_path.exists() {
+                // Remember the path even if hyprlang rejects something; workspace rules are
+                // read from the raw file.
+                self.path = Some(config_path.to_path_buf());
                 self.hyprland.parse_file(config_path)
                     .with_context(|| format!("Failed to parse Hyprland config: {}", config_path.display()))?;
                 return Ok(());
@@ -47,23 +53,12 @@ impl HyprConfig {
         anyhow::bail!("No Hyprland config file found");
     }
 
+    /// Collect `workspace = N, monitor:X, ...` rules from the config (following `source =` includes).
     pub fn get_workspace_rules(&self) -> Vec<HyprWorkspaceRule> {
         let mut rules = Vec::new();
-        
-        // Get workspace rules from Hyprland config
-        for workspace in self.hyprland.all_monitors() {
-            // Parse monitor workspace rules like "monitor =,preferred,auto,1"
-            if let Some(parts) = workspace.split(',').collect::<Vec<&str>>().get(1..=3) {
-                if parts.len() >= 3 {
-                    let rule = HyprWorkspaceRule {
-                        workspace_string: parts.get(2).unwrap_or(&"1").to_string(),
-                        monitor: workspace.split(',').next().unwrap_or("").to_string(),
-                    };
-                    rules.push(rule);
-                }
-            }
+        if let Some(path) = self.path.as_ref() {
+            collect_workspace_rules(path, &mut rules, 0);
         }
-
         rules
     }
 
@@ -94,3 +89,59 @@ impl Default for HyprConfig {
         Self::new()
     }
 }
+
+fn collect_workspace_rules(path: &Path, rules: &mut Vec<HyprWorkspaceRule>, depth: usize) {
+    // Guard against include cycles.
+    if depth > 8 {
+        return;
+    }
+    let text = match std::fs::read_to_string(path) {
+        Ok(t) => t,
+        Err(_) => return,
+    };
+
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        let (key, value) = match line.split_once('=') {
+            Some((k, v)) => (k.trim(), v.trim()),
+            None => continue,
+        };
+
+        match key {
+            "source" => {
+                let included = expand_home(value);
+                let included = if included.is_relative() {
+                    path.parent().map(|p| p.join(&included)).unwrap_or(included)
+                } else {
+                    included
+                };
+                collect_workspace_rules(&included, rules, depth + 1);
+            }
+            "workspace" => {
+                if let Some(rule) = parse_workspace_rule(value) {
+                    rules.push(rule);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn parse_workspace_rule(value: &str) -> Option<HyprWorkspaceRule> {
+    let mut parts = value.split(',').map(str::trim);
+    let workspace_string = parts.next()?.to_string();
+    let monitor = parts
+        .find_map(|p| p.strip_prefix("monitor:"))
+        .map(|m| m.trim().to_string())?;
+    Some(HyprWorkspaceRule {
+        workspace_string,
+        monitor,
+    })
+}
+
+fn expand_home(value: &str) -> PathBuf {
+    match value.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+        None => PathBuf::from(value),
+    }
+}
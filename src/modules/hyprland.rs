@@ -1,15 +1,24 @@
 use serde::Deserialize;
-use hyprland::data::{Workspace, Client, Workspaces, Clients};
+use hyprland::data::{Workspace, Client, Workspaces, Clients, Monitors};
 use hyprland::dispatch::{Dispatch, DispatchType};
 use hyprland::event_listener::EventListener;
 use hyprland::shared::{Address, HyprData, HyprDataActive, HyprDataActiveOptional};
+use std::collections::HashMap;
 use std::thread;
 use crossbeam_channel as cb;
+use super::dwl::DwlOutput;
+use super::mpris::MprisPlayer;
+use super::network_stats::NetworkSample;
+use super::system_stats::SystemSample;
 
 #[derive(Debug, Deserialize, Clone)]
 #[allow(dead_code)]
 pub struct HyprWorkspace {
     pub id: i32,
+    /// Number shown on the button. Equal to `id` on Hyprland; 0 for named sway
+    /// workspaces, which show their name instead.
+    #[serde(default)]
+    pub idx: i32,
     pub name: String,
     pub monitor: String,
     pub windows: i32,
@@ -23,6 +32,7 @@ impl From<Workspace> for HyprWorkspace {
     fn from(workspace: Workspace) -> Self {
         Self {
             id: workspace.id,
+            idx: workspace.id,
             name: workspace.name,
             monitor: workspace.monitor,
             windows: workspace.windows as i32,
@@ -99,11 +109,28 @@ pub enum AppMsg {
     HyprClients {
         clients: Vec<HyprClient>,
     },
+    /// Open special workspace per monitor name (empty when none is shown).
+    HyprSpecialWorkspaces(HashMap<String, String>),
+    /// A window asked for attention; cleared in the UI when it gets focus.
+    UrgentWindow(String),
+    /// Every window that currently has the urgent hint (sway, niri); replaces the UI's set.
+    UrgentWindows(Vec<String>),
+    SwayMode(String),
+    SwayScratchpad {
+        clients: Vec<HyprClient>,
+    },
+    DwlStatus(Vec<DwlOutput>),
     TrayItemsChanged(Vec<TrayItem>),
     TrayIconUpdated {
         item: TrayItem,
         icon: TrayIconPayload,
     },
+    /// All MPRIS players on the session bus, sorted by bus name.
+    MprisPlayers(Vec<MprisPlayer>),
+    /// CPU/memory/disk/temperature reading from the shared sampler.
+    SystemStats(SystemSample),
+    /// Interface counters and state from the network sampler.
+    Network(NetworkSample),
 }
 
 pub fn spawn_hyprland_poller(sender: cb::Sender<AppMsg>) {
@@ -134,6 +161,18 @@ pub fn spawn_hyprland_poller(sender: cb::Sender<AppMsg>) {
                     active_id: workspace_id.id,
                 });
             }
+            send_hyprland_special_workspaces(&sender2);
+        });
+
+        let sender_urgent = sender.clone();
+        event_listener.add_urgent_state_changed_handler(move |address| {
+            let _ = sender_urgent.send(AppMsg::UrgentWindow(address.to_string()));
+        });
+
+        // Special workspaces are toggled without a regular workspace change
+        let sender_special = sender.clone();
+        event_listener.add_changed_special_handler(move |_| {
+            send_hyprland_special_workspaces(&sender_special);
         });
         
         // Handle window open/close events
@@ -189,6 +228,41 @@ pub fn send_hyprland_snapshot(sender: &cb::Sender<AppMsg>) {
         let clients_vec: Vec<HyprClient> = clients.into_iter().map(|c| c.into()).collect();
         let _ = sender.send(AppMsg::HyprClients { clients: clients_vec });
     }
+
+    send_hyprland_special_workspaces(sender);
+}
+
+fn send_hyprland_special_workspaces(sender: &cb::Sender<AppMsg>) {
+    if let Ok(monitors) = Monitors::get() {
+        let open: HashMap<String, String> = monitors
+            .into_iter()
+            .map(|m| (m.name, m.special_workspace.name))
+            .collect();
+        let _ = sender.send(AppMsg::HyprSpecialWorkspaces(open));
+    }
+}
+
+/// Name to pass to `togglespecialworkspace` for a special workspace (`special:<name>`, or the
+/// unnamed `special`), or `None` for a regular workspace.
+pub fn special_workspace_name(name: &str) -> Option<&str> {
+    if name == "special" {
+        return Some(name);
+    }
+    name.strip_prefix("special:")
+}
+
+/// Ids of the workspaces scroll and key switching cycle through, in button order: by
+/// displayed number (niri ids aren't ordered), named workspaces last. Placeholders for
+/// persistent workspaces and special workspaces are skipped; `monitor` limits it to one output.
+pub fn workspace_nav_order(workspaces: &[HyprWorkspace], monitor: Option<&str>) -> Vec<i32> {
+    let mut existing: Vec<&HyprWorkspace> = workspaces
+        .iter()
+        .filter(|w| !w.ispersistent && w.id > 0 && monitor.is_none_or(|m| w.monitor == m))
+        .collect();
+    existing.sort_by_key(|w| (w.idx == 0, w.idx, w.id));
+    let mut ids: Vec<i32> = existing.iter().map(|w| w.id).collect();
+    ids.dedup();
+    ids
 }
 
 
@@ -198,12 +272,92 @@ pub fn hyprctl_dispatch_workspace(id: i32) -> anyhow::Result<()> {
     Ok(())
 }
 
+pub fn hyprctl_toggle_special_workspace(name: &str) -> anyhow::Result<()> {
+    Dispatch::call(DispatchType::ToggleSpecialWorkspace(Some(name.to_string())))?;
+    Ok(())
+}
+
+pub fn hyprctl_dispatch_move_active_to_workspace(id: i32, silent: bool) -> anyhow::Result<()> {
+    let workspace_id = hyprland::dispatch::WorkspaceIdentifierWithSpecial::Id(id);
+    if silent {
+        Dispatch::call(DispatchType::MoveToWorkspaceSilent(workspace_id, None))?;
+    } else {
+        Dispatch::call(DispatchType::MoveToWorkspace(workspace_id, None))?;
+    }
+    Ok(())
+}
+
 pub fn hyprctl_dispatch_focus_address(address: &str) -> anyhow::Result<()> {
     let addr = Address::new(address);
     Dispatch::call(DispatchType::FocusWindow(hyprland::dispatch::WindowIdentifier::Address(addr)))?;
     Ok(())
 }
 
+pub fn hyprctl_dispatch_move_address_to_workspace(address: &str, id: i32) -> anyhow::Result<()> {
+    let addr = Address::new(address);
+    let workspace_id = hyprland::dispatch::WorkspaceIdentifierWithSpecial::Id(id);
+    Dispatch::call(DispatchType::MoveToWorkspaceSilent(
+        workspace_id,
+        Some(hyprland::dispatch::WindowIdentifier::Address(addr)),
+    ))?;
+    Ok(())
+}
+
+pub fn hyprctl_dispatch_move_address_to_special(address: &str, name: &str) -> anyhow::Result<()> {
+    let addr = Address::new(address);
+    let workspace_id = hyprland::dispatch::WorkspaceIdentifierWithSpecial::Special(Some(name));
+    Dispatch::call(DispatchType::MoveToWorkspaceSilent(
+        workspace_id,
+        Some(hyprland::dispatch::WindowIdentifier::Address(addr)),
+    ))?;
+    Ok(())
+}
+
+pub fn hyprctl_dispatch_toggle_floating_address(address: &str) -> anyhow::Result<()> {
+    let addr = Address::new(address);
+    Dispatch::call(DispatchType::ToggleFloating(Some(hyprland::dispatch::WindowIdentifier::Address(addr))))?;
+    Ok(())
+}
+
+pub fn hyprctl_dispatch_pin_address(address: &str) -> anyhow::Result<()> {
+    let addr = Address::new(address);
+    Dispatch::call(DispatchType::Pin(Some(hyprland::dispatch::WindowIdentifier::Address(addr))))?;
+    Ok(())
+}
+
+/// `fullscreen` only acts on the focused window, so focus the target first.
+pub fn hyprctl_dispatch_toggle_fullscreen_address(address: &str) -> anyhow::Result<()> {
+    hyprctl_dispatch_focus_address(address)?;
+    Dispatch::call(DispatchType::ToggleFullscreen(hyprland::dispatch::FullscreenType::NoParam))?;
+    Ok(())
+}
+
+/// `movewindow mon:` only acts on the focused window, so focus the target first.
+pub fn hyprctl_dispatch_move_address_to_monitor(address: &str, monitor: &str) -> anyhow::Result<()> {
+    hyprctl_dispatch_focus_address(address)?;
+    Dispatch::call(DispatchType::MoveWindow(hyprland::dispatch::WindowMove::Monitor(
+        hyprland::dispatch::MonitorIdentifier::Name(monitor),
+    )))?;
+    Ok(())
+}
+
+pub fn hyprctl_dispatch_exec(command: &str) -> anyhow::Result<()> {
+    Dispatch::call(DispatchType::Exec(command))?;
+    Ok(())
+}
+
+/// Send SIGTERM to a client's process, for windows that ignore `closewindow`.
+pub fn terminate_pid(pid: i32) -> anyhow::Result<()> {
+    if pid <= 0 {
+        anyhow::bail!("Invalid pid {}", pid);
+    }
+    // SAFETY: kill(2) only takes plain integers.
+    if unsafe { libc::kill(pid, libc::SIGTERM) } != 0 {
+        anyhow::bail!("Failed to terminate pid {}: {}", pid, std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
 pub fn hyprctl_dispatch_close_address(address: &str) -> anyhow::Result<()> {
     let addr = Address::new(address);
     Dispatch::call(DispatchType::CloseWindow(hyprland::dispatch::WindowIdentifier::Address(addr)))?;
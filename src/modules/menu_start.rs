@@ -0,0 +1,388 @@
+use gtk4 as gtk;
+use gtk::prelude::*;
+use std::cell::RefCell;
+use std::process::Command;
+use std::rc::Rc;
+use std::thread;
+
+use super::config::MenuStartConfig;
+use super::pinned::{launch_app, load_pin_cache, normalize_id, pin_app, unpin_app};
+
+/// Freedesktop main categories, in menu order, with the label shown for each.
+const CATEGORIES: [(&str, &str); 11] = [
+    ("AudioVideo", "Multimedia"),
+    ("Development", "Development"),
+    ("Education", "Education"),
+    ("Game", "Games"),
+    ("Graphics", "Graphics"),
+    ("Network", "Internet"),
+    ("Office", "Office"),
+    ("Science", "Science"),
+    ("Settings", "Settings"),
+    ("System", "System"),
+    ("Utility", "Utility"),
+];
+
+const OTHER_CATEGORY: &str = "Other";
+
+/// Menu label for a `Categories=` value: the first main category listed, else "Other".
+fn main_category(categories: &str) -> &'static str {
+    for category in categories.split(';') {
+        let category = match category {
+            "Audio" | "Video" => "AudioVideo",
+            c => c,
+        };
+        if let Some((_, label)) = CATEGORIES.iter().find(|(key, _)| *key == category) {
+            return label;
+        }
+    }
+    OTHER_CATEGORY
+}
+
+#[derive(Clone)]
+struct MenuApp {
+    id: String,
+    name: String,
+    category: &'static str,
+    /// Lowercased name, generic name, keywords and executable, for the search.
+    haystack: String,
+    info: gio::DesktopAppInfo,
+}
+
+/// Installed apps that want to be shown, sorted by name.
+fn load_apps() -> Vec<MenuApp> {
+    let mut apps: Vec<MenuApp> = gio::AppInfo::all()
+        .into_iter()
+        .filter(|info| info.should_show())
+        .filter_map(|info| info.downcast::<gio::DesktopAppInfo>().ok())
+        .filter_map(|info| {
+            let id = info.id()?.to_string();
+            let name = info.display_name().to_string();
+            let mut haystack = name.to_lowercase();
+            if let Some(generic) = info.generic_name() {
+                haystack.push('\n');
+                haystack.push_str(&generic.to_lowercase());
+            }
+            for keyword in info.keywords() {
+                haystack.push('\n');
+                haystack.push_str(&keyword.to_lowercase());
+            }
+            if let Some(exe) = info.executable().file_name().and_then(|f| f.to_str()) {
+                haystack.push('\n');
+                haystack.push_str(&exe.to_lowercase());
+            }
+            Some(MenuApp {
+                id,
+                name,
+                category: main_category(&info.categories().unwrap_or_default()),
+                haystack,
+                info,
+            })
+        })
+        .collect();
+    apps.sort_by_key(|a| a.name.to_lowercase());
+    apps
+}
+
+fn run_command(command: &str) {
+    let command = command.to_string();
+    thread::spawn(move || {
+        let _ = Command::new("sh").arg("-c").arg(&command).spawn();
+    });
+}
+
+/// The `menu-start` button and its launcher popover: favourites, categories, type-ahead
+/// search and power buttons. With `launcher-command` set, the button runs that instead.
+#[derive(Clone)]
+pub struct MenuStartUi {
+    button: gtk::Button,
+    popover: gtk::Popover,
+    search: gtk::SearchEntry,
+    favourites_box: gtk::Box,
+    categories_box: gtk::Box,
+    apps_box: gtk::Box,
+    apps: Rc<RefCell<Vec<MenuApp>>>,
+    category: Rc<RefCell<Option<&'static str>>>,
+    config: MenuStartConfig,
+}
+
+impl MenuStartUi {
+    pub fn new(config: MenuStartConfig) -> Self {
+        let button = gtk::Button::new();
+        button.set_widget_name("menu-start-button");
+        let content = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        if !config.icon.is_empty() {
+            let icon = if config.icon.starts_with('/') {
+                gtk::Image::from_file(&config.icon)
+            } else {
+                gtk::Image::from_icon_name(&config.icon)
+            };
+            icon.set_pixel_size(config.icon_size_button);
+            content.append(&icon);
+        }
+        if !config.label.is_empty() {
+            content.append(&gtk::Label::new(Some(&config.label)));
+        }
+        button.set_child(Some(&content));
+
+        let popover = gtk::Popover::new();
+        popover.set_widget_name("menu-start-popover");
+        popover.set_has_arrow(false);
+        popover.set_parent(&button);
+        button.connect_destroy({
+            let popover = popover.clone();
+            move |_| popover.unparent()
+        });
+
+        let root = gtk::Box::new(gtk::Orientation::Vertical, 6);
+
+        let search = gtk::SearchEntry::new();
+        search.set_placeholder_text(Some("Type to search"));
+        root.append(&search);
+
+        let favourites_box = gtk::Box::new(gtk::Orientation::Horizontal, 2);
+        favourites_box.set_widget_name("menu-start-favourites");
+        root.append(&favourites_box);
+
+        let lists = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+        let categories_box = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        categories_box.set_widget_name("menu-start-categories");
+        lists.append(&categories_box);
+
+        let apps_box = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        apps_box.set_widget_name("menu-start-apps");
+        let scrolled = gtk::ScrolledWindow::new();
+        scrolled.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+        scrolled.set_min_content_height(config.height);
+        scrolled.set_min_content_width(config.width);
+        scrolled.set_hexpand(true);
+        scrolled.set_child(Some(&apps_box));
+        lists.append(&scrolled);
+        root.append(&lists);
+
+        let power_box = gtk::Box::new(gtk::Orientation::Horizontal, 2);
+        power_box.set_widget_name("menu-start-power");
+        power_box.set_halign(gtk::Align::End);
+        let power = [
+            ("system-lock-screen-symbolic", "Lock", &config.cmd_lock),
+            ("system-log-out-symbolic", "Log out", &config.cmd_logout),
+            ("system-reboot-symbolic", "Restart", &config.cmd_restart),
+            ("system-shutdown-symbolic", "Shut down", &config.cmd_shutdown),
+        ];
+        for (icon, tooltip, command) in power {
+            if command.is_empty() {
+                continue;
+            }
+            let btn = gtk::Button::from_icon_name(icon);
+            btn.set_tooltip_text(Some(tooltip));
+            let command = command.clone();
+            let popover = popover.clone();
+            btn.connect_clicked(move |_| {
+                popover.popdown();
+                run_command(&command);
+            });
+            power_box.append(&btn);
+        }
+        root.append(&power_box);
+
+        popover.set_child(Some(&root));
+        // Typing anywhere in the popover goes to the search.
+        search.set_key_capture_widget(Some(&popover));
+
+        let ui = Self {
+            button,
+            popover,
+            search,
+            favourites_box,
+            categories_box,
+            apps_box,
+            apps: Rc::new(RefCell::new(Vec::new())),
+            category: Rc::new(RefCell::new(None)),
+            config,
+        };
+
+        {
+            let ui_for_click = ui.clone();
+            ui.button.connect_clicked(move |_| ui_for_click.open());
+        }
+        {
+            let ui_for_search = ui.clone();
+            ui.search.connect_search_changed(move |_| ui_for_search.fill_apps());
+        }
+        {
+            // Enter launches the first match.
+            let ui_for_activate = ui.clone();
+            ui.search.connect_activate(move |_| {
+                let first = ui_for_activate.visible_apps().into_iter().next();
+                if let Some(app) = first {
+                    ui_for_activate.popover.popdown();
+                    launch_app(&app.info);
+                }
+            });
+        }
+        {
+            let ui_for_stop = ui.clone();
+            ui.search.connect_stop_search(move |_| ui_for_stop.popover.popdown());
+        }
+
+        ui
+    }
+
+    pub fn widget(&self) -> gtk::Widget {
+        self.button.clone().upcast()
+    }
+
+    /// Reload the app list (to pick up installs) and show the popover.
+    fn open(&self) {
+        if !self.config.launcher_command.is_empty() {
+            run_command(&self.config.launcher_command);
+            return;
+        }
+
+        match self.apps.try_borrow_mut() {
+            Ok(mut apps) => *apps = load_apps(),
+            Err(_) => return,
+        }
+        if let Ok(mut category) = self.category.try_borrow_mut() {
+            *category = None;
+        }
+        self.search.set_text("");
+        self.fill_categories();
+        self.fill_favourites();
+        self.fill_apps();
+        self.popover.popup();
+        self.search.grab_focus();
+    }
+
+    fn fill_categories(&self) {
+        while let Some(child) = self.categories_box.first_child() {
+            self.categories_box.remove(&child);
+        }
+        let present: Vec<&'static str> = match self.apps.try_borrow() {
+            Ok(apps) => {
+                let mut labels: Vec<&'static str> = CATEGORIES.iter().map(|(_, label)| *label).collect();
+                labels.push(OTHER_CATEGORY);
+                labels
+                    .into_iter()
+                    .filter(|label| apps.iter().any(|a| a.category == *label))
+                    .collect()
+            }
+            Err(_) => return,
+        };
+
+        let all = std::iter::once(None).chain(present.into_iter().map(Some));
+        for category in all {
+            let btn = gtk::Button::with_label(category.unwrap_or("All"));
+            btn.set_has_frame(false);
+            let ui = self.clone();
+            btn.connect_clicked(move |_| {
+                if let Ok(mut current) = ui.category.try_borrow_mut() {
+                    *current = category;
+                }
+                ui.fill_apps();
+            });
+            self.categories_box.append(&btn);
+        }
+    }
+
+    fn fill_favourites(&self) {
+        while let Some(child) = self.favourites_box.first_child() {
+            self.favourites_box.remove(&child);
+        }
+        let favourites: Vec<MenuApp> = match self.apps.try_borrow() {
+            Ok(apps) => load_pin_cache()
+                .iter()
+                .filter_map(|id| {
+                    let wanted = normalize_id(id);
+                    apps.iter().find(|a| normalize_id(&a.id) == wanted).cloned()
+                })
+                .collect(),
+            Err(_) => return,
+        };
+        for app in &favourites {
+            let btn = gtk::Button::new();
+            btn.set_has_frame(false);
+            btn.set_tooltip_text(Some(&app.name));
+            btn.set_child(Some(&self.app_icon(app, self.config.icon_size_large)));
+            self.connect_app_button(&btn, app, true);
+            self.favourites_box.append(&btn);
+        }
+        self.favourites_box.set_visible(!favourites.is_empty());
+    }
+
+    /// Apps matching the search, or else the selected category.
+    fn visible_apps(&self) -> Vec<MenuApp> {
+        let query = self.search.text().trim().to_lowercase();
+        let category = self.category.try_borrow().ok().and_then(|c| *c);
+        match self.apps.try_borrow() {
+            Ok(apps) => apps
+                .iter()
+                .filter(|a| {
+                    if !query.is_empty() {
+                        a.haystack.contains(&query)
+                    } else {
+                        category.is_none_or(|c| a.category == c)
+                    }
+                })
+                .cloned()
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn fill_apps(&self) {
+        while let Some(child) = self.apps_box.first_child() {
+            self.apps_box.remove(&child);
+        }
+        for app in self.visible_apps() {
+            let btn = gtk::Button::new();
+            btn.set_has_frame(false);
+            if let Some(description) = app.info.description() {
+                btn.set_tooltip_text(Some(&description));
+            }
+            let content = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+            content.append(&self.app_icon(&app, self.config.icon_size_button));
+            let label = gtk::Label::new(Some(&app.name));
+            label.set_xalign(0.0);
+            label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+            content.append(&label);
+            btn.set_child(Some(&content));
+            self.connect_app_button(&btn, &app, false);
+            self.apps_box.append(&btn);
+        }
+    }
+
+    fn app_icon(&self, app: &MenuApp, size: i32) -> gtk::Image {
+        let icon = match app.info.icon() {
+            Some(gicon) => gtk::Image::from_gicon(&gicon),
+            None => gtk::Image::from_icon_name("application-x-executable"),
+        };
+        icon.set_pixel_size(size);
+        icon
+    }
+
+    /// Left click launches; right click adds to / removes from the favourites.
+    fn connect_app_button(&self, btn: &gtk::Button, app: &MenuApp, favourite: bool) {
+        let popover = self.popover.clone();
+        let info = app.info.clone();
+        btn.connect_clicked(move |_| {
+            popover.popdown();
+            launch_app(&info);
+        });
+
+        let ui = self.clone();
+        let id = app.id.clone();
+        let right = gtk::GestureClick::new();
+        right.set_button(3);
+        right.connect_released(move |_, _, _, _| {
+            let result = if favourite { unpin_app(&id) } else { pin_app(&id) };
+            if let Err(e) = result {
+                eprintln!("menu-start: failed to update the pin cache: {e:#}");
+            }
+            // Rebuilding removes this button; wait until its handler returns.
+            let ui = ui.clone();
+            glib::idle_add_local_once(move || ui.fill_favourites());
+        });
+        btn.add_controller(right);
+    }
+}
@@ -1,7 +1,27 @@
+pub mod app_info;
+pub mod brightness_slider;
+pub mod compositor;
 pub mod config;
 pub mod controls;
+pub mod dwl;
+pub mod dwl_ipc;
 pub mod hyprland;
 pub mod hypr_config;
+pub mod menu_start;
+pub mod mpris;
+pub mod network_stats;
+pub mod network_ui;
+pub mod niri;
+#[cfg(feature = "openweather")]
+pub mod openweather;
+pub mod pinned;
+pub mod playerctl;
+pub mod random_wallpaper;
+pub mod sway;
+pub mod system_stats;
+pub mod system_ui;
+pub mod taskbar_model;
 pub mod theme;
 pub mod tray;
 pub mod ui;
+pub mod wlr;
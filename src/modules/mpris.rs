@@ -0,0 +1,436 @@
+use super::hyprland::AppMsg;
+use crossbeam_channel as cb;
+use std::collections::{BTreeMap, HashMap};
+use std::thread;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
+use zbus::fdo::{DBusProxy, PropertiesProxy};
+use zbus::zvariant::{OwnedValue, Value};
+use zbus::Proxy;
+
+use zbus::blocking::Connection as BlockingConnection;
+use zbus::blocking::Proxy as BlockingProxy;
+
+const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+const MPRIS_PATH: &str = "/org/mpris/MediaPlayer2";
+const MPRIS_ROOT_IFACE: &str = "org.mpris.MediaPlayer2";
+const MPRIS_PLAYER_IFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+/// State of one MPRIS player, as shown by the `playerctl` module.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MprisPlayer {
+    /// Bus name, e.g. `org.mpris.MediaPlayer2.spotify`.
+    pub bus_name: String,
+    /// `Identity` of the player, falling back to the bus name suffix.
+    pub identity: String,
+    /// `Playing`, `Paused` or `Stopped`.
+    pub status: String,
+    pub title: String,
+    pub artists: Vec<String>,
+    pub album: String,
+    pub art_url: String,
+    pub track_id: String,
+    /// Microseconds; 0 when unknown.
+    pub length: i64,
+    pub position: i64,
+    pub can_go_next: bool,
+    pub can_go_previous: bool,
+    pub can_seek: bool,
+}
+
+impl MprisPlayer {
+    pub fn is_playing(&self) -> bool {
+        self.status == "Playing"
+    }
+}
+
+fn value_str(value: &Value) -> Option<String> {
+    match value {
+        Value::Str(s) => Some(s.to_string()),
+        Value::ObjectPath(p) => Some(p.to_string()),
+        Value::Value(inner) => value_str(inner),
+        _ => None,
+    }
+}
+
+fn value_strs(value: &Value) -> Vec<String> {
+    match value {
+        Value::Array(items) => items.iter().filter_map(value_str).collect(),
+        Value::Value(inner) => value_strs(inner),
+        other => value_str(other).into_iter().collect(),
+    }
+}
+
+fn value_i64(value: &Value) -> Option<i64> {
+    match value {
+        Value::I64(n) => Some(*n),
+        Value::U64(n) => i64::try_from(*n).ok(),
+        Value::I32(n) => Some(i64::from(*n)),
+        Value::U32(n) => Some(i64::from(*n)),
+        Value::F64(n) => Some(*n as i64),
+        Value::Value(inner) => value_i64(inner),
+        _ => None,
+    }
+}
+
+/// Fill the track fields from an MPRIS `Metadata` dict.
+fn apply_metadata(player: &mut MprisPlayer, metadata: &HashMap<String, OwnedValue>) {
+    let get = |key: &str| metadata.get(key).map(|v| &**v);
+    player.title = get("xesam:title").and_then(value_str).unwrap_or_default();
+    player.artists = get("xesam:artist").map(value_strs).unwrap_or_default();
+    player.album = get("xesam:album").and_then(value_str).unwrap_or_default();
+    player.art_url = get("mpris:artUrl").and_then(value_str).unwrap_or_default();
+    player.track_id = get("mpris:trackid").and_then(value_str).unwrap_or_default();
+    player.length = get("mpris:length").and_then(value_i64).unwrap_or(0);
+}
+
+async fn player_proxy<'a>(connection: &zbus::Connection, bus_name: &'a str, iface: &'a str) -> zbus::Result<Proxy<'a>> {
+    Proxy::new(connection, bus_name, MPRIS_PATH, iface).await
+}
+
+/// Read the full state of a player; `None` once it has left the bus.
+async fn read_player(connection: &zbus::Connection, bus_name: &str) -> Option<MprisPlayer> {
+    let player = player_proxy(connection, bus_name, MPRIS_PLAYER_IFACE).await.ok()?;
+    let status = player.get_property::<String>("PlaybackStatus").await.ok()?;
+
+    let identity = match player_proxy(connection, bus_name, MPRIS_ROOT_IFACE).await {
+        Ok(root) => root.get_property::<String>("Identity").await.ok(),
+        Err(_) => None,
+    };
+
+    let mut state = MprisPlayer {
+        bus_name: bus_name.to_string(),
+        identity: identity
+            .filter(|i| !i.is_empty())
+            .unwrap_or_else(|| bus_name.trim_start_matches(MPRIS_PREFIX).to_string()),
+        status,
+        position: player.get_property::<i64>("Position").await.unwrap_or(0),
+        can_go_next: player.get_property::<bool>("CanGoNext").await.unwrap_or(false),
+        can_go_previous: player.get_property::<bool>("CanGoPrevious").await.unwrap_or(false),
+        can_seek: player.get_property::<bool>("CanSeek").await.unwrap_or(false),
+        ..Default::default()
+    };
+    if let Ok(metadata) = player.get_property::<HashMap<String, OwnedValue>>("Metadata").await {
+        apply_metadata(&mut state, &metadata);
+    }
+    Some(state)
+}
+
+/// Ask for a refresh of `bus_name` whenever its properties change or it seeks, until the
+/// name loses its owner.
+fn watch_player(connection: zbus::Connection, bus_name: String, refresh: mpsc::UnboundedSender<String>) {
+    tokio::spawn(async move {
+        use futures_util::StreamExt;
+
+        let properties = match PropertiesProxy::builder(&connection)
+            .destination(bus_name.as_str())
+            .and_then(|b| b.path(MPRIS_PATH))
+        {
+            Ok(b) => match b.build().await {
+                Ok(p) => p,
+                Err(_) => return,
+            },
+            Err(_) => return,
+        };
+        let player = match player_proxy(&connection, &bus_name, MPRIS_PLAYER_IFACE).await {
+            Ok(p) => p,
+            Err(_) => return,
+        };
+        let (mut changed, mut seeked, mut owner) = match (
+            properties.receive_properties_changed().await,
+            player.receive_signal("Seeked").await,
+            player.receive_owner_changed().await,
+        ) {
+            (Ok(c), Ok(s), Ok(o)) => (c, s, o),
+            _ => return,
+        };
+
+        loop {
+            tokio::select! {
+                Some(_) = changed.next() => {}
+                Some(_) = seeked.next() => {}
+                next = owner.next() => {
+                    if !matches!(next, Some(Some(_))) {
+                        let _ = refresh.send(bus_name.clone());
+                        return;
+                    }
+                }
+                else => return,
+            }
+            if refresh.send(bus_name.clone()).is_err() {
+                return;
+            }
+        }
+    });
+}
+
+/// Track all MPRIS players on `connection` and send `AppMsg::MprisPlayers` snapshots on
+/// every change (and every second while something plays, for the position).
+///
+/// Takes the connection so it can run against any bus, not only the session bus.
+pub async fn run_mpris_watcher(connection: zbus::Connection, sender: cb::Sender<AppMsg>) -> zbus::Result<()> {
+    use futures_util::StreamExt;
+
+    let dbus = DBusProxy::new(&connection).await?;
+    let (refresh_tx, mut refresh_rx) = mpsc::unbounded_channel::<String>();
+    let mut players: BTreeMap<String, MprisPlayer> = BTreeMap::new();
+
+    // Subscribe before listing so a player starting in between isn't missed.
+    let mut owners = dbus.receive_name_owner_changed().await?;
+    for name in dbus.list_names().await? {
+        let name = name.as_str().to_string();
+        if name.starts_with(MPRIS_PREFIX) {
+            watch_player(connection.clone(), name.clone(), refresh_tx.clone());
+            let _ = refresh_tx.send(name);
+        }
+    }
+
+    let mut tick = tokio::time::interval(Duration::from_secs(1));
+    loop {
+        tokio::select! {
+            Some(signal) = owners.next() => {
+                let args = match signal.args() {
+                    Ok(a) => a,
+                    Err(_) => continue,
+                };
+                let name = args.name.to_string();
+                if !name.starts_with(MPRIS_PREFIX) {
+                    continue;
+                }
+                let appeared = args.old_owner.is_none() && args.new_owner.is_some();
+                if appeared {
+                    watch_player(connection.clone(), name.clone(), refresh_tx.clone());
+                }
+                let _ = refresh_tx.send(name);
+            }
+            Some(name) = refresh_rx.recv() => {
+                let changed = match read_player(&connection, &name).await {
+                    Some(state) => players.insert(name, state.clone()).as_ref() != Some(&state),
+                    None => players.remove(&name).is_some(),
+                };
+                if changed && sender.send(AppMsg::MprisPlayers(players.values().cloned().collect())).is_err() {
+                    return Ok(());
+                }
+            }
+            _ = tick.tick() => {
+                let mut changed = false;
+                for state in players.values_mut().filter(|p| p.is_playing()) {
+                    if let Ok(player) = player_proxy(&connection, &state.bus_name, MPRIS_PLAYER_IFACE).await {
+                        if let Ok(position) = player.get_property::<i64>("Position").await {
+                            changed |= position != state.position;
+                            state.position = position;
+                        }
+                    }
+                }
+                if changed && sender.send(AppMsg::MprisPlayers(players.values().cloned().collect())).is_err() {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+pub fn spawn_mpris_watcher(sender: cb::Sender<AppMsg>) {
+    thread::spawn(move || {
+        let rt = match Runtime::new() {
+            Ok(rt) => rt,
+            Err(err) => {
+                eprintln!("Failed to start tokio runtime for playerctl: {err}");
+                return;
+            }
+        };
+
+        rt.block_on(async move {
+            let connection = match zbus::Connection::session().await {
+                Ok(c) => c,
+                Err(err) => {
+                    eprintln!("playerctl: failed to connect to the session bus: {err}");
+                    return;
+                }
+            };
+            if let Err(err) = run_mpris_watcher(connection, sender).await {
+                eprintln!("playerctl: MPRIS watcher stopped: {err}");
+            }
+        });
+    });
+}
+
+/// Call a no-argument `Player` method such as `PlayPause`, `Next` or `Previous`.
+pub fn mpris_call(bus_name: &str, method: &str) -> anyhow::Result<()> {
+    let conn = BlockingConnection::session()?;
+    let proxy = BlockingProxy::new(&conn, bus_name, MPRIS_PATH, MPRIS_PLAYER_IFACE)?;
+    let _: () = proxy.call(method, &())?;
+    Ok(())
+}
+
+/// Seek to `position` (microseconds) within the track `track_id`.
+fn mpris_set_position(conn: &BlockingConnection, bus_name: &str, track_id: &str, position: i64) -> anyhow::Result<()> {
+    let proxy = BlockingProxy::new(conn, bus_name, MPRIS_PATH, MPRIS_PLAYER_IFACE)?;
+    let track = zbus::zvariant::ObjectPath::try_from(track_id)?;
+    let _: () = proxy.call("SetPosition", &(track, position))?;
+    Ok(())
+}
+
+/// A request for the seek thread started by `spawn_mpris_seeker`.
+#[derive(Debug, Clone)]
+pub struct MprisSeek {
+    pub bus_name: String,
+    pub track_id: String,
+    /// Microseconds.
+    pub position: i64,
+}
+
+/// Start a thread that performs seeks over one session-bus connection. Dragging the seek
+/// slider sends a request per step; only the newest pending one is sent to the player.
+/// The thread ends when the returned sender is dropped.
+pub fn spawn_mpris_seeker() -> cb::Sender<MprisSeek> {
+    let (tx, rx) = cb::unbounded::<MprisSeek>();
+    thread::spawn(move || {
+        let mut conn: Option<BlockingConnection> = None;
+        while let Ok(mut seek) = rx.recv() {
+            while let Ok(newer) = rx.try_recv() {
+                seek = newer;
+            }
+            if conn.is_none() {
+                match BlockingConnection::session() {
+                    Ok(c) => conn = Some(c),
+                    Err(err) => {
+                        eprintln!("playerctl: failed to connect to the session bus: {err}");
+                        continue;
+                    }
+                }
+            }
+            let result = match conn.as_ref() {
+                Some(conn) => mpris_set_position(conn, &seek.bus_name, &seek.track_id, seek.position),
+                None => continue,
+            };
+            if let Err(err) = result {
+                eprintln!("playerctl: seek failed: {err:#}");
+            }
+        }
+    });
+    tx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command, Stdio};
+
+    /// A private `dbus-daemon`, killed when dropped.
+    struct PrivateBus {
+        daemon: Child,
+        address: String,
+    }
+
+    impl PrivateBus {
+        /// `None` when `dbus-daemon` isn't installed.
+        fn start() -> Option<Self> {
+            let mut daemon = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .spawn()
+                .ok()?;
+            let mut address = String::new();
+            BufReader::new(daemon.stdout.take()?).read_line(&mut address).ok()?;
+            Some(Self {
+                daemon,
+                address: address.trim().to_string(),
+            })
+        }
+    }
+
+    impl Drop for PrivateBus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+        }
+    }
+
+    struct MockPlayer {
+        status: String,
+    }
+
+    #[zbus::interface(name = "org.mpris.MediaPlayer2.Player")]
+    impl MockPlayer {
+        #[zbus(property)]
+        fn playback_status(&self) -> String {
+            self.status.clone()
+        }
+
+        #[zbus(property)]
+        fn metadata(&self) -> HashMap<String, OwnedValue> {
+            let mut metadata = HashMap::new();
+            metadata.insert("xesam:title".to_string(), Value::from("Song").try_into().unwrap());
+            metadata.insert("xesam:artist".to_string(), Value::from(vec!["Band"]).try_into().unwrap());
+            metadata.insert("mpris:length".to_string(), Value::from(180_000_000i64).try_into().unwrap());
+            metadata
+        }
+    }
+
+    fn next_snapshot(rx: &cb::Receiver<AppMsg>) -> Vec<MprisPlayer> {
+        loop {
+            match rx.recv_timeout(Duration::from_secs(5)) {
+                Ok(AppMsg::MprisPlayers(players)) => return players,
+                Ok(_) => continue,
+                Err(err) => panic!("no MPRIS snapshot: {err}"),
+            }
+        }
+    }
+
+    #[test]
+    fn snapshots_follow_the_player() {
+        let bus = match PrivateBus::start() {
+            Some(bus) => bus,
+            None => {
+                eprintln!("dbus-daemon not available, skipping");
+                return;
+            }
+        };
+        let rt = Runtime::new().unwrap();
+        let connect = || zbus::connection::Builder::address(bus.address.as_str()).unwrap();
+
+        let (tx, rx) = cb::unbounded();
+        let watcher = rt.block_on(connect().build()).unwrap();
+        rt.spawn(run_mpris_watcher(watcher, tx));
+
+        // NameOwnerChanged: the player appears.
+        let player = rt
+            .block_on(
+                connect()
+                    .serve_at(MPRIS_PATH, MockPlayer { status: "Playing".to_string() })
+                    .unwrap()
+                    .name("org.mpris.MediaPlayer2.mock")
+                    .unwrap()
+                    .build(),
+            )
+            .unwrap();
+        let players = next_snapshot(&rx);
+        assert_eq!(players.len(), 1);
+        let mock = &players[0];
+        assert_eq!(mock.bus_name, "org.mpris.MediaPlayer2.mock");
+        assert_eq!(mock.identity, "mock");
+        assert!(mock.is_playing());
+        assert_eq!((mock.title.as_str(), mock.artists.clone()), ("Song", vec!["Band".to_string()]));
+        assert_eq!(mock.length, 180_000_000);
+
+        // PropertiesChanged: it pauses.
+        rt.block_on(async {
+            let iface = player
+                .object_server()
+                .interface::<_, MockPlayer>(MPRIS_PATH)
+                .await
+                .unwrap();
+            iface.get_mut().await.status = "Paused".to_string();
+            iface.get().await.playback_status_changed(iface.signal_emitter()).await.unwrap();
+        });
+        let players = next_snapshot(&rx);
+        assert_eq!(players[0].status, "Paused");
+
+        // NameOwnerChanged: it leaves the bus.
+        drop(player);
+        assert!(next_snapshot(&rx).is_empty());
+    }
+}
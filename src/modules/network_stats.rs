@@ -0,0 +1,288 @@
+use super::hyprland::AppMsg;
+use crossbeam_channel as cb;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::fs;
+use std::io;
+use std::net::Ipv4Addr;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often the network sampler reads the byte counters.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// One reading of every interface, sent as `AppMsg::Network`.
+#[derive(Debug, Clone, Default)]
+pub struct NetworkSample {
+    pub interfaces: Vec<NetInterface>,
+    /// Interface of the lowest-metric IPv4 default route.
+    pub default_interface: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NetInterface {
+    pub name: String,
+    /// `operstate` is `up`, or `unknown` with a carrier (tun/wireguard).
+    pub up: bool,
+    pub wireless: bool,
+    pub mac: String,
+    /// Mb/s, when the driver reports it.
+    pub speed: Option<u32>,
+    pub ipv4: Vec<String>,
+    /// Global-scope addresses only.
+    pub ipv6: Vec<String>,
+    /// Bytes per second over the last interval.
+    pub rx_rate: f64,
+    pub tx_rate: f64,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+}
+
+/// `(rx_bytes, tx_bytes)` per interface from `/proc/net/dev`, in file order.
+fn read_counters() -> Vec<(String, u64, u64)> {
+    let text = fs::read_to_string("/proc/net/dev").unwrap_or_default();
+    text.lines()
+        .skip(2)
+        .filter_map(|line| {
+            let (name, values) = line.split_once(':')?;
+            let values: Vec<u64> = values.split_whitespace().filter_map(|v| v.parse().ok()).collect();
+            Some((name.trim().to_string(), *values.first()?, *values.get(8)?))
+        })
+        .collect()
+}
+
+/// `/proc/net/route` rows as `(iface, destination, mask, metric)`, in kernel byte order.
+fn read_routes() -> Vec<(String, u32, u32, u32)> {
+    let text = fs::read_to_string("/proc/net/route").unwrap_or_default();
+    text.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let hex = |i: usize| u32::from_str_radix(fields.get(i)?, 16).ok();
+            let metric = fields.get(6)?.parse().ok()?;
+            Some((fields.first()?.to_string(), hex(1)?, hex(7)?, metric))
+        })
+        .collect()
+}
+
+fn default_route(routes: &[(String, u32, u32, u32)]) -> Option<String> {
+    routes
+        .iter()
+        .filter(|(_, dest, mask, _)| *dest == 0 && *mask == 0)
+        .min_by_key(|(_, _, _, metric)| *metric)
+        .map(|(iface, ..)| iface.clone())
+}
+
+/// IPv4 addresses per interface from `getifaddrs(3)`. Unlike the routing table this also
+/// covers /32 and point-to-point addresses, which have no connected route.
+fn read_ipv4() -> HashMap<String, Vec<String>> {
+    let mut by_iface: HashMap<String, Vec<String>> = HashMap::new();
+    let mut addrs: *mut libc::ifaddrs = std::ptr::null_mut();
+    // SAFETY: on success the list is valid until the freeifaddrs below, and nothing read
+    // from it outlives that.
+    unsafe {
+        if libc::getifaddrs(&mut addrs) != 0 {
+            return by_iface;
+        }
+        let mut cursor = addrs;
+        while !cursor.is_null() {
+            let entry = &*cursor;
+            cursor = entry.ifa_next;
+            if entry.ifa_addr.is_null() || i32::from((*entry.ifa_addr).sa_family) != libc::AF_INET {
+                continue;
+            }
+            let sin = &*(entry.ifa_addr as *const libc::sockaddr_in);
+            let ip = Ipv4Addr::from(u32::from_be(sin.sin_addr.s_addr));
+            if ip.is_loopback() {
+                continue;
+            }
+            // Addresses with a label are reported as `eth0:1`; interface names can't
+            // contain ':'.
+            let name = CStr::from_ptr(entry.ifa_name).to_string_lossy();
+            let name = name.split(':').next().unwrap_or_default().to_string();
+            let ips = by_iface.entry(name).or_default();
+            if !ips.contains(&ip.to_string()) {
+                ips.push(ip.to_string());
+            }
+        }
+        libc::freeifaddrs(addrs);
+    }
+    by_iface
+}
+
+/// Global IPv6 addresses from `/proc/net/if_inet6`.
+fn read_ipv6() -> HashMap<String, Vec<String>> {
+    let text = fs::read_to_string("/proc/net/if_inet6").unwrap_or_default();
+    let mut by_iface: HashMap<String, Vec<String>> = HashMap::new();
+    for line in text.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 6 || fields[3] != "00" {
+            continue;
+        }
+        let addr = match u128::from_str_radix(fields[0], 16) {
+            Ok(a) => std::net::Ipv6Addr::from(a),
+            Err(_) => continue,
+        };
+        by_iface.entry(fields[5].to_string()).or_default().push(addr.to_string());
+    }
+    by_iface
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    Some(fs::read_to_string(path).ok()?.trim().to_string())
+}
+
+fn read_interface(name: &str) -> NetInterface {
+    let dir = Path::new("/sys/class/net").join(name);
+    let operstate = read_trimmed(&dir.join("operstate")).unwrap_or_default();
+    let carrier = read_trimmed(&dir.join("carrier")).unwrap_or_default();
+    NetInterface {
+        name: name.to_string(),
+        up: operstate == "up" || (operstate == "unknown" && carrier == "1"),
+        wireless: dir.join("wireless").exists() || dir.join("phy80211").exists(),
+        mac: read_trimmed(&dir.join("address")).unwrap_or_default(),
+        speed: read_trimmed(&dir.join("speed"))
+            .and_then(|s| s.parse::<i64>().ok())
+            .filter(|s| *s > 0)
+            .map(|s| s as u32),
+        ..Default::default()
+    }
+}
+
+/// Number of live `network` modules; the sampler idles at zero.
+static USERS: AtomicUsize = AtomicUsize::new(0);
+
+pub fn register() {
+    USERS.fetch_add(1, Ordering::SeqCst);
+}
+
+pub fn release() {
+    let _ = USERS.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1));
+}
+
+fn wake_sender() -> &'static OnceLock<cb::Sender<()>> {
+    static WAKE: OnceLock<cb::Sender<()>> = OnceLock::new();
+    &WAKE
+}
+
+/// Sample right away instead of at the next tick, e.g. after a link change.
+pub fn refresh_network() {
+    if let Some(wake) = wake_sender().get() {
+        let _ = wake.try_send(());
+    }
+}
+
+/// Wake the sampler whenever rtnetlink reports a link or address change, so it shows up
+/// before the next tick.
+fn spawn_netlink_watcher(wake: cb::Sender<()>) -> io::Result<()> {
+    // SAFETY: socket(2) only takes plain integers.
+    let fd = unsafe {
+        libc::socket(
+            libc::AF_NETLINK,
+            libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+            libc::NETLINK_ROUTE,
+        )
+    };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: `fd` is a fresh socket that nothing else owns.
+    let socket = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    // SAFETY: sockaddr_nl is plain data; all-zero is a valid value.
+    let mut addr: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+    addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+    addr.nl_groups = (libc::RTMGRP_LINK | libc::RTMGRP_IPV4_IFADDR | libc::RTMGRP_IPV6_IFADDR) as u32;
+    // SAFETY: `addr` is a valid sockaddr_nl of the given length.
+    let bound = unsafe {
+        libc::bind(
+            socket.as_raw_fd(),
+            &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+            std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+        )
+    };
+    if bound < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    thread::spawn(move || {
+        // The messages aren't parsed: every one is a change, and a sample re-reads it all.
+        let mut buf = [0u8; 8192];
+        loop {
+            // SAFETY: `buf` is writable for its full length.
+            let n = unsafe { libc::recv(socket.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len(), 0) };
+            if n < 0 {
+                let err = io::Error::last_os_error();
+                // ENOBUFS means messages were dropped, which still means something changed.
+                if !matches!(err.raw_os_error(), Some(libc::EINTR) | Some(libc::ENOBUFS)) {
+                    eprintln!("network: netlink socket failed: {err}");
+                    return;
+                }
+            }
+            let _ = wake.try_send(());
+        }
+    });
+    Ok(())
+}
+
+/// Start the thread behind the `network` modules. It sends `AppMsg::Network` every couple
+/// of seconds, and immediately after `refresh_network`.
+pub fn spawn_network_sampler(sender: cb::Sender<AppMsg>) {
+    let (wake_tx, wake_rx) = cb::bounded::<()>(1);
+    if wake_sender().set(wake_tx.clone()).is_err() {
+        return;
+    }
+    if let Err(err) = spawn_netlink_watcher(wake_tx) {
+        eprintln!("network: no netlink notifications, sampling every {SAMPLE_INTERVAL:?}: {err}");
+    }
+
+    thread::spawn(move || {
+        let mut previous: HashMap<String, (u64, u64)> = HashMap::new();
+        let mut last = Instant::now();
+        loop {
+            let _ = wake_rx.recv_timeout(SAMPLE_INTERVAL);
+            if USERS.load(Ordering::SeqCst) == 0 {
+                continue;
+            }
+
+            let now = Instant::now();
+            let elapsed = now.duration_since(last).as_secs_f64().max(0.001);
+            last = now;
+
+            let routes = read_routes();
+            let mut ipv4 = read_ipv4();
+            let mut ipv6 = read_ipv6();
+            let mut current = HashMap::new();
+            let interfaces = read_counters()
+                .into_iter()
+                .filter(|(name, ..)| name != "lo")
+                .map(|(name, rx, tx)| {
+                    let mut iface = read_interface(&name);
+                    if let Some((prev_rx, prev_tx)) = previous.get(&name) {
+                        iface.rx_rate = rx.saturating_sub(*prev_rx) as f64 / elapsed;
+                        iface.tx_rate = tx.saturating_sub(*prev_tx) as f64 / elapsed;
+                    }
+                    iface.rx_bytes = rx;
+                    iface.tx_bytes = tx;
+                    iface.ipv4 = ipv4.remove(&name).unwrap_or_default();
+                    iface.ipv6 = ipv6.remove(&name).unwrap_or_default();
+                    current.insert(name, (rx, tx));
+                    iface
+                })
+                .collect();
+            previous = current;
+
+            let sample = NetworkSample {
+                interfaces,
+                default_interface: default_route(&routes),
+            };
+            if sender.send(AppMsg::Network(sample)).is_err() {
+                return;
+            }
+        }
+    });
+}
@@ -0,0 +1,129 @@
+use gtk4 as gtk;
+use gtk::prelude::*;
+
+use super::config::NetworkConfig;
+use super::network_stats::{refresh_network, register, release, NetInterface, NetworkSample};
+use super::system_ui::{fill, human_bytes};
+
+fn rate(bytes_per_second: f64) -> String {
+    format!("{}/s", human_bytes(bytes_per_second as u64))
+}
+
+/// `interface` is a name, or a prefix when it ends in `*` (`wl*`); empty follows the
+/// default route. Among several matches an interface that is up wins.
+fn select_interface<'a>(sample: &'a NetworkSample, wanted: &str) -> Option<&'a NetInterface> {
+    let matches = |iface: &&NetInterface| match wanted.strip_suffix('*') {
+        Some(prefix) => iface.name.starts_with(prefix),
+        None => iface.name == wanted,
+    };
+    if wanted.is_empty() {
+        return sample
+            .default_interface
+            .as_ref()
+            .and_then(|name| sample.interfaces.iter().find(|i| i.name == *name))
+            .or_else(|| sample.interfaces.iter().find(|i| i.up));
+    }
+    sample
+        .interfaces
+        .iter()
+        .filter(matches)
+        .find(|i| i.up)
+        .or_else(|| sample.interfaces.iter().find(matches))
+}
+
+fn tooltip(iface: &NetInterface) -> String {
+    let mut lines = vec![format!(
+        "{} ({}, {})",
+        iface.name,
+        if iface.wireless { "wireless" } else { "wired" },
+        if iface.up { "up" } else { "down" }
+    )];
+    for ip in iface.ipv4.iter().chain(iface.ipv6.iter()) {
+        lines.push(ip.clone());
+    }
+    if !iface.mac.is_empty() {
+        lines.push(format!("MAC: {}", iface.mac));
+    }
+    if let Some(speed) = iface.speed {
+        lines.push(format!("Speed: {} Mb/s", speed));
+    }
+    lines.push(format!("Down: {} ({} total)", rate(iface.rx_rate), human_bytes(iface.rx_bytes)));
+    lines.push(format!("Up: {} ({} total)", rate(iface.tx_rate), human_bytes(iface.tx_bytes)));
+    lines.join("\n")
+}
+
+/// The `network` module: link icon plus a `format` line for one interface.
+#[derive(Clone)]
+pub struct NetworkUi {
+    root: gtk::Box,
+    image: gtk::Image,
+    label: gtk::Label,
+    config: NetworkConfig,
+}
+
+impl NetworkUi {
+    pub fn new(config: NetworkConfig) -> Self {
+        let root = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        root.set_widget_name("network");
+
+        let image = gtk::Image::new();
+        image.set_pixel_size(config.icon_size);
+        image.set_visible(config.show_icon);
+        root.append(&image);
+
+        let label = gtk::Label::new(None);
+        root.append(&label);
+
+        register();
+        refresh_network();
+        root.connect_destroy(move |_| release());
+
+        Self { root, image, label, config }
+    }
+
+    pub fn widget(&self) -> gtk::Widget {
+        self.root.clone().upcast()
+    }
+
+    pub fn set_sample(&self, sample: &NetworkSample) {
+        let iface = match select_interface(sample, &self.config.interface).filter(|i| i.up) {
+            Some(i) => i,
+            None => {
+                self.image.set_icon_name(Some(&self.config.icon_disconnected));
+                self.label.set_text(&self.config.format_disconnected);
+                self.label.set_visible(!self.config.format_disconnected.is_empty());
+                self.root.set_tooltip_text(Some("Disconnected"));
+                self.root.add_css_class("disconnected");
+                self.root.remove_css_class("wired");
+                self.root.remove_css_class("wireless");
+                return;
+            }
+        };
+
+        let (icon, class, other) = if iface.wireless {
+            (&self.config.icon_wireless, "wireless", "wired")
+        } else {
+            (&self.config.icon_wired, "wired", "wireless")
+        };
+        self.image.set_icon_name(Some(icon));
+        self.root.remove_css_class("disconnected");
+        self.root.remove_css_class(other);
+        self.root.add_css_class(class);
+
+        let text = fill(
+            &self.config.format,
+            &[
+                ("ifname", iface.name.clone()),
+                ("ip", iface.ipv4.first().cloned().unwrap_or_default()),
+                ("ipv6", iface.ipv6.first().cloned().unwrap_or_default()),
+                ("down", rate(iface.rx_rate)),
+                ("up", rate(iface.tx_rate)),
+                ("down_total", human_bytes(iface.rx_bytes)),
+                ("up_total", human_bytes(iface.tx_bytes)),
+            ],
+        );
+        self.label.set_visible(!text.is_empty());
+        self.label.set_text(&text);
+        self.root.set_tooltip_text(Some(&tooltip(iface)));
+    }
+}
@@ -0,0 +1,485 @@
+use super::hyprland::{AppMsg, HyprClient, HyprWorkspace, HyprWorkspaceInfo};
+use anyhow::Context;
+use crossbeam_channel as cb;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug, Deserialize, Clone)]
+#[allow(dead_code)]
+pub struct NiriWorkspace {
+    pub id: u64,
+    pub idx: u8,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub output: Option<String>,
+    #[serde(default)]
+    pub is_active: bool,
+    #[serde(default)]
+    pub is_focused: bool,
+    #[serde(default)]
+    pub is_urgent: bool,
+    #[serde(default)]
+    pub active_window_id: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[allow(dead_code)]
+pub struct NiriWindow {
+    pub id: u64,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub app_id: Option<String>,
+    #[serde(default)]
+    pub pid: Option<i32>,
+    #[serde(default)]
+    pub workspace_id: Option<u64>,
+    #[serde(default)]
+    pub is_focused: bool,
+    #[serde(default)]
+    pub is_floating: bool,
+    #[serde(default)]
+    pub is_urgent: bool,
+}
+
+/// Subset of niri's `Event` enum we react to; everything else is ignored.
+#[derive(Debug, Deserialize)]
+enum NiriEvent {
+    WorkspacesChanged {
+        workspaces: Vec<NiriWorkspace>,
+    },
+    WorkspaceActivated {
+        id: u64,
+        focused: bool,
+    },
+    WorkspaceActiveWindowChanged {
+        workspace_id: u64,
+        active_window_id: Option<u64>,
+    },
+    WindowsChanged {
+        windows: Vec<NiriWindow>,
+    },
+    WindowOpenedOrChanged {
+        window: NiriWindow,
+    },
+    WindowClosed {
+        id: u64,
+    },
+    WindowFocusChanged {
+        id: Option<u64>,
+    },
+}
+
+/// Client side of the niri JSON IPC socket (one request/reply per line).
+pub struct NiriIpc {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+}
+
+impl NiriIpc {
+    pub fn connect() -> anyhow::Result<Self> {
+        let path = std::env::var("NIRI_SOCKET").context("NIRI_SOCKET is not set")?;
+        Self::connect_to(path)
+    }
+
+    pub fn connect_to<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let writer = UnixStream::connect(path)
+            .with_context(|| format!("Failed to connect to niri socket {}", path.display()))?;
+        let reader = BufReader::new(writer.try_clone()?);
+        Ok(Self { reader, writer })
+    }
+
+    pub fn read_line(&mut self) -> anyhow::Result<serde_json::Value> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            anyhow::bail!("niri socket closed");
+        }
+        Ok(serde_json::from_str(&line)?)
+    }
+
+    /// Send a request and return the `Ok` payload of its reply.
+    pub fn request(&mut self, request: &serde_json::Value) -> anyhow::Result<serde_json::Value> {
+        let mut line = serde_json::to_string(request)?;
+        line.push('\n');
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.flush()?;
+
+        let mut reply = self.read_line()?;
+        if let Some(err) = reply.get("Err") {
+            anyhow::bail!("niri request {} failed: {}", request, err);
+        }
+        Ok(reply.get_mut("Ok").map(serde_json::Value::take).unwrap_or_default())
+    }
+
+    pub fn action(&mut self, action: serde_json::Value) -> anyhow::Result<()> {
+        self.request(&json!({ "Action": action }))?;
+        Ok(())
+    }
+}
+
+/// Window/workspace state mirrored from the event stream.
+#[derive(Debug, Default)]
+struct NiriState {
+    workspaces: HashMap<u64, NiriWorkspace>,
+    windows: HashMap<u64, NiriWindow>,
+}
+
+impl NiriState {
+    fn apply(&mut self, event: NiriEvent) {
+        match event {
+            NiriEvent::WorkspacesChanged { workspaces } => {
+                self.workspaces = workspaces.into_iter().map(|w| (w.id, w)).collect();
+            }
+            NiriEvent::WorkspaceActivated { id, focused } => {
+                let output = self.workspaces.get(&id).and_then(|w| w.output.clone());
+                for ws in self.workspaces.values_mut() {
+                    // Activation is per output; focus is global.
+                    if ws.output == output {
+                        ws.is_active = ws.id == id;
+                    }
+                    if focused {
+                        ws.is_focused = ws.id == id;
+                    }
+                }
+            }
+            NiriEvent::WorkspaceActiveWindowChanged {
+                workspace_id,
+                active_window_id,
+            } => {
+                if let Some(ws) = self.workspaces.get_mut(&workspace_id) {
+                    ws.active_window_id = active_window_id;
+                }
+            }
+            NiriEvent::WindowsChanged { windows } => {
+                self.windows = windows.into_iter().map(|w| (w.id, w)).collect();
+            }
+            NiriEvent::WindowOpenedOrChanged { window } => {
+                if window.is_focused {
+                    for w in self.windows.values_mut() {
+                        w.is_focused = false;
+                    }
+                }
+                self.windows.insert(window.id, window);
+            }
+            NiriEvent::WindowClosed { id } => {
+                self.windows.remove(&id);
+            }
+            NiriEvent::WindowFocusChanged { id } => {
+                for w in self.windows.values_mut() {
+                    w.is_focused = Some(w.id) == id;
+                }
+            }
+        }
+    }
+
+    fn send(&self, sender: &cb::Sender<AppMsg>) {
+        let mut workspaces: Vec<&NiriWorkspace> = self.workspaces.values().collect();
+        workspaces.sort_by_key(|w| (w.output.clone(), w.idx));
+
+        let mut active_id = 0;
+        let mut ws_out = Vec::with_capacity(workspaces.len());
+        for ws in workspaces {
+            if ws.is_focused {
+                active_id = ws.id as i32;
+            }
+            let windows: Vec<&NiriWindow> = self
+                .windows
+                .values()
+                .filter(|w| w.workspace_id == Some(ws.id))
+                .collect();
+            let last = ws.active_window_id.and_then(|id| self.windows.get(&id));
+            ws_out.push(HyprWorkspace {
+                // The stable id is what actions reference; the per-output index is what the
+                // user sees, so it drives labels and ordering.
+                id: ws.id as i32,
+                idx: ws.idx as i32,
+                name: ws.name.clone().unwrap_or_else(|| ws.idx.to_string()),
+                monitor: ws.output.clone().unwrap_or_default(),
+                windows: windows.len() as i32,
+                hasfullscreen: false,
+                lastwindow: last.map(|w| w.id.to_string()).unwrap_or_default(),
+                lastwindowtitle: last.and_then(|w| w.title.clone()).unwrap_or_default(),
+                // Named niri workspaces are real workspaces, not placeholders.
+                ispersistent: false,
+            });
+        }
+
+        let mut windows: Vec<&NiriWindow> = self.windows.values().collect();
+        windows.sort_by_key(|w| w.id);
+        let clients: Vec<HyprClient> = windows.iter().map(|w| self.client_from_window(w)).collect();
+
+        let urgent: Vec<String> = windows
+            .iter()
+            .filter(|w| w.is_urgent)
+            .map(|w| w.id.to_string())
+            .collect();
+        let _ = sender.send(AppMsg::UrgentWindows(urgent));
+        if let Some(focused) = windows.iter().find(|w| w.is_focused) {
+            let _ = sender.send(AppMsg::HyprActiveWindow(focused.title.clone().unwrap_or_default()));
+            let _ = sender.send(AppMsg::HyprActiveWindowAddress(focused.id.to_string()));
+        }
+        let _ = sender.send(AppMsg::HyprWorkspaces {
+            workspaces: ws_out,
+            active_id,
+        });
+        let _ = sender.send(AppMsg::HyprClients { clients });
+    }
+
+    fn client_from_window(&self, window: &NiriWindow) -> HyprClient {
+        let ws = window.workspace_id.and_then(|id| self.workspaces.get(&id));
+        let app_id = window.app_id.clone().unwrap_or_default();
+        HyprClient {
+            address: window.id.to_string(),
+            mapped: true,
+            hidden: false,
+            at: (0, 0),
+            size: (0, 0),
+            workspace: HyprWorkspaceInfo {
+                id: ws.map(|w| w.id as i32).unwrap_or(-1),
+                name: ws
+                    .map(|w| w.name.clone().unwrap_or_else(|| w.idx.to_string()))
+                    .unwrap_or_default(),
+            },
+            floating: window.is_floating,
+            fullscreen: false,
+            fullscreen_mode: 0,
+            maximized: false,
+            focus_history_id: 0,
+            pid: window.pid.unwrap_or(0),
+            xwayland: false,
+            title: window.title.clone().unwrap_or_default(),
+            initial_class: app_id.clone(),
+            class: app_id,
+        }
+    }
+}
+
+pub fn spawn_niri_listener(sender: cb::Sender<AppMsg>) {
+    thread::spawn(move || loop {
+        if let Err(err) = run_niri_listener(&sender) {
+            eprintln!("niri: IPC listener stopped: {err:#}");
+        }
+        thread::sleep(Duration::from_secs(2));
+    });
+}
+
+fn run_niri_listener(sender: &cb::Sender<AppMsg>) -> anyhow::Result<()> {
+    let mut ipc = NiriIpc::connect()?;
+    ipc.request(&json!("EventStream"))?;
+
+    // niri sends the full workspace/window state as the first events of the stream.
+    let mut state = NiriState::default();
+    loop {
+        let value = ipc.read_line()?;
+        let event = match serde_json::from_value::<NiriEvent>(value) {
+            Ok(ev) => ev,
+            Err(_) => continue,
+        };
+        state.apply(event);
+        state.send(sender);
+    }
+}
+
+pub fn send_niri_snapshot(sender: &cb::Sender<AppMsg>) {
+    let mut ipc = match NiriIpc::connect() {
+        Ok(ipc) => ipc,
+        Err(_) => return,
+    };
+
+    let mut state = NiriState::default();
+    if let Ok(reply) = ipc.request(&json!("Workspaces")) {
+        if let Some(workspaces) = reply.get("Workspaces").cloned() {
+            if let Ok(workspaces) = serde_json::from_value(workspaces) {
+                state.apply(NiriEvent::WorkspacesChanged { workspaces });
+            }
+        }
+    }
+
+    // One request per connection: niri closes the socket after replying.
+    let mut ipc = match NiriIpc::connect() {
+        Ok(ipc) => ipc,
+        Err(_) => return,
+    };
+    if let Ok(reply) = ipc.request(&json!("Windows")) {
+        if let Some(windows) = reply.get("Windows").cloned() {
+            if let Ok(windows) = serde_json::from_value(windows) {
+                state.apply(NiriEvent::WindowsChanged { windows });
+            }
+        }
+    }
+
+    state.send(sender);
+}
+
+fn niri_action(action: serde_json::Value) -> anyhow::Result<()> {
+    NiriIpc::connect()?.action(action)
+}
+
+pub fn niri_focus_workspace(id: i32) -> anyhow::Result<()> {
+    niri_action(json!({ "FocusWorkspace": { "reference": { "Id": id } } }))
+}
+
+pub fn niri_move_focused_to_workspace(id: i32, silent: bool) -> anyhow::Result<()> {
+    niri_action(json!({
+        "MoveWindowToWorkspace": {
+            "window_id": null,
+            "reference": { "Id": id },
+            "focus": !silent,
+        }
+    }))
+}
+
+pub fn niri_focus_window(id: &str) -> anyhow::Result<()> {
+    let id: u64 = id.parse().context("Invalid niri window id")?;
+    niri_action(json!({ "FocusWindow": { "id": id } }))
+}
+
+pub fn niri_move_window_to_workspace(id: &str, workspace_id: i32) -> anyhow::Result<()> {
+    let id: u64 = id.parse().context("Invalid niri window id")?;
+    niri_action(json!({
+        "MoveWindowToWorkspace": {
+            "window_id": id,
+            "reference": { "Id": workspace_id },
+            "focus": false,
+        }
+    }))
+}
+
+pub fn niri_close_window(id: &str) -> anyhow::Result<()> {
+    let id: u64 = id.parse().context("Invalid niri window id")?;
+    niri_action(json!({ "CloseWindow": { "id": id } }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::hyprland::workspace_nav_order;
+
+    fn apply_line(state: &mut NiriState, line: &str) {
+        let value: serde_json::Value = serde_json::from_str(line).unwrap();
+        state.apply(serde_json::from_value(value).unwrap());
+    }
+
+    fn snapshot(state: &NiriState) -> (Vec<HyprWorkspace>, i32, Vec<HyprClient>) {
+        let (tx, rx) = cb::unbounded();
+        state.send(&tx);
+        let (mut workspaces, mut active, mut clients) = (Vec::new(), 0, Vec::new());
+        for msg in rx.try_iter() {
+            match msg {
+                AppMsg::HyprWorkspaces { workspaces: w, active_id } => {
+                    workspaces = w;
+                    active = active_id;
+                }
+                AppMsg::HyprClients { clients: c } => clients = c,
+                _ => {}
+            }
+        }
+        (workspaces, active, clients)
+    }
+
+    const WORKSPACES: &str = r#"{"WorkspacesChanged":{"workspaces":[
+        {"id":5,"idx":1,"name":null,"output":"DP-1","is_urgent":false,"is_active":true,"is_focused":true,"active_window_id":12},
+        {"id":3,"idx":2,"name":null,"output":"DP-1","is_urgent":false,"is_active":false,"is_focused":false,"active_window_id":null},
+        {"id":8,"idx":1,"name":"web","output":"HDMI-A-1","is_urgent":false,"is_active":true,"is_focused":false,"active_window_id":null}]}}"#;
+
+    const WINDOWS: &str = r#"{"WindowsChanged":{"windows":[
+        {"id":12,"title":"foot","app_id":"foot","pid":100,"workspace_id":5,"is_focused":true,"is_floating":false,"is_urgent":false,"layout":{}},
+        {"id":13,"title":"Firefox","app_id":"firefox","pid":200,"workspace_id":8,"is_focused":false,"is_floating":false,"is_urgent":false}]}}"#;
+
+    #[test]
+    fn workspaces_use_id_for_dispatch_and_idx_for_labels() {
+        let mut state = NiriState::default();
+        apply_line(&mut state, WORKSPACES);
+        apply_line(&mut state, WINDOWS);
+
+        let (workspaces, active, clients) = snapshot(&state);
+        let summary: Vec<(i32, i32, &str, i32)> = workspaces
+            .iter()
+            .map(|w| (w.id, w.idx, w.name.as_str(), w.windows))
+            .collect();
+        assert_eq!(summary, vec![(5, 1, "1", 1), (3, 2, "2", 0), (8, 1, "web", 1)]);
+        assert_eq!(active, 5);
+        assert_eq!(workspaces[0].lastwindowtitle, "foot");
+
+        assert_eq!(clients.len(), 2);
+        assert_eq!((clients[0].address.as_str(), clients[0].workspace.id), ("12", 5));
+        assert_eq!((clients[1].class.as_str(), clients[1].workspace.name.as_str()), ("firefox", "web"));
+    }
+
+    #[test]
+    fn named_workspaces_are_navigation_candidates() {
+        let mut state = NiriState::default();
+        apply_line(&mut state, WORKSPACES);
+        let (workspaces, ..) = snapshot(&state);
+        assert!(workspaces.iter().all(|w| !w.ispersistent));
+        // Per-output idx first, so the named `web` (idx 1 on HDMI-A-1) sorts with DP-1's first.
+        assert_eq!(workspace_nav_order(&workspaces, None), vec![5, 8, 3]);
+        assert_eq!(workspace_nav_order(&workspaces, Some("HDMI-A-1")), vec![8]);
+    }
+
+    #[test]
+    fn window_opened_or_changed_updates_and_moves_focus() {
+        let mut state = NiriState::default();
+        apply_line(&mut state, WORKSPACES);
+        apply_line(&mut state, WINDOWS);
+        apply_line(
+            &mut state,
+            r#"{"WindowOpenedOrChanged":{"window":{"id":14,"title":"mpv","app_id":"mpv","pid":300,"workspace_id":3,"is_focused":true,"is_floating":true,"is_urgent":false}}}"#,
+        );
+        apply_line(
+            &mut state,
+            r#"{"WindowOpenedOrChanged":{"window":{"id":13,"title":"Mozilla Firefox","app_id":"firefox","pid":200,"workspace_id":8,"is_focused":false,"is_floating":false,"is_urgent":false}}}"#,
+        );
+
+        let (workspaces, _, clients) = snapshot(&state);
+        assert_eq!(workspaces.iter().find(|w| w.id == 3).unwrap().windows, 1);
+        let focused: Vec<&str> = state.windows.values().filter(|w| w.is_focused).map(|w| w.title.as_deref().unwrap()).collect();
+        assert_eq!(focused, vec!["mpv"]);
+        assert_eq!(clients.iter().find(|c| c.address == "13").unwrap().title, "Mozilla Firefox");
+        assert!(clients.iter().find(|c| c.address == "14").unwrap().floating);
+    }
+
+    #[test]
+    fn urgent_list_follows_the_hint() {
+        let urgent = |state: &NiriState| {
+            let (tx, rx) = cb::unbounded();
+            state.send(&tx);
+            rx.try_iter()
+                .find_map(|msg| match msg {
+                    AppMsg::UrgentWindows(addresses) => Some(addresses),
+                    _ => None,
+                })
+                .unwrap()
+        };
+        let mut state = NiriState::default();
+        apply_line(&mut state, WORKSPACES);
+        apply_line(&mut state, WINDOWS);
+        apply_line(
+            &mut state,
+            r#"{"WindowOpenedOrChanged":{"window":{"id":13,"title":"Firefox","app_id":"firefox","pid":200,"workspace_id":8,"is_focused":false,"is_floating":false,"is_urgent":true}}}"#,
+        );
+        assert_eq!(urgent(&state), vec!["13".to_string()]);
+
+        // Cleared by the window itself, not by focusing it.
+        apply_line(
+            &mut state,
+            r#"{"WindowOpenedOrChanged":{"window":{"id":13,"title":"Firefox","app_id":"firefox","pid":200,"workspace_id":8,"is_focused":false,"is_floating":false,"is_urgent":false}}}"#,
+        );
+        assert!(urgent(&state).is_empty());
+    }
+
+    #[test]
+    fn unknown_events_are_skipped() {
+        let value: serde_json::Value =
+            serde_json::from_str(r#"{"KeyboardLayoutsChanged":{"keyboard_layouts":{"names":["us"],"current_idx":0}}}"#).unwrap();
+        assert!(serde_json::from_value::<NiriEvent>(value).is_err());
+    }
+}
@@ -0,0 +1,605 @@
+use anyhow::{anyhow, bail, Context};
+use crossbeam_channel as cb;
+use gio::prelude::*;
+use gtk4 as gtk;
+use gtk::prelude::*;
+use serde_json::Value;
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use super::config::OpenWeatherConfig;
+
+/// How soon to try again after a failed fetch.
+const RETRY_INTERVAL: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone, Default)]
+pub struct CurrentWeather {
+    pub location: String,
+    pub icon: String,
+    pub description: String,
+    pub temp: f64,
+    pub feels_like: f64,
+    pub humidity: i64,
+    pub pressure: i64,
+    pub wind_speed: f64,
+    pub clouds: i64,
+    /// Metres.
+    pub visibility: i64,
+    pub sunrise: i64,
+    pub sunset: i64,
+    /// Seconds east of UTC at the location.
+    pub timezone: i64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ForecastEntry {
+    pub time: i64,
+    pub icon: String,
+    pub description: String,
+    pub temp: f64,
+    /// Probability of precipitation, 0..1.
+    pub pop: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WeatherData {
+    pub current: Option<CurrentWeather>,
+    pub forecast: Vec<ForecastEntry>,
+    /// Unix time of the newest response in use.
+    pub updated: i64,
+    /// The fetch failed and this is older cached data.
+    pub stale: bool,
+}
+
+/// Plain GET through GIO (TLS comes from glib-networking). HTTP/1.0 so the body is never
+/// chunked.
+fn http_get(url: &str, timeout: u32) -> anyhow::Result<String> {
+    let uri = glib::Uri::parse(url, glib::UriFlags::NONE).with_context(|| format!("bad URL {url}"))?;
+    let https = uri.scheme().eq_ignore_ascii_case("https");
+    let host = uri.host().ok_or_else(|| anyhow!("no host in {url}"))?;
+    let mut target = uri.path().to_string();
+    if target.is_empty() {
+        target.push('/');
+    }
+    if let Some(query) = uri.query() {
+        target.push('?');
+        target.push_str(&query);
+    }
+
+    let client = gio::SocketClient::new();
+    client.set_tls(https);
+    client.set_timeout(timeout);
+    let connection = client.connect_to_uri(url, if https { 443 } else { 80 }, None::<&gio::Cancellable>)?;
+
+    // Host carries the port when the URL names one; IPv6 literals need their brackets back.
+    let host_header = if host.contains(':') { format!("[{host}]") } else { host.to_string() };
+    let host_header = match uri.port() {
+        -1 => host_header,
+        port => format!("{host_header}:{port}"),
+    };
+    let request = format!(
+        "GET {target} HTTP/1.0\r\nHost: {host_header}\r\nUser-Agent: nwg-panel\r\nAccept: application/json\r\n\r\n"
+    );
+    connection
+        .output_stream()
+        .write_all(request.as_bytes(), None::<&gio::Cancellable>)
+        .map_err(|(_, err)| err)?;
+
+    let input = connection.input_stream();
+    let mut response = Vec::new();
+    loop {
+        let chunk = input.read_bytes(16 * 1024, None::<&gio::Cancellable>)?;
+        if chunk.is_empty() {
+            break;
+        }
+        response.extend_from_slice(&chunk);
+    }
+    let _ = connection.close(None::<&gio::Cancellable>);
+
+    let response = String::from_utf8_lossy(&response);
+    let (head, body) = response.split_once("\r\n\r\n").ok_or_else(|| anyhow!("malformed response"))?;
+    let status = head.split_whitespace().nth(1).unwrap_or_default();
+    if status != "200" {
+        bail!("HTTP {status} from {host}");
+    }
+    Ok(body.to_string())
+}
+
+fn endpoint_url(config: &OpenWeatherConfig, endpoint: &str) -> String {
+    format!(
+        "{}/{}?lat={}&lon={}&units={}&lang={}&appid={}",
+        config.base_url.trim_end_matches('/'),
+        endpoint,
+        config.lat,
+        config.long,
+        glib::Uri::escape_string(&config.units, None, false),
+        glib::Uri::escape_string(&config.lang, None, false),
+        glib::Uri::escape_string(&config.appid, None, false),
+    )
+}
+
+fn cache_path(cache_dir: &Path, config: &OpenWeatherConfig, endpoint: &str) -> PathBuf {
+    cache_dir.join(format!("nwg-openweather-{}-{}_{}", endpoint, config.lat, config.long))
+}
+
+fn cache_age(path: &Path) -> Option<Duration> {
+    SystemTime::now().duration_since(fs::metadata(path).ok()?.modified().ok()?).ok()
+}
+
+fn mtime_secs(path: &Path) -> i64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// The response for `endpoint` and its unix time: the cache while it is younger than
+/// `interval`, else a fresh fetch, else the expired cache flagged stale.
+fn load_endpoint(config: &OpenWeatherConfig, cache_dir: Option<&Path>, endpoint: &str) -> Option<(Value, i64, bool)> {
+    let path = cache_dir.map(|dir| cache_path(dir, config, endpoint));
+    let cached = |path: &Path| -> Option<Value> { serde_json::from_str(&fs::read_to_string(path).ok()?).ok() };
+
+    if let Some(path) = path.as_ref() {
+        if cache_age(path).is_some_and(|age| age.as_secs() < config.interval) {
+            if let Some(value) = cached(path) {
+                return Some((value, mtime_secs(path), false));
+            }
+        }
+    }
+
+    let fetched = http_get(&endpoint_url(config, endpoint), config.timeout)
+        .and_then(|body| Ok((serde_json::from_str::<Value>(&body)?, body)));
+    match fetched {
+        Ok((value, body)) => {
+            if let Some(path) = path.as_ref() {
+                if let Err(err) = fs::write(path, body) {
+                    eprintln!("openweather: failed to write {}: {err}", path.display());
+                }
+            }
+            let now = chrono::Utc::now().timestamp();
+            Some((value, now, false))
+        }
+        Err(err) => {
+            eprintln!("openweather: {endpoint}: {err:#}");
+            let path = path?;
+            Some((cached(&path)?, mtime_secs(&path), true))
+        }
+    }
+}
+
+fn first_weather(value: &Value) -> (String, String) {
+    let weather = &value["weather"][0];
+    (
+        weather["icon"].as_str().unwrap_or_default().to_string(),
+        weather["description"].as_str().unwrap_or_default().to_string(),
+    )
+}
+
+fn parse_current(value: &Value) -> CurrentWeather {
+    let (icon, description) = first_weather(value);
+    CurrentWeather {
+        location: value["name"].as_str().unwrap_or_default().to_string(),
+        icon,
+        description,
+        temp: value["main"]["temp"].as_f64().unwrap_or(0.0),
+        feels_like: value["main"]["feels_like"].as_f64().unwrap_or(0.0),
+        humidity: value["main"]["humidity"].as_i64().unwrap_or(0),
+        pressure: value["main"]["pressure"].as_i64().unwrap_or(0),
+        wind_speed: value["wind"]["speed"].as_f64().unwrap_or(0.0),
+        clouds: value["clouds"]["all"].as_i64().unwrap_or(0),
+        visibility: value["visibility"].as_i64().unwrap_or(0),
+        sunrise: value["sys"]["sunrise"].as_i64().unwrap_or(0),
+        sunset: value["sys"]["sunset"].as_i64().unwrap_or(0),
+        timezone: value["timezone"].as_i64().unwrap_or(0),
+    }
+}
+
+fn parse_forecast(value: &Value) -> Vec<ForecastEntry> {
+    value["list"]
+        .as_array()
+        .map(|list| {
+            list.iter()
+                .map(|entry| {
+                    let (icon, description) = first_weather(entry);
+                    ForecastEntry {
+                        time: entry["dt"].as_i64().unwrap_or(0),
+                        icon,
+                        description,
+                        temp: entry["main"]["temp"].as_f64().unwrap_or(0.0),
+                        pop: entry["pop"].as_f64().unwrap_or(0.0),
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn load_weather(config: &OpenWeatherConfig) -> WeatherData {
+    load_weather_in(config, dirs::cache_dir().as_deref())
+}
+
+/// `load_weather` with the response cache kept in `cache_dir` (none when `None`).
+fn load_weather_in(config: &OpenWeatherConfig, cache_dir: Option<&Path>) -> WeatherData {
+    let current = load_endpoint(config, cache_dir, "weather");
+    let forecast = load_endpoint(config, cache_dir, "forecast");
+    WeatherData {
+        current: current.as_ref().map(|(v, ..)| parse_current(v)),
+        forecast: forecast.as_ref().map(|(v, ..)| parse_forecast(v)).unwrap_or_default(),
+        updated: current.as_ref().map(|(_, t, _)| *t).unwrap_or(0),
+        stale: current.as_ref().is_some_and(|(.., s)| *s) || forecast.as_ref().is_some_and(|(.., s)| *s),
+    }
+}
+
+/// OpenWeather icon code (`10d`) to a freedesktop weather icon.
+fn weather_icon_name(code: &str) -> &'static str {
+    let night = code.ends_with('n');
+    match code.get(..2).unwrap_or_default() {
+        "01" if night => "weather-clear-night",
+        "01" => "weather-clear",
+        "02" if night => "weather-few-clouds-night",
+        "02" => "weather-few-clouds",
+        "03" | "04" => "weather-overcast",
+        "09" => "weather-showers",
+        "10" => "weather-showers-scattered",
+        "11" => "weather-storm",
+        "13" => "weather-snow",
+        "50" => "weather-fog",
+        _ => "weather-severe-alert",
+    }
+}
+
+fn temp_unit(units: &str) -> &'static str {
+    match units {
+        "imperial" => "°F",
+        "standard" => "K",
+        _ => "°C",
+    }
+}
+
+fn speed_unit(units: &str) -> &'static str {
+    if units == "imperial" { "mph" } else { "m/s" }
+}
+
+/// `timestamp` at the location's UTC offset.
+fn local_time(timestamp: i64, offset: i64, format: &str) -> String {
+    chrono::DateTime::from_timestamp(timestamp + offset, 0)
+        .map(|t| t.naive_utc().format(format).to_string())
+        .unwrap_or_default()
+}
+
+/// The `openweather` module: icon and temperature, with details and the forecast in a
+/// popover. Refreshes every `interval` seconds from a background thread.
+#[derive(Clone)]
+pub struct OpenWeatherUi {
+    root: gtk::Button,
+    image: gtk::Image,
+    label: gtk::Label,
+    popover_box: gtk::Box,
+    config: OpenWeatherConfig,
+}
+
+impl OpenWeatherUi {
+    pub fn new(config: OpenWeatherConfig) -> Self {
+        let root = gtk::Button::new();
+        root.set_widget_name("openweather");
+        root.add_css_class("flat");
+
+        let content = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        let image = gtk::Image::from_icon_name("weather-severe-alert");
+        image.set_pixel_size(config.icon_size);
+        content.append(&image);
+        let label = gtk::Label::new(Some("…"));
+        content.append(&label);
+        root.set_child(Some(&content));
+
+        let popover = gtk::Popover::new();
+        popover.set_parent(&root);
+        popover.set_widget_name("openweather-popup");
+        let popover_box = gtk::Box::new(gtk::Orientation::Vertical, 6);
+        popover.set_child(Some(&popover_box));
+        root.connect_clicked({
+            let popover = popover.clone();
+            move |_| popover.popup()
+        });
+
+
+        let ui = Self {
+            root,
+            image,
+            label,
+            popover_box,
+            config,
+        };
+
+        let (sender, receiver) = cb::unbounded::<WeatherData>();
+        let thread_config = ui.config.clone();
+        thread::spawn(move || loop {
+            let data = load_weather(&thread_config);
+            let wait = if data.stale || data.current.is_none() {
+                RETRY_INTERVAL.min(Duration::from_secs(thread_config.interval))
+            } else {
+                Duration::from_secs(thread_config.interval)
+            };
+            // The widget is gone once the receiver is dropped.
+            if sender.send(data).is_err() {
+                return;
+            }
+            thread::sleep(wait.max(Duration::from_secs(1)));
+        });
+
+        let ui_for_poll = ui.clone();
+        let source = glib::timeout_add_local(Duration::from_millis(500), move || {
+            while let Ok(data) = receiver.try_recv() {
+                ui_for_poll.set_weather(&data);
+            }
+            glib::ControlFlow::Continue
+        });
+        let source = Rc::new(RefCell::new(Some(source)));
+        ui.root.connect_destroy(move |_| {
+            if let Ok(mut source) = source.try_borrow_mut() {
+                if let Some(id) = source.take() {
+                    id.remove();
+                }
+            }
+            popover.unparent();
+        });
+
+        ui
+    }
+
+    pub fn widget(&self) -> gtk::Widget {
+        self.root.clone().upcast()
+    }
+
+    fn set_weather(&self, data: &WeatherData) {
+        if data.stale {
+            self.root.add_css_class("stale");
+        } else {
+            self.root.remove_css_class("stale");
+        }
+
+        let current = match data.current.as_ref() {
+            Some(c) => c,
+            None => {
+                self.image.set_icon_name(Some("weather-severe-alert"));
+                self.label.set_text("?");
+                self.root.set_tooltip_text(Some("No weather data"));
+                return;
+            }
+        };
+
+        let unit = temp_unit(&self.config.units);
+        let location = if self.config.loc_name.is_empty() {
+            current.location.clone()
+        } else {
+            self.config.loc_name.clone()
+        };
+        self.image.set_icon_name(Some(weather_icon_name(&current.icon)));
+        let mut text = format!("{:.0}{}", current.temp, unit);
+        if self.config.show_name && !location.is_empty() {
+            text = format!("{location} {text}");
+        }
+        self.label.set_text(&text);
+
+        let updated = chrono::DateTime::from_timestamp(data.updated, 0)
+            .map(|t| t.with_timezone(&chrono::Local).format("%H:%M").to_string())
+            .unwrap_or_default();
+        let mut tooltip = format!("{location}: {}\nUpdated {updated}", current.description);
+        if data.stale {
+            tooltip.push_str(" (offline)");
+        }
+        self.root.set_tooltip_text(Some(&tooltip));
+
+        self.fill_popover(data, current, &location);
+    }
+
+    fn fill_popover(&self, data: &WeatherData, current: &CurrentWeather, location: &str) {
+        while let Some(child) = self.popover_box.first_child() {
+            self.popover_box.remove(&child);
+        }
+        let unit = temp_unit(&self.config.units);
+
+        let header = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+        header.set_widget_name("openweather-header");
+        let icon = gtk::Image::from_icon_name(weather_icon_name(&current.icon));
+        icon.set_pixel_size(self.config.popup_icon_size * 2);
+        header.append(&icon);
+        let summary = gtk::Label::new(None);
+        summary.set_xalign(0.0);
+        summary.set_markup(&format!(
+            "<b>{}</b>\n<big>{:.0}{unit}</big>  {}",
+            glib::markup_escape_text(location),
+            current.temp,
+            glib::markup_escape_text(&current.description),
+        ));
+        header.append(&summary);
+        self.popover_box.append(&header);
+
+        let details = [
+            format!("Feels like {:.0}{unit}", current.feels_like),
+            format!("Humidity {}%", current.humidity),
+            format!("Wind {:.1} {}", current.wind_speed, speed_unit(&self.config.units)),
+            format!("Pressure {} hPa", current.pressure),
+            format!("Clouds {}%", current.clouds),
+            format!("Visibility {:.1} km", current.visibility as f64 / 1000.0),
+            format!(
+                "Sunrise {}  Sunset {}",
+                local_time(current.sunrise, current.timezone, "%H:%M"),
+                local_time(current.sunset, current.timezone, "%H:%M")
+            ),
+        ];
+        let details_label = gtk::Label::new(Some(&details.join("\n")));
+        details_label.set_xalign(0.0);
+        self.popover_box.append(&details_label);
+
+        if data.stale {
+            let stale = gtk::Label::new(Some("Offline, showing cached data"));
+            stale.add_css_class("stale");
+            stale.set_xalign(0.0);
+            self.popover_box.append(&stale);
+        }
+
+        if data.forecast.is_empty() {
+            return;
+        }
+        let list = gtk::Box::new(gtk::Orientation::Vertical, 2);
+        list.set_widget_name("openweather-forecast");
+        for entry in &data.forecast {
+            let row = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+            let when = gtk::Label::new(Some(&local_time(entry.time, current.timezone, "%a %H:%M")));
+            when.set_width_chars(10);
+            when.set_xalign(0.0);
+            row.append(&when);
+            let icon = gtk::Image::from_icon_name(weather_icon_name(&entry.icon));
+            icon.set_pixel_size(self.config.popup_icon_size);
+            row.append(&icon);
+            let temp = gtk::Label::new(Some(&format!("{:.0}{unit}", entry.temp)));
+            temp.set_width_chars(6);
+            row.append(&temp);
+            let text = if entry.pop > 0.0 {
+                format!("{} ({:.0}%)", entry.description, entry.pop * 100.0)
+            } else {
+                entry.description.clone()
+            };
+            let description = gtk::Label::new(Some(&text));
+            description.set_xalign(0.0);
+            row.append(&description);
+            list.append(&row);
+        }
+        let scrolled = gtk::ScrolledWindow::new();
+        scrolled.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+        scrolled.set_min_content_height(300);
+        scrolled.set_propagate_natural_width(true);
+        scrolled.set_child(Some(&list));
+        self.popover_box.append(&scrolled);
+    }
+}
+
+#[cfg(all(test, feature = "openweather"))]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    const CURRENT: &str = r#"{"name":"Warsaw","weather":[{"icon":"10d","description":"light rain"}],
+        "main":{"temp":18.5,"feels_like":18.0,"humidity":70,"pressure":1012},"timezone":7200}"#;
+    const FORECAST: &str = r#"{"list":[
+        {"dt":1700000000,"weather":[{"icon":"01d","description":"clear sky"}],"main":{"temp":20.0},"pop":0.0},
+        {"dt":1700010800,"weather":[{"icon":"10n","description":"rain"}],"main":{"temp":15.0},"pop":0.6}]}"#;
+
+    /// An HTTP stub on localhost that answers `weather` and `forecast`, or 500 while
+    /// `failing` is set. Request heads are kept for inspection.
+    struct Stub {
+        base_url: String,
+        requests: Arc<Mutex<Vec<String>>>,
+        failing: Arc<AtomicBool>,
+    }
+
+    impl Stub {
+        fn start() -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let base_url = format!("http://{}/data/2.5", listener.local_addr().unwrap());
+            let requests: Arc<Mutex<Vec<String>>> = Arc::default();
+            let failing: Arc<AtomicBool> = Arc::default();
+            let (requests_for_thread, failing_for_thread) = (requests.clone(), failing.clone());
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let mut stream = match stream {
+                        Ok(s) => s,
+                        Err(_) => return,
+                    };
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut head = String::new();
+                    loop {
+                        let mut line = String::new();
+                        if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                            break;
+                        }
+                        head.push_str(&line);
+                    }
+                    let body = if head.starts_with("GET /data/2.5/forecast") { FORECAST } else { CURRENT };
+                    let response = if failing_for_thread.load(Ordering::SeqCst) {
+                        "HTTP/1.0 500 Internal Server Error\r\n\r\n".to_string()
+                    } else {
+                        format!("HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n{body}")
+                    };
+                    requests_for_thread.lock().unwrap().push(head);
+                    let _ = stream.write_all(response.as_bytes());
+                }
+            });
+            Self {
+                base_url,
+                requests,
+                failing,
+            }
+        }
+
+        fn requests(&self) -> Vec<String> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    fn cache_dir() -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "nwg-panel-openweather-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn load_weather_fetches_then_caches_then_falls_back_to_stale() {
+        let stub = Stub::start();
+        let dir = cache_dir();
+        let mut config = OpenWeatherConfig {
+            base_url: stub.base_url.clone(),
+            lat: 52.23,
+            long: 21.01,
+            interval: 600,
+            timeout: 5,
+            ..Default::default()
+        };
+
+        // Fresh: both endpoints are fetched and cached.
+        let data = load_weather_in(&config, Some(&dir));
+        let requests = stub.requests();
+        assert_eq!(requests.len(), 2);
+        let port = stub.base_url.trim_start_matches("http://127.0.0.1:").split('/').next().unwrap();
+        assert!(requests[0].contains(&format!("Host: 127.0.0.1:{port}\r\n")), "{}", requests[0]);
+        assert!(!data.stale);
+        let current = data.current.unwrap();
+        assert_eq!((current.location.as_str(), current.temp), ("Warsaw", 18.5));
+        assert_eq!(data.forecast.len(), 2);
+        assert_eq!(data.forecast[1].description, "rain");
+        assert!(data.updated > 0);
+
+        // Cached: younger than `interval`, so the server isn't asked again.
+        let data = load_weather_in(&config, Some(&dir));
+        assert_eq!(stub.requests().len(), 2);
+        assert!(!data.stale);
+        assert_eq!(data.current.unwrap().location, "Warsaw");
+
+        // Stale: the cache has expired and the fetch fails, so the old data is flagged.
+        config.interval = 0;
+        stub.failing.store(true, Ordering::SeqCst);
+        let data = load_weather_in(&config, Some(&dir));
+        assert_eq!(stub.requests().len(), 4);
+        assert!(data.stale);
+        assert_eq!(data.current.unwrap().temp, 18.5);
+        assert_eq!(data.forecast.len(), 2);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,277 @@
+use gtk4 as gtk;
+use gdk4 as gdk;
+use gtk::prelude::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::thread;
+
+use super::app_info::resolve_app;
+use super::compositor::Compositor;
+use super::config::PinnedConfig;
+use super::hyprland::HyprClient;
+
+/// The pin list shared with nwg-drawer / nwg-menu: one desktop id per line.
+pub fn pin_cache_path() -> Option<PathBuf> {
+    Some(dirs::cache_dir()?.join("nwg-pin-cache"))
+}
+
+/// Pinned desktop ids, in file order. A missing cache is an empty list.
+pub fn load_pin_cache() -> Vec<String> {
+    pin_cache_path()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .map(|text| {
+            text.lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn save_pin_cache(ids: &[String]) -> anyhow::Result<()> {
+    let path = pin_cache_path().ok_or_else(|| anyhow::anyhow!("No cache directory"))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut text = ids.join("\n");
+    text.push('\n');
+    fs::write(path, text)?;
+    Ok(())
+}
+
+/// Compare ids the way they appear in configs and caches: with or without `.desktop`.
+pub fn normalize_id(id: &str) -> String {
+    id.trim().trim_end_matches(".desktop").to_lowercase()
+}
+
+fn desktop_file_name(id: &str) -> String {
+    let id = id.trim();
+    if id.ends_with(".desktop") {
+        id.to_string()
+    } else {
+        format!("{}.desktop", id)
+    }
+}
+
+pub fn is_pinned(app_id: &str) -> bool {
+    let wanted = normalize_id(app_id);
+    load_pin_cache().iter().any(|id| normalize_id(id) == wanted)
+}
+
+/// Append `app_id` to the pin cache (as `<id>.desktop`, like the drawer writes it).
+pub fn pin_app(app_id: &str) -> anyhow::Result<()> {
+    let mut ids = load_pin_cache();
+    let wanted = normalize_id(app_id);
+    if ids.iter().any(|id| normalize_id(id) == wanted) {
+        return Ok(());
+    }
+    ids.push(desktop_file_name(app_id));
+    save_pin_cache(&ids)
+}
+
+pub fn unpin_app(app_id: &str) -> anyhow::Result<()> {
+    let mut ids = load_pin_cache();
+    let wanted = normalize_id(app_id);
+    let before = ids.len();
+    ids.retain(|id| normalize_id(id) != wanted);
+    if ids.len() == before {
+        return Ok(());
+    }
+    save_pin_cache(&ids)
+}
+
+/// Launch a desktop entry with the display's launch context (startup notification).
+pub fn launch_app(info: &gio::DesktopAppInfo) {
+    let context = gdk::Display::default().map(|d| d.app_launch_context());
+    if let Err(e) = info.launch(&[], context.as_ref()) {
+        eprintln!("failed to launch {}: {}", info.display_name(), e);
+    }
+}
+
+/// Launcher buttons for the `pinned` config list plus the pin cache. Apps with an open
+/// window get the `running` CSS class, and clicking them focuses it instead of launching.
+#[derive(Clone)]
+pub struct PinnedUi {
+    root: gtk::Box,
+    config: PinnedConfig,
+    compositor: Compositor,
+    buttons: Rc<RefCell<Vec<(String, gtk::Button)>>>,
+    /// Normalized desktop id -> address of a window of that app.
+    running: Rc<RefCell<HashMap<String, String>>>,
+    clients: Rc<RefCell<Vec<HyprClient>>>,
+    monitor: Rc<RefCell<Option<gio::FileMonitor>>>,
+}
+
+impl PinnedUi {
+    pub fn new(config: PinnedConfig, compositor: Compositor) -> Self {
+        let root = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+        root.set_widget_name("pinned");
+
+        let ui = Self {
+            root,
+            config,
+            compositor,
+            buttons: Rc::new(RefCell::new(Vec::new())),
+            running: Rc::new(RefCell::new(HashMap::new())),
+            clients: Rc::new(RefCell::new(Vec::new())),
+            monitor: Rc::new(RefCell::new(None)),
+        };
+        ui.rebuild();
+        if ui.config.use_pin_cache {
+            ui.watch_pin_cache();
+        }
+        ui
+    }
+
+    pub fn widget(&self) -> gtk::Widget {
+        self.root.clone().upcast()
+    }
+
+    /// Pins from the taskbar menu (or the drawer) land in the cache file; follow it.
+    fn watch_pin_cache(&self) {
+        let path = match pin_cache_path() {
+            Some(p) => p,
+            None => return,
+        };
+        let monitor = match gio::File::for_path(&path)
+            .monitor_file(gio::FileMonitorFlags::NONE, None::<&gio::Cancellable>)
+        {
+            Ok(m) => m,
+            Err(e) => {
+                eprintln!("pinned: cannot watch {}: {}", path.display(), e);
+                return;
+            }
+        };
+        let ui = self.clone();
+        monitor.connect_changed(move |_, _, _, event| {
+            if matches!(
+                event,
+                gio::FileMonitorEvent::ChangesDoneHint
+                    | gio::FileMonitorEvent::Created
+                    | gio::FileMonitorEvent::Deleted
+            ) {
+                ui.rebuild();
+            }
+        });
+        if let Ok(mut slot) = self.monitor.try_borrow_mut() {
+            *slot = Some(monitor);
+        }
+    }
+
+    /// Config apps first, then cache entries, without duplicates.
+    fn pinned_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.config.apps.clone();
+        if self.config.use_pin_cache {
+            ids.extend(load_pin_cache());
+        }
+        let mut seen = Vec::new();
+        ids.retain(|id| {
+            let key = normalize_id(id);
+            let new = !key.is_empty() && !seen.contains(&key);
+            seen.push(key);
+            new
+        });
+        ids
+    }
+
+    fn rebuild(&self) {
+        let mut buttons = match self.buttons.try_borrow_mut() {
+            Ok(b) => b,
+            Err(_) => return,
+        };
+        for (_, btn) in buttons.drain(..) {
+            self.root.remove(&btn);
+        }
+
+        for id in self.pinned_ids() {
+            let info = match gio::DesktopAppInfo::new(&desktop_file_name(&id)) {
+                Some(i) => i,
+                None => {
+                    eprintln!("pinned: no desktop entry for '{}'", id);
+                    continue;
+                }
+            };
+            let btn = self.create_button(&id, &info);
+            self.root.append(&btn);
+            buttons.push((normalize_id(&id), btn));
+        }
+        self.root.set_visible(!buttons.is_empty());
+        drop(buttons);
+
+        self.update_running();
+    }
+
+    fn create_button(&self, id: &str, info: &gio::DesktopAppInfo) -> gtk::Button {
+        let btn = gtk::Button::new();
+        btn.set_widget_name("pinned-item");
+        btn.set_tooltip_text(Some(&info.display_name()));
+
+        let content = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        let icon = match info.icon() {
+            Some(gicon) => gtk::Image::from_gicon(&gicon),
+            None => gtk::Image::from_icon_name("application-x-executable"),
+        };
+        icon.set_pixel_size(self.config.icon_size);
+        content.append(&icon);
+        if self.config.show_names {
+            content.append(&gtk::Label::new(Some(&info.display_name())));
+        }
+        btn.set_child(Some(&content));
+
+        let compositor = self.compositor;
+        let running = self.running.clone();
+        let key = normalize_id(id);
+        let info = info.clone();
+        btn.connect_clicked(move |_| {
+            let address = running.try_borrow().ok().and_then(|r| r.get(&key).cloned());
+            match address {
+                Some(address) => {
+                    thread::spawn(move || {
+                        let _ = compositor.focus_address(&address);
+                    });
+                }
+                None => launch_app(&info),
+            }
+        });
+
+        btn
+    }
+
+    pub fn set_clients(&self, clients: Vec<HyprClient>) {
+        match self.clients.try_borrow_mut() {
+            Ok(mut c) => *c = clients,
+            Err(_) => return,
+        }
+        self.update_running();
+    }
+
+    fn update_running(&self) {
+        let running: HashMap<String, String> = match self.clients.try_borrow() {
+            Ok(clients) => clients
+                .iter()
+                .filter_map(|c| {
+                    let app = resolve_app(&c.class, &c.initial_class, c.pid)?;
+                    Some((normalize_id(&app.id), c.address.clone()))
+                })
+                .collect(),
+            Err(_) => return,
+        };
+
+        if let Ok(buttons) = self.buttons.try_borrow() {
+            for (key, btn) in buttons.iter() {
+                if running.contains_key(key) {
+                    btn.add_css_class("running");
+                } else {
+                    btn.remove_css_class("running");
+                }
+            }
+        }
+        if let Ok(mut r) = self.running.try_borrow_mut() {
+            *r = running;
+        }
+    }
+}
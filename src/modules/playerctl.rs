@@ -0,0 +1,380 @@
+use gtk4 as gtk;
+use gtk::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::thread;
+use std::time::Duration;
+
+use super::config::PlayerctlConfig;
+use super::mpris::{mpris_call, spawn_mpris_seeker, MprisPlayer, MprisSeek};
+
+/// `m:ss` (or `h:mm:ss`) for a position in microseconds.
+fn format_time(micros: i64) -> String {
+    let secs = micros.max(0) / 1_000_000;
+    let (h, m, s) = (secs / 3600, (secs / 60) % 60, secs % 60);
+    if h > 0 {
+        format!("{}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{}:{:02}", m, s)
+    }
+}
+
+fn track_text(player: &MprisPlayer) -> String {
+    let artists = player.artists.join(", ");
+    match (artists.is_empty(), player.title.is_empty()) {
+        (false, false) => format!("{} - {}", artists, player.title),
+        (true, false) => player.title.clone(),
+        (false, true) => artists,
+        (true, true) => player.identity.clone(),
+    }
+}
+
+/// The `playerctl` module: prev/play-pause/next, the current track (scrolling when longer
+/// than `chars`), and a popover with cover art, a seek slider and the player choice.
+#[derive(Clone)]
+pub struct PlayerctlUi {
+    root: gtk::Box,
+    prev: gtk::Button,
+    play_pause: gtk::Button,
+    next: gtk::Button,
+    label: gtk::Label,
+    popover: gtk::Popover,
+    art: gtk::Image,
+    title: gtk::Label,
+    artist: gtk::Label,
+    album: gtk::Label,
+    seek: gtk::Scale,
+    time: gtk::Label,
+    selector: gtk::DropDown,
+    selector_model: gtk::StringList,
+    players: Rc<RefCell<Vec<MprisPlayer>>>,
+    /// Bus name the user picked in the popover; otherwise the first playing player is shown.
+    selected: Rc<RefCell<Option<String>>>,
+    art_url: Rc<RefCell<String>>,
+    text: Rc<RefCell<String>>,
+    scroll_offset: Rc<Cell<usize>>,
+    updating_selector: Rc<Cell<bool>>,
+    scroll_source: Rc<RefCell<Option<glib::SourceId>>>,
+    config: PlayerctlConfig,
+}
+
+impl PlayerctlUi {
+    pub fn new(config: PlayerctlConfig) -> Self {
+        let root = gtk::Box::new(gtk::Orientation::Horizontal, 2);
+        root.set_widget_name("playerctl");
+        root.set_visible(false);
+
+        let media_button = |icon: &str| {
+            let btn = gtk::Button::new();
+            let image = gtk::Image::from_icon_name(icon);
+            image.set_pixel_size(config.icon_size);
+            btn.set_child(Some(&image));
+            if !config.button_css_name.is_empty() {
+                btn.set_widget_name(&config.button_css_name);
+            }
+            btn
+        };
+        let prev = media_button("media-skip-backward-symbolic");
+        let play_pause = media_button("media-playback-start-symbolic");
+        let next = media_button("media-skip-forward-symbolic");
+
+        let label = gtk::Label::new(None);
+        if !config.label_css_name.is_empty() {
+            label.set_widget_name(&config.label_css_name);
+        }
+        let label_button = gtk::Button::new();
+        label_button.set_has_frame(false);
+        label_button.set_child(Some(&label));
+
+        let buttons = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+        buttons.append(&prev);
+        buttons.append(&play_pause);
+        buttons.append(&next);
+        if config.buttons_position == "right" {
+            root.append(&label_button);
+            root.append(&buttons);
+        } else {
+            root.append(&buttons);
+            root.append(&label_button);
+        }
+
+        // Popover: cover, track info, seek slider, player selection
+        let popover = gtk::Popover::new();
+        popover.set_widget_name("playerctl-popover");
+        popover.set_parent(&label_button);
+        root.connect_destroy({
+            let popover = popover.clone();
+            move |_| popover.unparent()
+        });
+        let details = gtk::Box::new(gtk::Orientation::Vertical, 4);
+
+        let art = gtk::Image::from_icon_name("audio-x-generic-symbolic");
+        art.set_pixel_size(config.art_size);
+        details.append(&art);
+
+        let title = gtk::Label::new(None);
+        title.set_widget_name("playerctl-title");
+        let artist = gtk::Label::new(None);
+        artist.set_widget_name("playerctl-artist");
+        let album = gtk::Label::new(None);
+        album.set_widget_name("playerctl-album");
+        for l in [&title, &artist, &album] {
+            l.set_wrap(true);
+            l.set_max_width_chars(40);
+            details.append(l);
+        }
+
+        let seek = gtk::Scale::with_range(gtk::Orientation::Horizontal, 0.0, 1.0, 1.0);
+        seek.set_draw_value(false);
+        seek.set_hexpand(true);
+        details.append(&seek);
+        let time = gtk::Label::new(None);
+        time.set_widget_name("playerctl-time");
+        details.append(&time);
+
+        let selector_model = gtk::StringList::new(&[]);
+        let selector = gtk::DropDown::new(Some(selector_model.clone()), None::<gtk::Expression>);
+        details.append(&selector);
+
+        popover.set_child(Some(&details));
+
+        let ui = Self {
+            root,
+            prev,
+            play_pause,
+            next,
+            label,
+            popover,
+            art,
+            title,
+            artist,
+            album,
+            seek,
+            time,
+            selector,
+            selector_model,
+            players: Rc::new(RefCell::new(Vec::new())),
+            selected: Rc::new(RefCell::new(None)),
+            art_url: Rc::new(RefCell::new(String::new())),
+            text: Rc::new(RefCell::new(String::new())),
+            scroll_offset: Rc::new(Cell::new(0)),
+            updating_selector: Rc::new(Cell::new(false)),
+            scroll_source: Rc::new(RefCell::new(None)),
+            config,
+        };
+
+        for (btn, method) in [(&ui.prev, "Previous"), (&ui.play_pause, "PlayPause"), (&ui.next, "Next")] {
+            let ui_for_click = ui.clone();
+            btn.connect_clicked(move |_| {
+                if let Some(player) = ui_for_click.current() {
+                    thread::spawn(move || {
+                        let _ = mpris_call(&player.bus_name, method);
+                    });
+                }
+            });
+        }
+
+        {
+            let popover = ui.popover.clone();
+            label_button.connect_clicked(move |_| popover.popup());
+        }
+
+        {
+            // Only user changes emit change-value, so backend updates don't seek.
+            let ui_for_seek = ui.clone();
+            let seeker = spawn_mpris_seeker();
+            ui.seek.connect_change_value(move |_, _, value| {
+                if let Some(player) = ui_for_seek.current() {
+                    let _ = seeker.send(MprisSeek {
+                        bus_name: player.bus_name,
+                        track_id: player.track_id,
+                        position: (value.max(0.0) * 1_000_000.0) as i64,
+                    });
+                }
+                glib::Propagation::Proceed
+            });
+        }
+
+        {
+            let ui_for_select = ui.clone();
+            ui.selector.connect_selected_notify(move |dropdown| {
+                if ui_for_select.updating_selector.get() {
+                    return;
+                }
+                let bus_name = ui_for_select
+                    .players
+                    .try_borrow()
+                    .ok()
+                    .and_then(|p| p.get(dropdown.selected() as usize).map(|p| p.bus_name.clone()));
+                if let Ok(mut selected) = ui_for_select.selected.try_borrow_mut() {
+                    *selected = bus_name;
+                }
+                ui_for_select.update();
+            });
+        }
+
+        if ui.config.scroll {
+            let ui_for_scroll = ui.clone();
+            let id = glib::timeout_add_local(Duration::from_millis(500), move || {
+                ui_for_scroll.scroll_offset.set(ui_for_scroll.scroll_offset.get() + 1);
+                ui_for_scroll.render_label();
+                glib::ControlFlow::Continue
+            });
+            if let Ok(mut source) = ui.scroll_source.try_borrow_mut() {
+                *source = Some(id);
+            }
+            let scroll_source = ui.scroll_source.clone();
+            ui.root.connect_destroy(move |_| {
+                if let Some(id) = scroll_source.borrow_mut().take() {
+                    id.remove();
+                }
+            });
+        }
+
+        ui
+    }
+
+    pub fn widget(&self) -> gtk::Widget {
+        self.root.clone().upcast()
+    }
+
+    /// The player to show: the one picked in the popover, else the first one playing, else
+    /// the first one.
+    fn current(&self) -> Option<MprisPlayer> {
+        let players = self.players.try_borrow().ok()?;
+        let selected = self.selected.try_borrow().ok().and_then(|s| s.clone());
+        selected
+            .and_then(|name| players.iter().find(|p| p.bus_name == name))
+            .or_else(|| players.iter().find(|p| p.is_playing()))
+            .or_else(|| players.first())
+            .cloned()
+    }
+
+    pub fn set_players(&self, players: Vec<MprisPlayer>) {
+        match self.players.try_borrow_mut() {
+            Ok(mut p) => *p = players,
+            Err(_) => return,
+        }
+        self.update();
+    }
+
+    fn update(&self) {
+        let (names, index) = match self.players.try_borrow() {
+            Ok(players) => {
+                let current = self.current().map(|p| p.bus_name);
+                let names: Vec<String> = players.iter().map(|p| p.identity.clone()).collect();
+                let index = players.iter().position(|p| Some(&p.bus_name) == current.as_ref());
+                (names, index)
+            }
+            Err(_) => return,
+        };
+
+        // Position ticks arrive every second; only touch the list when players come or go.
+        self.updating_selector.set(true);
+        let shown: Vec<String> = (0..self.selector_model.n_items())
+            .filter_map(|i| self.selector_model.string(i).map(|s| s.to_string()))
+            .collect();
+        if shown != names {
+            let refs: Vec<&str> = names.iter().map(String::as_str).collect();
+            self.selector_model.splice(0, self.selector_model.n_items(), &refs);
+        }
+        if let Some(index) = index {
+            if self.selector.selected() != index as u32 {
+                self.selector.set_selected(index as u32);
+            }
+        }
+        self.updating_selector.set(false);
+        self.selector.set_visible(names.len() > 1);
+
+        let player = match self.current() {
+            Some(p) => p,
+            None => {
+                self.popover.popdown();
+                self.root.set_visible(false);
+                return;
+            }
+        };
+        self.root.set_visible(true);
+
+        let text = track_text(&player);
+        let changed = match self.text.try_borrow_mut() {
+            Ok(mut current) if *current != text => {
+                *current = text;
+                true
+            }
+            _ => false,
+        };
+        if changed {
+            self.scroll_offset.set(0);
+            self.render_label();
+        }
+        self.root.set_tooltip_text(Some(&player.identity));
+
+        let icon = if player.is_playing() {
+            "media-playback-pause-symbolic"
+        } else {
+            "media-playback-start-symbolic"
+        };
+        if let Some(image) = self.play_pause.child().and_downcast::<gtk::Image>() {
+            image.set_icon_name(Some(icon));
+        }
+        self.prev.set_sensitive(player.can_go_previous);
+        self.next.set_sensitive(player.can_go_next);
+
+        self.title.set_text(&player.title);
+        self.artist.set_text(&player.artists.join(", "));
+        self.artist.set_visible(!player.artists.is_empty());
+        self.album.set_text(&player.album);
+        self.album.set_visible(!player.album.is_empty());
+        self.update_art(&player.art_url);
+
+        let length = player.length as f64 / 1_000_000.0;
+        self.seek.set_range(0.0, length.max(1.0));
+        self.seek.set_value(player.position as f64 / 1_000_000.0);
+        self.seek.set_sensitive(player.can_seek && player.length > 0 && !player.track_id.is_empty());
+        self.time.set_text(&if player.length > 0 {
+            format!("{} / {}", format_time(player.position), format_time(player.length))
+        } else {
+            format_time(player.position)
+        });
+    }
+
+    /// Cover art from `mpris:artUrl` (`file://` or anything GIO can open).
+    fn update_art(&self, url: &str) {
+        match self.art_url.try_borrow_mut() {
+            Ok(mut current) if *current != url => *current = url.to_string(),
+            _ => return,
+        }
+        if url.is_empty() {
+            self.art.set_icon_name(Some("audio-x-generic-symbolic"));
+        } else {
+            let icon = gio::FileIcon::new(&gio::File::for_uri(url));
+            self.art.set_from_gicon(&icon);
+        }
+    }
+
+    /// Show the track text, truncated to `chars` or scrolled through when `scroll` is set.
+    fn render_label(&self) {
+        let text = match self.text.try_borrow() {
+            Ok(t) => t.clone(),
+            Err(_) => return,
+        };
+        let chars: Vec<char> = text.chars().collect();
+        let max = self.config.chars.max(1);
+        if chars.len() <= max {
+            self.label.set_text(&text);
+            return;
+        }
+        if !self.config.scroll {
+            let shown: String = chars.iter().take(max - 1).collect();
+            self.label.set_text(&format!("{}…", shown));
+            return;
+        }
+
+        let gap = ['\u{00a0}'; 3];
+        let cycle: Vec<char> = chars.iter().chain(gap.iter()).copied().collect();
+        let offset = self.scroll_offset.get() % cycle.len();
+        let shown: String = cycle.iter().cycle().skip(offset).take(max).collect();
+        self.label.set_text(&shown);
+    }
+}
@@ -0,0 +1,258 @@
+use gtk4 as gtk;
+use gtk::prelude::*;
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use std::time::Duration;
+
+use super::config::RandomWallpaperConfig;
+
+const IMAGE_EXTENSIONS: [&str; 5] = ["jpg", "jpeg", "png", "webp", "bmp"];
+
+/// `~/...` to an absolute path.
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Images directly in `dir`, sorted.
+fn list_images(dir: &Path) -> Vec<PathBuf> {
+    let mut images: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|d| {
+            d.flatten()
+                .map(|e| e.path())
+                .filter(|p| {
+                    p.extension()
+                        .and_then(|e| e.to_str())
+                        .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    images.sort();
+    images
+}
+
+/// There is one wallpaper however many panels show the module, so the image, the setter
+/// process and the rotation timer are shared by all of them.
+struct SharedWallpaper {
+    current: Option<PathBuf>,
+    /// The running setter, when `command` stays in the foreground (swaybg).
+    child: Option<Child>,
+    /// Rotation timer, started by the first module with a non-zero `interval`.
+    timer: Option<glib::SourceId>,
+    /// Live modules, all showing `current`.
+    views: Vec<RandomWallpaperUi>,
+}
+
+thread_local! {
+    static SHARED: RefCell<SharedWallpaper> = const {
+        RefCell::new(SharedWallpaper {
+            current: None,
+            child: None,
+            timer: None,
+            views: Vec::new(),
+        })
+    };
+}
+
+/// Upstream `random-wallpaper`, local directory only: picks an image, applies it with
+/// `command`, and offers next/save in a popover.
+#[derive(Clone)]
+pub struct RandomWallpaperUi {
+    root: gtk::MenuButton,
+    picture: gtk::Picture,
+    name: gtk::Label,
+    status: gtk::Label,
+    config: RandomWallpaperConfig,
+}
+
+impl RandomWallpaperUi {
+    pub fn new(config: RandomWallpaperConfig) -> Self {
+        let root = gtk::MenuButton::new();
+        root.set_widget_name("random-wallpaper");
+        root.add_css_class("flat");
+        let icon = gtk::Image::from_icon_name(&config.icon);
+        icon.set_pixel_size(config.icon_size);
+        root.set_child(Some(&icon));
+        root.set_tooltip_text(Some("Wallpaper"));
+
+        let content = gtk::Box::new(gtk::Orientation::Vertical, 6);
+        let picture = gtk::Picture::new();
+        picture.set_size_request(config.preview_width, config.preview_width * 9 / 16);
+        picture.set_content_fit(gtk::ContentFit::Cover);
+        content.append(&picture);
+        let name = gtk::Label::new(None);
+        name.set_ellipsize(gtk::pango::EllipsizeMode::Middle);
+        name.set_max_width_chars(40);
+        content.append(&name);
+        let status = gtk::Label::new(None);
+        status.add_css_class("dim-label");
+        status.set_visible(false);
+        content.append(&status);
+
+        let buttons = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+        buttons.set_halign(gtk::Align::Center);
+        let next = gtk::Button::with_label("Next");
+        let save = gtk::Button::with_label("Save");
+        save.set_visible(!config.save_path.is_empty());
+        buttons.append(&next);
+        buttons.append(&save);
+        content.append(&buttons);
+
+        let popover = gtk::Popover::new();
+        popover.set_child(Some(&content));
+        root.set_popover(Some(&popover));
+
+        let ui = Self {
+            root,
+            picture,
+            name,
+            status,
+            config,
+        };
+
+        let ui_for_next = ui.clone();
+        next.connect_clicked(move |_| ui_for_next.next());
+        let ui_for_save = ui.clone();
+        save.connect_clicked(move |_| ui_for_save.save());
+
+        let (current, first) = SHARED.with(|shared| {
+            let mut shared = shared.borrow_mut();
+            shared.views.push(ui.clone());
+            (shared.current.clone(), shared.views.len() == 1)
+        });
+        match current {
+            Some(path) => ui.show_current(&path),
+            None if first && ui.config.refresh_on_startup => ui.next(),
+            None => {}
+        }
+
+        if ui.config.interval > 0 && SHARED.with(|shared| shared.borrow().timer.is_none()) {
+            let ui_for_timer = ui.clone();
+            let id = glib::timeout_add_local(Duration::from_secs(ui.config.interval * 60), move || {
+                ui_for_timer.next();
+                glib::ControlFlow::Continue
+            });
+            SHARED.with(|shared| shared.borrow_mut().timer = Some(id));
+        }
+
+        ui.root.connect_destroy(|root| {
+            let (timer, child) = SHARED.with(|shared| {
+                let mut shared = shared.borrow_mut();
+                shared.views.retain(|view| view.root != *root);
+                if !shared.views.is_empty() {
+                    return (None, None);
+                }
+                // The last module is gone: stop rotating and don't leave the setter behind.
+                shared.current = None;
+                (shared.timer.take(), shared.child.take())
+            });
+            if let Some(id) = timer {
+                id.remove();
+            }
+            if let Some(mut child) = child {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+        });
+
+        ui
+    }
+
+    pub fn widget(&self) -> gtk::Widget {
+        self.root.clone().upcast()
+    }
+
+    fn show_status(&self, text: &str) {
+        self.status.set_text(text);
+        self.status.set_visible(!text.is_empty());
+    }
+
+    /// Apply a random image from `local-path`, never the current one twice in a row.
+    fn next(&self) {
+        let dir = expand_home(&self.config.local_path);
+        let current = SHARED.with(|shared| shared.borrow().current.clone());
+        let mut images = list_images(&dir);
+        if images.len() > 1 {
+            images.retain(|p| Some(p) != current.as_ref());
+        }
+        if images.is_empty() {
+            self.show_status(&format!("No images in {}", dir.display()));
+            return;
+        }
+        let index = glib::random_int_range(0, images.len() as i32) as usize;
+        let path = images.swap_remove(index);
+        self.apply(path);
+    }
+
+    fn apply(&self, path: PathBuf) {
+        let quoted = glib::shell_quote(&path);
+        let command = self.config.command.replace("{path}", &quoted.to_string_lossy());
+        match Command::new("sh").args(["-c", &command]).spawn() {
+            Ok(child) => {
+                let previous = SHARED.with(|shared| shared.borrow_mut().child.replace(child));
+                // Let a long-running setter paint before its predecessor goes away.
+                if let Some(mut previous) = previous {
+                    glib::timeout_add_local_once(Duration::from_secs(1), move || {
+                        if let Ok(None) = previous.try_wait() {
+                            let _ = previous.kill();
+                        }
+                        let _ = previous.wait();
+                    });
+                }
+            }
+            Err(err) => {
+                eprintln!("random-wallpaper: failed to run '{command}': {err}");
+                self.show_status("Failed to run the wallpaper command");
+                return;
+            }
+        }
+
+        self.show_status("");
+        let views = SHARED.with(|shared| {
+            let mut shared = shared.borrow_mut();
+            shared.current = Some(path.clone());
+            shared.views.clone()
+        });
+        for view in views {
+            view.show_current(&path);
+        }
+    }
+
+    fn show_current(&self, path: &Path) {
+        self.picture.set_filename(Some(path));
+        let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        self.name.set_text(&file_name);
+        self.root.set_tooltip_text(Some(&file_name));
+    }
+
+    /// Copy the current image into `save-path`.
+    fn save(&self) {
+        let current = match SHARED.with(|shared| shared.borrow().current.clone()) {
+            Some(p) => p,
+            None => return,
+        };
+        let dir = expand_home(&self.config.save_path);
+        let target = match current.file_name() {
+            Some(name) => dir.join(name),
+            None => return,
+        };
+        // Copying a file onto itself would truncate it.
+        if fs::canonicalize(&target).ok() == fs::canonicalize(&current).ok() && target.exists() {
+            self.show_status("Already saved");
+            return;
+        }
+        let result = fs::create_dir_all(&dir).and_then(|_| fs::copy(&current, &target));
+        match result {
+            Ok(_) => self.show_status(&format!("Saved to {}", target.display())),
+            Err(err) => {
+                eprintln!("random-wallpaper: failed to save {}: {err}", target.display());
+                self.show_status("Save failed");
+            }
+        }
+    }
+}
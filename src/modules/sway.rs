@@ -0,0 +1,607 @@
+use super::hyprland::{AppMsg, HyprClient, HyprWorkspace, HyprWorkspaceInfo};
+use anyhow::Context;
+use crossbeam_channel as cb;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+// i3-IPC message framing: "i3-ipc" + u32 payload length + u32 message type (native endian).
+const IPC_MAGIC: &[u8; 6] = b"i3-ipc";
+const IPC_HEADER_LEN: usize = 14;
+
+const MSG_RUN_COMMAND: u32 = 0;
+const MSG_GET_WORKSPACES: u32 = 1;
+const MSG_SUBSCRIBE: u32 = 2;
+const MSG_GET_TREE: u32 = 4;
+const MSG_GET_BINDING_STATE: u32 = 12;
+
+const EVENT_WORKSPACE: u32 = 0x8000_0000;
+const EVENT_OUTPUT: u32 = 0x8000_0001;
+const EVENT_MODE: u32 = 0x8000_0002;
+const EVENT_WINDOW: u32 = 0x8000_0003;
+
+const SCRATCHPAD_WORKSPACE: &str = "__i3_scratch";
+
+/// Workspaces without a number (sway reports `num: -1`) are keyed by this plus their con
+/// id, so they can't collide with numbered ones or with each other.
+const NAMED_WORKSPACE_BASE: i32 = 1_000_000;
+
+#[derive(Debug, Deserialize, Clone)]
+#[allow(dead_code)]
+pub struct SwayWorkspace {
+    /// Con id of the workspace node.
+    #[serde(default)]
+    pub id: i64,
+    #[serde(default)]
+    pub num: i32,
+    pub name: String,
+    #[serde(default)]
+    pub output: String,
+    #[serde(default)]
+    pub focused: bool,
+    #[serde(default)]
+    pub visible: bool,
+    #[serde(default)]
+    pub urgent: bool,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct SwayWindowProperties {
+    #[serde(default)]
+    pub class: Option<String>,
+    #[serde(default)]
+    pub instance: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[allow(dead_code)]
+pub struct SwayNode {
+    pub id: i64,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(rename = "type", default)]
+    pub node_type: String,
+    #[serde(default)]
+    pub num: Option<i32>,
+    #[serde(default)]
+    pub output: Option<String>,
+    #[serde(default)]
+    pub focused: bool,
+    #[serde(default)]
+    pub urgent: bool,
+    #[serde(default)]
+    pub fullscreen_mode: u8,
+    #[serde(default)]
+    pub app_id: Option<String>,
+    #[serde(default)]
+    pub pid: Option<i32>,
+    #[serde(default)]
+    pub shell: Option<String>,
+    #[serde(default)]
+    pub window_properties: Option<SwayWindowProperties>,
+    #[serde(default)]
+    pub rect: SwayRect,
+    #[serde(default)]
+    pub nodes: Vec<SwayNode>,
+    #[serde(default)]
+    pub floating_nodes: Vec<SwayNode>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+pub struct SwayRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl SwayNode {
+    /// A node is a window if it carries an app id (Wayland) or X11 window properties.
+    fn is_window(&self) -> bool {
+        (self.node_type == "con" || self.node_type == "floating_con")
+            && (self.app_id.is_some() || self.window_properties.is_some())
+    }
+
+    fn class(&self) -> String {
+        if let Some(app_id) = self.app_id.as_ref() {
+            if !app_id.is_empty() {
+                return app_id.clone();
+            }
+        }
+        self.window_properties
+            .as_ref()
+            .and_then(|p| p.class.clone().or_else(|| p.instance.clone()))
+            .unwrap_or_default()
+    }
+}
+
+/// Client side of the sway i3-IPC socket.
+pub struct SwayIpc {
+    stream: UnixStream,
+}
+
+impl SwayIpc {
+    pub fn connect() -> anyhow::Result<Self> {
+        let path = std::env::var("SWAYSOCK").context("SWAYSOCK is not set")?;
+        Self::connect_to(path)
+    }
+
+    pub fn connect_to<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let stream = UnixStream::connect(path)
+            .with_context(|| format!("Failed to connect to sway socket {}", path.display()))?;
+        Ok(Self { stream })
+    }
+
+    pub fn send(&mut self, msg_type: u32, payload: &str) -> anyhow::Result<()> {
+        let mut buf = Vec::with_capacity(IPC_HEADER_LEN + payload.len());
+        buf.extend_from_slice(IPC_MAGIC);
+        buf.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+        buf.extend_from_slice(&msg_type.to_ne_bytes());
+        buf.extend_from_slice(payload.as_bytes());
+        self.stream.write_all(&buf)?;
+        Ok(())
+    }
+
+    /// Read one message (reply or event) and return its type and payload.
+    pub fn read(&mut self) -> anyhow::Result<(u32, Vec<u8>)> {
+        let mut header = [0u8; IPC_HEADER_LEN];
+        self.stream.read_exact(&mut header)?;
+        if &header[..6] != IPC_MAGIC {
+            anyhow::bail!("Invalid i3-ipc magic from sway socket");
+        }
+        let len = u32::from_ne_bytes([header[6], header[7], header[8], header[9]]) as usize;
+        let msg_type = u32::from_ne_bytes([header[10], header[11], header[12], header[13]]);
+        let mut payload = vec![0u8; len];
+        self.stream.read_exact(&mut payload)?;
+        Ok((msg_type, payload))
+    }
+
+    pub fn request(&mut self, msg_type: u32, payload: &str) -> anyhow::Result<Vec<u8>> {
+        self.send(msg_type, payload)?;
+        let (_, reply) = self.read()?;
+        Ok(reply)
+    }
+
+    pub fn run_command(&mut self, command: &str) -> anyhow::Result<()> {
+        #[derive(Deserialize)]
+        struct CommandOutcome {
+            success: bool,
+            #[serde(default)]
+            error: Option<String>,
+        }
+
+        let reply = self.request(MSG_RUN_COMMAND, command)?;
+        let outcomes: Vec<CommandOutcome> = serde_json::from_slice(&reply)?;
+        for outcome in outcomes {
+            if !outcome.success {
+                anyhow::bail!(
+                    "sway command '{}' failed: {}",
+                    command,
+                    outcome.error.unwrap_or_default()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_workspaces(&mut self) -> anyhow::Result<Vec<SwayWorkspace>> {
+        let reply = self.request(MSG_GET_WORKSPACES, "")?;
+        Ok(serde_json::from_slice(&reply)?)
+    }
+
+    pub fn get_tree(&mut self) -> anyhow::Result<SwayNode> {
+        let reply = self.request(MSG_GET_TREE, "")?;
+        Ok(serde_json::from_slice(&reply)?)
+    }
+
+    /// Name of the current binding mode (`default` outside of any mode).
+    pub fn get_binding_state(&mut self) -> anyhow::Result<String> {
+        #[derive(Deserialize)]
+        struct BindingState {
+            name: String,
+        }
+
+        let reply = self.request(MSG_GET_BINDING_STATE, "")?;
+        Ok(serde_json::from_slice::<BindingState>(&reply)?.name)
+    }
+
+    pub fn subscribe(&mut self, events: &[&str]) -> anyhow::Result<()> {
+        #[derive(Deserialize)]
+        struct SubscribeReply {
+            success: bool,
+        }
+
+        let payload = serde_json::to_string(events)?;
+        let reply = self.request(MSG_SUBSCRIBE, &payload)?;
+        let reply: SubscribeReply = serde_json::from_slice(&reply)?;
+        if !reply.success {
+            anyhow::bail!("sway refused subscription to {:?}", events);
+        }
+        Ok(())
+    }
+}
+
+/// Flattened view of the sway tree, shaped like the Hyprland data the widgets consume.
+#[derive(Debug, Clone, Default)]
+pub struct SwaySnapshot {
+    pub workspaces: Vec<HyprWorkspace>,
+    pub active_workspace: i32,
+    pub clients: Vec<HyprClient>,
+    pub focused: Option<HyprClient>,
+    pub scratchpad: Vec<HyprClient>,
+    /// Con ids of windows with the urgent hint set.
+    pub urgent: Vec<String>,
+}
+
+/// Widget id of a sway workspace: its number, or `NAMED_WORKSPACE_BASE + con id` when it
+/// has none.
+pub fn workspace_id(num: i32, con_id: i64) -> i32 {
+    if num >= 0 {
+        num
+    } else {
+        NAMED_WORKSPACE_BASE.saturating_add(i32::try_from(con_id).unwrap_or(i32::MAX))
+    }
+}
+
+/// Names of the unnumbered workspaces in the last snapshot, by widget id.
+fn named_workspaces() -> &'static Mutex<HashMap<i32, String>> {
+    static NAMED: OnceLock<Mutex<HashMap<i32, String>>> = OnceLock::new();
+    NAMED.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// `number N` for numbered workspaces, `"name"` for named ones.
+fn workspace_target(id: i32) -> anyhow::Result<String> {
+    if id < NAMED_WORKSPACE_BASE {
+        return Ok(format!("number {}", id));
+    }
+    let named = named_workspaces().lock().map_err(|_| anyhow::anyhow!("sway workspace names poisoned"))?;
+    let name = named.get(&id).with_context(|| format!("Unknown sway workspace {}", id))?;
+    Ok(format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\"")))
+}
+
+pub fn snapshot_from_tree(tree: &SwayNode, workspaces: &[SwayWorkspace]) -> SwaySnapshot {
+    let mut snapshot = SwaySnapshot::default();
+    let mut per_workspace: HashMap<String, Vec<HyprClient>> = HashMap::new();
+
+    for output in &tree.nodes {
+        for ws in output.nodes.iter().filter(|n| n.node_type == "workspace") {
+            let ws_name = ws.name.clone().unwrap_or_default();
+            let ws_info = HyprWorkspaceInfo {
+                id: workspace_id(ws.num.unwrap_or(-1), ws.id),
+                name: ws_name.clone(),
+            };
+            let mut windows = Vec::new();
+            collect_windows(ws, &ws_info, &mut windows, &mut snapshot.focused, &mut snapshot.urgent);
+
+            if ws_name == SCRATCHPAD_WORKSPACE {
+                snapshot.scratchpad = windows;
+            } else {
+                per_workspace.insert(ws_name, windows);
+            }
+        }
+    }
+
+    for ws in workspaces {
+        let windows = per_workspace.remove(&ws.name).unwrap_or_default();
+        let last = windows.last();
+        let id = workspace_id(ws.num, ws.id);
+        snapshot.workspaces.push(HyprWorkspace {
+            id,
+            idx: ws.num.max(0),
+            name: ws.name.clone(),
+            monitor: ws.output.clone(),
+            windows: windows.len() as i32,
+            hasfullscreen: windows.iter().any(|c| c.fullscreen),
+            lastwindow: last.map(|c| c.address.clone()).unwrap_or_default(),
+            lastwindowtitle: last.map(|c| c.title.clone()).unwrap_or_default(),
+            ispersistent: false,
+        });
+        if ws.focused {
+            snapshot.active_workspace = id;
+        }
+        snapshot.clients.extend(windows);
+    }
+
+    snapshot
+}
+
+fn collect_windows(
+    node: &SwayNode,
+    ws: &HyprWorkspaceInfo,
+    out: &mut Vec<HyprClient>,
+    focused: &mut Option<HyprClient>,
+    urgent: &mut Vec<String>,
+) {
+    for child in node.nodes.iter().chain(node.floating_nodes.iter()) {
+        if child.is_window() {
+            let client = client_from_node(child, ws);
+            if child.focused {
+                *focused = Some(client.clone());
+            }
+            if child.urgent {
+                urgent.push(client.address.clone());
+            }
+            out.push(client);
+        }
+        collect_windows(child, ws, out, focused, urgent);
+    }
+}
+
+fn client_from_node(node: &SwayNode, ws: &HyprWorkspaceInfo) -> HyprClient {
+    let class = node.class();
+    HyprClient {
+        address: node.id.to_string(),
+        mapped: true,
+        hidden: false,
+        at: (node.rect.x, node.rect.y),
+        size: (node.rect.width, node.rect.height),
+        workspace: ws.clone(),
+        floating: node.node_type == "floating_con",
+        fullscreen: node.fullscreen_mode != 0,
+        fullscreen_mode: node.fullscreen_mode as i32,
+        maximized: false,
+        focus_history_id: 0,
+        pid: node.pid.unwrap_or(0),
+        xwayland: node.shell.as_deref() == Some("xwayland"),
+        title: node.name.clone().unwrap_or_default(),
+        initial_class: class.clone(),
+        class,
+    }
+}
+
+pub fn spawn_sway_listener(sender: cb::Sender<AppMsg>) {
+    thread::spawn(move || loop {
+        if let Err(err) = run_sway_listener(&sender) {
+            eprintln!("sway: IPC listener stopped: {err:#}");
+        }
+        // Sway restarts (or a missing socket) shouldn't kill the panel; retry.
+        thread::sleep(Duration::from_secs(2));
+    });
+}
+
+fn run_sway_listener(sender: &cb::Sender<AppMsg>) -> anyhow::Result<()> {
+    let mut events = SwayIpc::connect()?;
+    events.subscribe(&["workspace", "window", "mode", "output"])?;
+
+    let mut query = SwayIpc::connect()?;
+    send_sway_snapshot_with(&mut query, sender)?;
+    // `mode` events only come on changes; start from the current one.
+    let _ = sender.send(AppMsg::SwayMode(query.get_binding_state()?));
+
+    loop {
+        let (msg_type, payload) = events.read()?;
+        match msg_type {
+            EVENT_WORKSPACE | EVENT_WINDOW | EVENT_OUTPUT => {
+                send_sway_snapshot_with(&mut query, sender)?;
+            }
+            EVENT_MODE => {
+                #[derive(Deserialize)]
+                struct ModeEvent {
+                    change: String,
+                }
+                if let Ok(ev) = serde_json::from_slice::<ModeEvent>(&payload) {
+                    let _ = sender.send(AppMsg::SwayMode(ev.change));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn send_sway_snapshot(sender: &cb::Sender<AppMsg>) {
+    if let Ok(mut ipc) = SwayIpc::connect() {
+        let _ = send_sway_snapshot_with(&mut ipc, sender);
+    }
+}
+
+fn send_sway_snapshot_with(ipc: &mut SwayIpc, sender: &cb::Sender<AppMsg>) -> anyhow::Result<()> {
+    let workspaces = ipc.get_workspaces()?;
+    let tree = ipc.get_tree()?;
+    let snapshot = snapshot_from_tree(&tree, &workspaces);
+
+    if let Ok(mut named) = named_workspaces().lock() {
+        *named = snapshot
+            .workspaces
+            .iter()
+            .filter(|w| w.id >= NAMED_WORKSPACE_BASE)
+            .map(|w| (w.id, w.name.clone()))
+            .collect();
+    }
+
+    let _ = sender.send(AppMsg::UrgentWindows(snapshot.urgent.clone()));
+    if let Some(focused) = snapshot.focused.as_ref() {
+        let _ = sender.send(AppMsg::HyprActiveWindow(focused.title.clone()));
+        let _ = sender.send(AppMsg::HyprActiveWindowAddress(focused.address.clone()));
+    }
+    let _ = sender.send(AppMsg::HyprWorkspaces {
+        workspaces: snapshot.workspaces,
+        active_id: snapshot.active_workspace,
+    });
+    let _ = sender.send(AppMsg::HyprClients {
+        clients: snapshot.clients,
+    });
+    let _ = sender.send(AppMsg::SwayScratchpad {
+        clients: snapshot.scratchpad,
+    });
+    Ok(())
+}
+
+fn swaymsg(command: &str) -> anyhow::Result<()> {
+    SwayIpc::connect()?.run_command(command)
+}
+
+pub fn swaymsg_workspace(id: i32) -> anyhow::Result<()> {
+    swaymsg(&format!("workspace {}", workspace_target(id)?))
+}
+
+pub fn swaymsg_move_focused_to_workspace(id: i32, silent: bool) -> anyhow::Result<()> {
+    let target = workspace_target(id)?;
+    if silent {
+        swaymsg(&format!("move container to workspace {}", target))
+    } else {
+        swaymsg(&format!("move container to workspace {}; workspace {}", target, target))
+    }
+}
+
+pub fn swaymsg_focus_con(con_id: &str) -> anyhow::Result<()> {
+    swaymsg(&format!("[con_id={}] focus", con_id))
+}
+
+pub fn swaymsg_move_con_to_workspace(con_id: &str, id: i32) -> anyhow::Result<()> {
+    swaymsg(&format!("[con_id={}] move container to workspace {}", con_id, workspace_target(id)?))
+}
+
+pub fn swaymsg_close_con(con_id: &str) -> anyhow::Result<()> {
+    swaymsg(&format!("[con_id={}] kill", con_id))
+}
+
+pub fn swaymsg_scratchpad_show_con(con_id: &str) -> anyhow::Result<()> {
+    swaymsg(&format!("[con_id={}] scratchpad show", con_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn socket_path() -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "nwg-panel-sway-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("sway-ipc.sock")
+    }
+
+    fn frame(msg_type: u32, payload: &str) -> Vec<u8> {
+        let mut buf = IPC_MAGIC.to_vec();
+        buf.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+        buf.extend_from_slice(&msg_type.to_ne_bytes());
+        buf.extend_from_slice(payload.as_bytes());
+        buf
+    }
+
+    /// Fake sway: answers each request in turn with the next reply (same message type),
+    /// then sends `events`. Returns the requests it saw.
+    fn fake_sway(replies: Vec<&'static str>, events: Vec<(u32, &'static str)>) -> (PathBuf, thread::JoinHandle<Vec<(u32, String)>>) {
+        let path = socket_path();
+        let listener = UnixListener::bind(&path).unwrap();
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut seen = Vec::new();
+            for reply in replies {
+                let mut header = [0u8; IPC_HEADER_LEN];
+                stream.read_exact(&mut header).unwrap();
+                assert_eq!(&header[..6], IPC_MAGIC);
+                let len = u32::from_ne_bytes(header[6..10].try_into().unwrap()) as usize;
+                let msg_type = u32::from_ne_bytes(header[10..14].try_into().unwrap());
+                let mut payload = vec![0u8; len];
+                stream.read_exact(&mut payload).unwrap();
+                seen.push((msg_type, String::from_utf8(payload).unwrap()));
+                stream.write_all(&frame(msg_type, reply)).unwrap();
+            }
+            for (event_type, payload) in events {
+                stream.write_all(&frame(event_type, payload)).unwrap();
+            }
+            seen
+        });
+        (path, handle)
+    }
+
+    #[test]
+    fn requests_are_framed_and_replies_parsed() {
+        let (path, server) = fake_sway(
+            vec![
+                r#"[{"id": 4, "num": 1, "name": "1", "output": "DP-1", "focused": true},
+                    {"id": 9, "num": -1, "name": "mail", "output": "DP-1"}]"#,
+                r#"{"name": "resize"}"#,
+            ],
+            Vec::new(),
+        );
+        let mut ipc = SwayIpc::connect_to(&path).unwrap();
+
+        let workspaces = ipc.get_workspaces().unwrap();
+        assert_eq!(workspaces.len(), 2);
+        assert_eq!((workspaces[1].id, workspaces[1].num, workspaces[1].name.as_str()), (9, -1, "mail"));
+        assert_eq!(ipc.get_binding_state().unwrap(), "resize");
+
+        let seen = server.join().unwrap();
+        assert_eq!(seen, vec![(MSG_GET_WORKSPACES, String::new()), (MSG_GET_BINDING_STATE, String::new())]);
+    }
+
+    #[test]
+    fn failed_command_is_an_error() {
+        let (path, server) = fake_sway(vec![r#"[{"success": false, "error": "No such workspace"}]"#], Vec::new());
+        let err = SwayIpc::connect_to(&path).unwrap().run_command("workspace number 99").unwrap_err();
+        assert!(err.to_string().contains("No such workspace"));
+        assert_eq!(server.join().unwrap(), vec![(MSG_RUN_COMMAND, "workspace number 99".to_string())]);
+    }
+
+    #[test]
+    fn subscription_then_events() {
+        let (path, server) = fake_sway(
+            vec![r#"{"success": true}"#],
+            vec![(EVENT_MODE, r#"{"change": "resize", "pango_markup": false}"#)],
+        );
+        let mut ipc = SwayIpc::connect_to(&path).unwrap();
+        ipc.subscribe(&["mode"]).unwrap();
+        let (msg_type, payload) = ipc.read().unwrap();
+        assert_eq!(msg_type, EVENT_MODE);
+        assert!(String::from_utf8(payload).unwrap().contains("resize"));
+        assert_eq!(server.join().unwrap(), vec![(MSG_SUBSCRIBE, r#"["mode"]"#.to_string())]);
+    }
+
+    #[test]
+    fn bad_magic_is_rejected() {
+        let path = socket_path();
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(b"i4-ipc\0\0\0\0\0\0\0\0").unwrap();
+        });
+        assert!(SwayIpc::connect_to(&path).unwrap().read().is_err());
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn named_workspaces_get_distinct_ids() {
+        let tree: SwayNode = serde_json::from_str(
+            r#"{"id": 1, "type": "root", "nodes": [{"id": 2, "type": "output", "name": "DP-1", "nodes": [
+                {"id": 10, "type": "workspace", "name": "mail", "num": -1, "nodes": [
+                    {"id": 20, "type": "con", "name": "Inbox", "app_id": "thunderbird"}]},
+                {"id": 11, "type": "workspace", "name": "chat", "num": -1, "nodes": []},
+                {"id": 12, "type": "workspace", "name": "3", "num": 3, "nodes": []}]}]}"#,
+        )
+        .unwrap();
+        let workspaces: Vec<SwayWorkspace> = serde_json::from_str(
+            r#"[{"id": 10, "num": -1, "name": "mail", "output": "DP-1", "focused": true},
+                {"id": 11, "num": -1, "name": "chat", "output": "DP-1"},
+                {"id": 12, "num": 3, "name": "3", "output": "DP-1"}]"#,
+        )
+        .unwrap();
+
+        let snapshot = snapshot_from_tree(&tree, &workspaces);
+        let ids: Vec<i32> = snapshot.workspaces.iter().map(|w| w.id).collect();
+        assert_eq!(ids, vec![NAMED_WORKSPACE_BASE + 10, NAMED_WORKSPACE_BASE + 11, 3]);
+        assert_eq!(snapshot.active_workspace, NAMED_WORKSPACE_BASE + 10);
+        assert_eq!(snapshot.workspaces[0].idx, 0);
+        assert_eq!(snapshot.clients[0].workspace.id, NAMED_WORKSPACE_BASE + 10);
+
+        named_workspaces()
+            .lock()
+            .unwrap()
+            .insert(NAMED_WORKSPACE_BASE + 11, r#"say "hi""#.to_string());
+        assert_eq!(workspace_target(3).unwrap(), "number 3");
+        assert_eq!(workspace_target(NAMED_WORKSPACE_BASE + 11).unwrap(), r#""say \"hi\"""#);
+    }
+}
@@ -0,0 +1,273 @@
+use super::hyprland::AppMsg;
+use crossbeam_channel as cb;
+use gio::prelude::*;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+/// How often the shared sampler reads `/proc` and `/sys`.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// One reading of everything the resource modules show.
+#[derive(Debug, Clone, Default)]
+pub struct SystemSample {
+    /// Busy percentage over the last interval, all CPUs.
+    pub cpu_total: f64,
+    pub cpu_cores: Vec<f64>,
+    pub memory: MemoryUsage,
+    pub disks: Vec<DiskUsage>,
+    pub temperatures: Vec<Temperature>,
+}
+
+/// In bytes, from `/proc/meminfo`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemoryUsage {
+    pub total: u64,
+    pub available: u64,
+    pub swap_total: u64,
+    pub swap_free: u64,
+}
+
+impl MemoryUsage {
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.available)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiskUsage {
+    pub mount: String,
+    pub total: u64,
+    pub free: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Temperature {
+    /// `hwmon-name/label` (e.g. `coretemp/Package id 0`) or `thermal/zone-type`.
+    pub sensor: String,
+    pub celsius: f64,
+    /// The sensor's own critical point, when it reports one.
+    pub critical: Option<f64>,
+}
+
+/// Cumulative jiffies of one `cpu` line in `/proc/stat`.
+#[derive(Debug, Clone, Copy, Default)]
+struct CpuTimes {
+    idle: u64,
+    total: u64,
+}
+
+/// Total (`cpu`) and per-core (`cpuN`) times, in file order.
+fn read_cpu_times() -> Vec<CpuTimes> {
+    let text = fs::read_to_string("/proc/stat").unwrap_or_default();
+    text.lines()
+        .take_while(|l| l.starts_with("cpu"))
+        .map(|line| {
+            let values: Vec<u64> = line.split_whitespace().skip(1).filter_map(|v| v.parse().ok()).collect();
+            // user nice system idle iowait irq softirq steal (guest time is already in user)
+            let idle = values.get(3).copied().unwrap_or(0) + values.get(4).copied().unwrap_or(0);
+            let total = values.iter().take(8).sum();
+            CpuTimes { idle, total }
+        })
+        .collect()
+}
+
+fn busy_percent(previous: CpuTimes, current: CpuTimes) -> f64 {
+    let total = current.total.saturating_sub(previous.total);
+    if total == 0 {
+        return 0.0;
+    }
+    let idle = current.idle.saturating_sub(previous.idle);
+    100.0 * (total.saturating_sub(idle)) as f64 / total as f64
+}
+
+fn read_memory() -> MemoryUsage {
+    let text = fs::read_to_string("/proc/meminfo").unwrap_or_default();
+    let field = |name: &str| -> u64 {
+        text.lines()
+            .find_map(|l| l.strip_prefix(name)?.strip_prefix(':'))
+            .and_then(|v| v.split_whitespace().next()?.parse::<u64>().ok())
+            .map(|kb| kb * 1024)
+            .unwrap_or(0)
+    };
+    MemoryUsage {
+        total: field("MemTotal"),
+        available: field("MemAvailable"),
+        swap_total: field("SwapTotal"),
+        swap_free: field("SwapFree"),
+    }
+}
+
+/// Size and free space of the filesystem at `mount` (statvfs, via GIO).
+fn read_disk(mount: &str) -> Option<DiskUsage> {
+    let info = gio::File::for_path(mount)
+        .query_filesystem_info("filesystem::size,filesystem::free", None::<&gio::Cancellable>)
+        .ok()?;
+    Some(DiskUsage {
+        mount: mount.to_string(),
+        total: info.attribute_uint64("filesystem::size"),
+        free: info.attribute_uint64("filesystem::free"),
+    })
+}
+
+fn read_millidegrees(path: &Path) -> Option<f64> {
+    let value: f64 = fs::read_to_string(path).ok()?.trim().parse().ok()?;
+    Some(value / 1000.0)
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    Some(fs::read_to_string(path).ok()?.trim().to_string())
+}
+
+/// Every `temp*_input` under `/sys/class/hwmon`, then every thermal zone.
+fn read_temperatures() -> Vec<Temperature> {
+    let mut temperatures = Vec::new();
+
+    let mut hwmons: Vec<_> = fs::read_dir("/sys/class/hwmon").map(|d| d.flatten().map(|e| e.path()).collect()).unwrap_or_default();
+    hwmons.sort();
+    for dir in hwmons {
+        let name = read_trimmed(&dir.join("name")).unwrap_or_else(|| "hwmon".to_string());
+        let mut inputs: Vec<String> = fs::read_dir(&dir)
+            .map(|d| {
+                d.flatten()
+                    .filter_map(|e| e.file_name().to_str().map(str::to_string))
+                    .filter(|f| f.starts_with("temp") && f.ends_with("_input"))
+                    .collect()
+            })
+            .unwrap_or_default();
+        inputs.sort();
+        for input in inputs {
+            let base = input.trim_end_matches("_input");
+            let celsius = match read_millidegrees(&dir.join(&input)) {
+                Some(c) => c,
+                None => continue,
+            };
+            let label = read_trimmed(&dir.join(format!("{}_label", base))).unwrap_or_else(|| base.to_string());
+            temperatures.push(Temperature {
+                sensor: format!("{}/{}", name, label),
+                celsius,
+                critical: read_millidegrees(&dir.join(format!("{}_crit", base))),
+            });
+        }
+    }
+
+    let mut zones: Vec<_> = fs::read_dir("/sys/class/thermal")
+        .map(|d| {
+            d.flatten()
+                .map(|e| e.path())
+                .filter(|p| p.file_name().and_then(|f| f.to_str()).is_some_and(|f| f.starts_with("thermal_zone")))
+                .collect()
+        })
+        .unwrap_or_default();
+    zones.sort();
+    for dir in zones {
+        let celsius = match read_millidegrees(&dir.join("temp")) {
+            Some(c) => c,
+            None => continue,
+        };
+        let kind = read_trimmed(&dir.join("type")).unwrap_or_else(|| "zone".to_string());
+        let critical = (0..16).find_map(|i| {
+            match read_trimmed(&dir.join(format!("trip_point_{}_type", i)))?.as_str() {
+                "critical" => read_millidegrees(&dir.join(format!("trip_point_{}_temp", i))),
+                _ => None,
+            }
+        });
+        temperatures.push(Temperature {
+            sensor: format!("thermal/{}", kind),
+            celsius,
+            critical,
+        });
+    }
+
+    temperatures
+}
+
+/// The sensor matching `wanted` (case-insensitive substring of `Temperature::sensor`), or a
+/// CPU package sensor when `wanted` is empty.
+pub fn pick_temperature<'a>(temperatures: &'a [Temperature], wanted: &str) -> Option<&'a Temperature> {
+    let find = |needle: &str| {
+        let needle = needle.to_lowercase();
+        temperatures.iter().find(|t| t.sensor.to_lowercase().contains(&needle))
+    };
+    if !wanted.is_empty() {
+        return find(wanted);
+    }
+    ["coretemp/package", "k10temp/tctl", "zenpower/tdie", "cpu", "x86_pkg_temp"]
+        .iter()
+        .find_map(|n| find(n))
+        .or_else(|| temperatures.first())
+}
+
+/// What the live resource modules need: how many exist, and the mounts they show.
+#[derive(Debug, Default)]
+struct Interest {
+    modules: usize,
+    mounts: BTreeMap<String, usize>,
+}
+
+fn interest() -> &'static Mutex<Interest> {
+    static INTEREST: OnceLock<Mutex<Interest>> = OnceLock::new();
+    INTEREST.get_or_init(|| Mutex::new(Interest::default()))
+}
+
+/// Register a module (and the mounts it shows) with the sampler; undo with `release`.
+pub fn register(mounts: &[String]) {
+    if let Ok(mut interest) = interest().lock() {
+        interest.modules += 1;
+        for mount in mounts {
+            *interest.mounts.entry(mount.clone()).or_insert(0) += 1;
+        }
+    }
+}
+
+pub fn release(mounts: &[String]) {
+    if let Ok(mut interest) = interest().lock() {
+        interest.modules = interest.modules.saturating_sub(1);
+        for mount in mounts {
+            if let Some(count) = interest.mounts.get_mut(mount) {
+                *count -= 1;
+                if *count == 0 {
+                    interest.mounts.remove(mount);
+                }
+            }
+        }
+    }
+}
+
+/// Start the sampling thread shared by the `cpu`, `memory`, `disk` and `temperature`
+/// modules. It sends `AppMsg::SystemStats` every couple of seconds while any is shown.
+pub fn spawn_system_sampler(sender: cb::Sender<AppMsg>) {
+    thread::spawn(move || {
+        let mut previous = read_cpu_times();
+        loop {
+            thread::sleep(SAMPLE_INTERVAL);
+
+            let mounts: Vec<String> = match interest().lock() {
+                Ok(i) if i.modules > 0 => i.mounts.keys().cloned().collect(),
+                _ => {
+                    // Keep the CPU baseline fresh so the first sample after idling is right.
+                    previous = read_cpu_times();
+                    continue;
+                }
+            };
+
+            let current = read_cpu_times();
+            let mut usage = previous.iter().zip(current.iter()).map(|(p, c)| busy_percent(*p, *c));
+            let sample = SystemSample {
+                cpu_total: usage.next().unwrap_or(0.0),
+                cpu_cores: usage.collect(),
+                memory: read_memory(),
+                disks: mounts.iter().filter_map(|m| read_disk(m)).collect(),
+                temperatures: read_temperatures(),
+            };
+            previous = current;
+
+            if sender.send(AppMsg::SystemStats(sample)).is_err() {
+                return;
+            }
+        }
+    });
+}
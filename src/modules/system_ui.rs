@@ -0,0 +1,366 @@
+use gtk4 as gtk;
+use gtk::prelude::*;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use super::config::{CpuConfig, DiskConfig, MemoryConfig, PanelConfig, TemperatureConfig};
+use super::system_stats::{pick_temperature, register, release, SystemSample};
+
+/// `1536 MiB` -> `1.5G`, binary units like `free -h`.
+pub fn human_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 6] = ["B", "K", "M", "G", "T", "P"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 || value >= 100.0 {
+        format!("{:.0}{}", value, UNITS[unit])
+    } else {
+        format!("{:.1}{}", value, UNITS[unit])
+    }
+}
+
+fn percent(part: u64, whole: u64) -> f64 {
+    if whole == 0 { 0.0 } else { 100.0 * part as f64 / whole as f64 }
+}
+
+/// `{name}` placeholders in a module `format`.
+pub fn fill(format: &str, values: &[(&str, String)]) -> String {
+    values
+        .iter()
+        .fold(format.to_string(), |text, (name, value)| text.replace(&format!("{{{}}}", name), value))
+}
+
+/// Module box with an optional icon and the value label; registers with the sampler
+/// until destroyed.
+fn resource_box(name: &str, icon: &str, icon_size: i32, mounts: Vec<String>) -> (gtk::Box, gtk::Label) {
+    let root = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+    root.set_widget_name(name);
+    if !icon.is_empty() {
+        let image = gtk::Image::from_icon_name(icon);
+        image.set_pixel_size(icon_size);
+        root.append(&image);
+    }
+    let label = gtk::Label::new(None);
+    root.append(&label);
+
+    register(&mounts);
+    root.connect_destroy(move |_| release(&mounts));
+    (root, label)
+}
+
+#[derive(Clone)]
+pub struct CpuUi {
+    root: gtk::Box,
+    label: gtk::Label,
+    cores: gtk::Box,
+    core_bars: Rc<RefCell<Vec<gtk::LevelBar>>>,
+    sparkline: Option<gtk::DrawingArea>,
+    history: Rc<RefCell<VecDeque<f64>>>,
+    config: CpuConfig,
+}
+
+impl CpuUi {
+    pub fn new(config: CpuConfig) -> Self {
+        let (root, label) = resource_box("cpu", &config.icon, config.icon_size, Vec::new());
+
+        let cores = gtk::Box::new(gtk::Orientation::Horizontal, 1);
+        cores.set_widget_name("cpu-cores");
+        cores.set_visible(config.per_core);
+        root.append(&cores);
+
+        let history = Rc::new(RefCell::new(VecDeque::with_capacity(config.sparkline_length)));
+        let sparkline = if config.sparkline {
+            let area = gtk::DrawingArea::new();
+            area.set_widget_name("cpu-sparkline");
+            area.set_content_width(config.sparkline_width);
+            area.set_content_height(config.icon_size);
+            let history = history.clone();
+            let length = config.sparkline_length.max(2);
+            area.set_draw_func(move |area, cr, width, height| {
+                let history = match history.try_borrow() {
+                    Ok(h) => h,
+                    Err(_) => return,
+                };
+                if history.len() < 2 {
+                    return;
+                }
+                #[allow(deprecated)]
+                let color = area.style_context().color();
+                cr.set_source_rgba(
+                    color.red() as f64,
+                    color.green() as f64,
+                    color.blue() as f64,
+                    color.alpha() as f64,
+                );
+                let step = width as f64 / (length - 1) as f64;
+                let x0 = width as f64 - step * (history.len() - 1) as f64;
+                for (i, value) in history.iter().enumerate() {
+                    let x = x0 + step * i as f64;
+                    let y = height as f64 * (1.0 - value / 100.0);
+                    if i == 0 {
+                        cr.move_to(x, y);
+                    } else {
+                        cr.line_to(x, y);
+                    }
+                }
+                cr.set_line_width(1.0);
+                let _ = cr.stroke();
+            });
+            root.append(&area);
+            Some(area)
+        } else {
+            None
+        };
+
+        Self {
+            root,
+            label,
+            cores,
+            core_bars: Rc::new(RefCell::new(Vec::new())),
+            sparkline,
+            history,
+            config,
+        }
+    }
+
+    pub fn widget(&self) -> gtk::Widget {
+        self.root.clone().upcast()
+    }
+
+    pub fn set_sample(&self, sample: &SystemSample) {
+        self.label.set_text(&fill(&self.config.format, &[("usage", format!("{:.0}", sample.cpu_total))]));
+
+        let per_core: Vec<String> = sample
+            .cpu_cores
+            .iter()
+            .enumerate()
+            .map(|(i, usage)| format!("cpu{}: {:.0}%", i, usage))
+            .collect();
+        self.root.set_tooltip_text(Some(&format!("CPU: {:.0}%\n{}", sample.cpu_total, per_core.join("\n"))));
+
+        if self.config.per_core {
+            if let Ok(mut bars) = self.core_bars.try_borrow_mut() {
+                // CPUs can go on/offline
+                if bars.len() != sample.cpu_cores.len() {
+                    for bar in bars.drain(..) {
+                        self.cores.remove(&bar);
+                    }
+                    for _ in &sample.cpu_cores {
+                        let bar = gtk::LevelBar::for_interval(0.0, 100.0);
+                        bar.set_orientation(gtk::Orientation::Vertical);
+                        bar.set_inverted(true);
+                        self.cores.append(&bar);
+                        bars.push(bar);
+                    }
+                }
+                for (bar, usage) in bars.iter().zip(sample.cpu_cores.iter()) {
+                    bar.set_value(*usage);
+                }
+            }
+        }
+
+        if let Some(area) = self.sparkline.as_ref() {
+            if let Ok(mut history) = self.history.try_borrow_mut() {
+                history.push_back(sample.cpu_total);
+                while history.len() > self.config.sparkline_length.max(2) {
+                    history.pop_front();
+                }
+            }
+            area.queue_draw();
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MemoryUi {
+    root: gtk::Box,
+    label: gtk::Label,
+    config: MemoryConfig,
+}
+
+impl MemoryUi {
+    pub fn new(config: MemoryConfig) -> Self {
+        let (root, label) = resource_box("memory", &config.icon, config.icon_size, Vec::new());
+        Self { root, label, config }
+    }
+
+    pub fn widget(&self) -> gtk::Widget {
+        self.root.clone().upcast()
+    }
+
+    pub fn set_sample(&self, sample: &SystemSample) {
+        let memory = &sample.memory;
+        let swap_used = memory.swap_total.saturating_sub(memory.swap_free);
+        self.label.set_text(&fill(
+            &self.config.format,
+            &[
+                ("used", human_bytes(memory.used())),
+                ("total", human_bytes(memory.total)),
+                ("available", human_bytes(memory.available)),
+                ("percent", format!("{:.0}", percent(memory.used(), memory.total))),
+                ("swap_used", human_bytes(swap_used)),
+            ],
+        ));
+        self.root.set_tooltip_text(Some(&format!(
+            "Memory: {} / {}\nSwap: {} / {}",
+            human_bytes(memory.used()),
+            human_bytes(memory.total),
+            human_bytes(swap_used),
+            human_bytes(memory.swap_total),
+        )));
+    }
+}
+
+#[derive(Clone)]
+pub struct DiskUi {
+    root: gtk::Box,
+    label: gtk::Label,
+    config: DiskConfig,
+}
+
+impl DiskUi {
+    pub fn new(config: DiskConfig) -> Self {
+        let (root, label) = resource_box("disk", &config.icon, config.icon_size, config.mounts.clone());
+        Self { root, label, config }
+    }
+
+    pub fn widget(&self) -> gtk::Widget {
+        self.root.clone().upcast()
+    }
+
+    pub fn set_sample(&self, sample: &SystemSample) {
+        let mut texts = Vec::new();
+        let mut tooltip = Vec::new();
+        for mount in &self.config.mounts {
+            let disk = match sample.disks.iter().find(|d| d.mount == *mount) {
+                Some(d) => d,
+                None => continue,
+            };
+            let used = disk.total.saturating_sub(disk.free);
+            texts.push(fill(
+                &self.config.format,
+                &[
+                    ("mount", disk.mount.clone()),
+                    ("used", human_bytes(used)),
+                    ("free", human_bytes(disk.free)),
+                    ("total", human_bytes(disk.total)),
+                    ("percent", format!("{:.0}", percent(used, disk.total))),
+                ],
+            ));
+            tooltip.push(format!(
+                "{}: {} free of {}",
+                disk.mount,
+                human_bytes(disk.free),
+                human_bytes(disk.total)
+            ));
+        }
+        self.label.set_text(&texts.join(" "));
+        self.root.set_tooltip_text(Some(&tooltip.join("\n")));
+    }
+}
+
+#[derive(Clone)]
+pub struct TemperatureUi {
+    root: gtk::Box,
+    label: gtk::Label,
+    config: TemperatureConfig,
+}
+
+impl TemperatureUi {
+    pub fn new(config: TemperatureConfig) -> Self {
+        let (root, label) = resource_box("temperature", &config.icon, config.icon_size, Vec::new());
+        Self { root, label, config }
+    }
+
+    pub fn widget(&self) -> gtk::Widget {
+        self.root.clone().upcast()
+    }
+
+    pub fn set_sample(&self, sample: &SystemSample) {
+        let temperature = match pick_temperature(&sample.temperatures, &self.config.sensor) {
+            Some(t) => t,
+            None => {
+                self.root.set_visible(false);
+                return;
+            }
+        };
+        self.root.set_visible(true);
+        self.label.set_text(&fill(
+            &self.config.format,
+            &[
+                ("temp", format!("{:.0}", temperature.celsius)),
+                ("sensor", temperature.sensor.clone()),
+            ],
+        ));
+        self.root.set_tooltip_text(Some(&temperature.sensor));
+
+        // An explicit threshold wins over the sensor's own critical point.
+        let critical = if self.config.critical > 0.0 {
+            Some(self.config.critical)
+        } else {
+            temperature.critical
+        };
+        if critical.is_some_and(|c| temperature.celsius >= c) {
+            self.root.add_css_class("critical");
+        } else {
+            self.root.remove_css_class("critical");
+        }
+    }
+}
+
+/// The resource modules a panel lists, fed from `AppMsg::SystemStats`.
+#[derive(Clone, Default)]
+pub struct ResourceUis {
+    cpu: Option<CpuUi>,
+    memory: Option<MemoryUi>,
+    disk: Option<DiskUi>,
+    temperature: Option<TemperatureUi>,
+}
+
+impl ResourceUis {
+    pub fn new(panel: &PanelConfig) -> Self {
+        let listed = |name: &str| {
+            panel
+                .modules_left
+                .iter()
+                .chain(panel.modules_center.iter())
+                .chain(panel.modules_right.iter())
+                .any(|m| m == name)
+        };
+        Self {
+            cpu: listed("cpu").then(|| CpuUi::new(panel.cpu.clone())),
+            memory: listed("memory").then(|| MemoryUi::new(panel.memory.clone())),
+            disk: listed("disk").then(|| DiskUi::new(panel.disk.clone())),
+            temperature: listed("temperature").then(|| TemperatureUi::new(panel.temperature.clone())),
+        }
+    }
+
+    pub fn widget(&self, name: &str) -> Option<gtk::Widget> {
+        match name {
+            "cpu" => self.cpu.as_ref().map(CpuUi::widget),
+            "memory" => self.memory.as_ref().map(MemoryUi::widget),
+            "disk" => self.disk.as_ref().map(DiskUi::widget),
+            "temperature" => self.temperature.as_ref().map(TemperatureUi::widget),
+            _ => None,
+        }
+    }
+
+    pub fn set_sample(&self, sample: &SystemSample) {
+        if let Some(cpu) = self.cpu.as_ref() {
+            cpu.set_sample(sample);
+        }
+        if let Some(memory) = self.memory.as_ref() {
+            memory.set_sample(sample);
+        }
+        if let Some(disk) = self.disk.as_ref() {
+            disk.set_sample(sample);
+        }
+        if let Some(temperature) = self.temperature.as_ref() {
+            temperature.set_sample(sample);
+        }
+    }
+}
@@ -0,0 +1,471 @@
+use super::hyprland::HyprClient;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How task buttons are ordered (`sort-by` in the taskbar config).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TaskOrder {
+    /// A header per workspace (ascending id), followed by its windows in open order.
+    #[default]
+    Workspace,
+    OpenTime,
+    /// Alphabetically by app, then open order.
+    App,
+    /// By the app order the user set by dragging, then open order.
+    Manual,
+}
+
+impl TaskOrder {
+    pub fn from_config(value: &str) -> Self {
+        match value {
+            "open-time" => TaskOrder::OpenTime,
+            "app" => TaskOrder::App,
+            "manual" => TaskOrder::Manual,
+            _ => TaskOrder::Workspace,
+        }
+    }
+}
+
+/// One position in the laid-out taskbar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskbarSlot {
+    Workspace(i32),
+    Task(String),
+    /// Several windows of one app, by group id (see `TaskOrderState::layout_grouped`).
+    Group(String),
+}
+
+/// What changed between two client lists, by window address. Any change means the
+/// taskbar has to be laid out again.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ClientDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// Windows now on another workspace.
+    pub moved: Vec<String>,
+    /// Windows whose title, class or other shown properties changed.
+    pub changed: Vec<String>,
+}
+
+impl ClientDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.moved.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Whether anything a task button shows differs between two snapshots of a window.
+fn content_changed(old: &HyprClient, new: &HyprClient) -> bool {
+    old.title != new.title
+        || old.class != new.class
+        || old.initial_class != new.initial_class
+        || old.xwayland != new.xwayland
+        || old.pid != new.pid
+}
+
+/// Compare the previously shown clients with the new list. Output follows the order of
+/// `new` (additions, moves, changes) and of `old` (removals). A window can be both moved
+/// and changed.
+pub fn diff_clients(old: &[&HyprClient], new: &[&HyprClient]) -> ClientDiff {
+    let previous: HashMap<&str, &HyprClient> = old.iter().map(|c| (c.address.as_str(), *c)).collect();
+    let mut diff = ClientDiff::default();
+
+    for client in new {
+        match previous.get(client.address.as_str()) {
+            None => diff.added.push(client.address.clone()),
+            Some(prev) => {
+                if prev.workspace.id != client.workspace.id {
+                    diff.moved.push(client.address.clone());
+                }
+                if content_changed(prev, client) {
+                    diff.changed.push(client.address.clone());
+                }
+            }
+        }
+    }
+
+    for client in old {
+        if !new.iter().any(|c| c.address == client.address) {
+            diff.removed.push(client.address.clone());
+        }
+    }
+
+    diff
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SavedOrder {
+    /// The single order saved before it was kept per output; used by outputs without their own.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    apps: Vec<String>,
+    /// Manual app order by panel output name.
+    #[serde(default)]
+    outputs: HashMap<String, Vec<String>>,
+}
+
+impl SavedOrder {
+    fn read(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Ordering state for one taskbar: when each window was first seen, and the manual app
+/// order (persisted per output, since window addresses don't survive a restart).
+#[derive(Debug, Default)]
+pub struct TaskOrderState {
+    opened: HashMap<String, u64>,
+    next_seq: u64,
+    manual: Vec<String>,
+    /// The panel output the manual order belongs to.
+    output: String,
+    path: Option<PathBuf>,
+}
+
+/// App key used for the manual order.
+fn app_key(client: &HyprClient) -> String {
+    let class = if client.class.is_empty() { &client.initial_class } else { &client.class };
+    class.to_lowercase()
+}
+
+fn state_path() -> Option<PathBuf> {
+    let dir = dirs::state_dir().or_else(dirs::cache_dir)?;
+    Some(dir.join("nwg-panel-rs").join("taskbar-order.json"))
+}
+
+impl TaskOrderState {
+    /// Load the saved manual order of the panel on `output`; a missing or broken state file
+    /// starts empty.
+    pub fn load(output: &str) -> Self {
+        Self::load_from(state_path(), output)
+    }
+
+    fn load_from(path: Option<PathBuf>, output: &str) -> Self {
+        let mut saved = path.as_deref().map(SavedOrder::read).unwrap_or_default();
+        Self {
+            manual: saved.outputs.remove(output).unwrap_or(saved.apps),
+            output: output.to_string(),
+            path,
+            ..Default::default()
+        }
+    }
+
+    /// Store this output's order, keeping the other panels' orders in the file.
+    fn save(&self) -> anyhow::Result<()> {
+        let path = self.path.as_deref().ok_or_else(|| anyhow::anyhow!("No state directory"))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut saved = SavedOrder::read(path);
+        saved.outputs.insert(self.output.clone(), self.manual.clone());
+        fs::write(path, serde_json::to_string_pretty(&saved)?)?;
+        Ok(())
+    }
+
+    /// Record open times for new windows and forget closed ones. Windows present at startup
+    /// keep the compositor's order.
+    pub fn observe(&mut self, clients: &[&HyprClient]) {
+        self.opened.retain(|address, _| clients.iter().any(|c| c.address == *address));
+        for client in clients {
+            if !self.opened.contains_key(&client.address) {
+                self.opened.insert(client.address.clone(), self.next_seq);
+                self.next_seq += 1;
+            }
+        }
+    }
+
+    fn seq(&self, client: &HyprClient) -> u64 {
+        self.opened.get(&client.address).copied().unwrap_or(u64::MAX)
+    }
+
+    fn manual_rank(&self, client: &HyprClient) -> usize {
+        let key = app_key(client);
+        self.manual
+            .iter()
+            .position(|k| *k == key)
+            .unwrap_or(self.manual.len())
+    }
+
+    /// Order `clients` into header/task slots. Only `TaskOrder::Workspace` emits headers.
+    pub fn layout(&self, order: TaskOrder, clients: &[&HyprClient]) -> Vec<TaskbarSlot> {
+        let mut sorted: Vec<&HyprClient> = clients.to_vec();
+        match order {
+            TaskOrder::Workspace => sorted.sort_by_key(|c| (c.workspace.id, self.seq(c))),
+            TaskOrder::OpenTime => sorted.sort_by_key(|c| self.seq(c)),
+            TaskOrder::App => sorted.sort_by_key(|c| (app_key(c), self.seq(c))),
+            TaskOrder::Manual => sorted.sort_by_key(|c| (self.manual_rank(c), self.seq(c))),
+        }
+
+        let mut slots = Vec::with_capacity(sorted.len() * 2);
+        let mut current_ws = None;
+        for client in sorted {
+            if order == TaskOrder::Workspace && current_ws != Some(client.workspace.id) {
+                current_ws = Some(client.workspace.id);
+                slots.push(TaskbarSlot::Workspace(client.workspace.id));
+            }
+            slots.push(TaskbarSlot::Task(client.address.clone()));
+        }
+        slots
+    }
+
+    /// Like `layout`, but windows of the same app collapse into one `Group` slot placed
+    /// where its first window would be. Groups are per workspace in `Workspace` order.
+    /// Apps with a single window stay plain `Task`s. Group ids start with `scope` (e.g.
+    /// `regular` or `special`), so layouts of separate task lists can share one member map.
+    /// Returns the slots and each group's member addresses, in layout order.
+    pub fn layout_grouped(
+        &self,
+        order: TaskOrder,
+        clients: &[&HyprClient],
+        scope: &str,
+    ) -> (Vec<TaskbarSlot>, HashMap<String, Vec<String>>) {
+        let mut slots = Vec::new();
+        let mut members: HashMap<String, Vec<String>> = HashMap::new();
+        for slot in self.layout(order, clients) {
+            let address = match &slot {
+                TaskbarSlot::Task(address) => address,
+                _ => {
+                    slots.push(slot);
+                    continue;
+                }
+            };
+            let client = match clients.iter().find(|c| c.address == *address) {
+                Some(c) => c,
+                None => continue,
+            };
+            let group = if order == TaskOrder::Workspace {
+                format!("{scope}:{}:{}", client.workspace.id, app_key(client))
+            } else {
+                format!("{scope}:{}", app_key(client))
+            };
+            let entry = members.entry(group.clone()).or_default();
+            if entry.is_empty() {
+                slots.push(TaskbarSlot::Group(group));
+            }
+            entry.push(address.clone());
+        }
+
+        for slot in slots.iter_mut() {
+            if let TaskbarSlot::Group(group) = slot {
+                if let Some([only]) = members.get(group.as_str()).map(Vec::as_slice) {
+                    *slot = TaskbarSlot::Task(only.clone());
+                }
+            }
+        }
+        members.retain(|_, addresses| addresses.len() > 1);
+        (slots, members)
+    }
+
+    /// Move the app of window `dragged` in front of the app of window `target` and save the
+    /// manual order. `clients` are the windows currently shown, in display order.
+    pub fn move_before(&mut self, dragged: &str, target: &str, clients: &[&HyprClient]) -> anyhow::Result<()> {
+        let find = |address: &str| clients.iter().find(|c| c.address == address).map(|c| app_key(c));
+        let (dragged, target) = match (find(dragged), find(target)) {
+            (Some(d), Some(t)) => (d, t),
+            _ => return Ok(()),
+        };
+        if dragged == target {
+            return Ok(());
+        }
+
+        // Make every shown app explicit so the drop lands exactly where it was made.
+        for client in self.manual_display_order(clients) {
+            let key = app_key(client);
+            if !self.manual.contains(&key) {
+                self.manual.push(key);
+            }
+        }
+        self.manual.retain(|k| *k != dragged);
+        let pos = self
+            .manual
+            .iter()
+            .position(|k| *k == target)
+            .unwrap_or(self.manual.len());
+        self.manual.insert(pos, dragged);
+        self.save()
+    }
+
+    fn manual_display_order<'a>(&self, clients: &[&'a HyprClient]) -> Vec<&'a HyprClient> {
+        let mut sorted: Vec<&HyprClient> = clients.to_vec();
+        sorted.sort_by_key(|c| (self.manual_rank(c), self.seq(c)));
+        sorted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::hyprland::HyprWorkspaceInfo;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn client(address: &str, class: &str, ws: i32) -> HyprClient {
+        HyprClient {
+            address: address.to_string(),
+            mapped: true,
+            hidden: false,
+            at: (0, 0),
+            size: (0, 0),
+            workspace: HyprWorkspaceInfo {
+                id: ws,
+                name: ws.to_string(),
+            },
+            floating: false,
+            fullscreen: false,
+            fullscreen_mode: 0,
+            maximized: false,
+            focus_history_id: 0,
+            pid: 0,
+            xwayland: false,
+            title: format!("{class} window"),
+            class: class.to_string(),
+            initial_class: class.to_string(),
+        }
+    }
+
+    fn state_file() -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "nwg-panel-taskbar-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("taskbar-order.json")
+    }
+
+    #[test]
+    fn manual_order_is_saved_per_output() {
+        let path = state_file();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, r#"{"apps": ["legacy"]}"#).unwrap();
+
+        let (a, b) = (client("0x1", "foot", 1), client("0x2", "firefox", 1));
+        let shown = [&a, &b];
+        let mut left = TaskOrderState::load_from(Some(path.clone()), "DP-1");
+        let mut right = TaskOrderState::load_from(Some(path.clone()), "HDMI-A-1");
+        assert_eq!(left.manual, vec!["legacy"]);
+        left.observe(&shown);
+        right.observe(&shown);
+        left.move_before("0x2", "0x1", &shown).unwrap();
+        right.move_before("0x1", "0x2", &shown).unwrap();
+
+        assert_eq!(TaskOrderState::load_from(Some(path.clone()), "DP-1").manual, vec!["legacy", "firefox", "foot"]);
+        assert_eq!(TaskOrderState::load_from(Some(path.clone()), "HDMI-A-1").manual, vec!["legacy", "foot", "firefox"]);
+        // Outputs without their own order still start from the old single list.
+        assert_eq!(TaskOrderState::load_from(Some(path), "eDP-1").manual, vec!["legacy"]);
+    }
+
+    fn task(address: &str) -> TaskbarSlot {
+        TaskbarSlot::Task(address.to_string())
+    }
+
+    #[test]
+    fn diff_reports_added_removed_moved_and_changed() {
+        let old = [client("0x1", "foot", 1), client("0x2", "firefox", 1), client("0x3", "mpv", 2)];
+        let mut retitled = client("0x2", "firefox", 1);
+        retitled.title = "Another page".to_string();
+        // Moving to another workspace is not a content change.
+        let moved = client("0x3", "mpv", 3);
+        let new = [retitled, moved, client("0x4", "gimp", 1)];
+
+        let old: Vec<&HyprClient> = old.iter().collect();
+        let new: Vec<&HyprClient> = new.iter().collect();
+        let diff = diff_clients(&old, &new);
+        assert_eq!(diff.added, vec!["0x4"]);
+        assert_eq!(diff.removed, vec!["0x1"]);
+        assert_eq!(diff.moved, vec!["0x3"]);
+        assert_eq!(diff.changed, vec!["0x2"]);
+        assert!(diff_clients(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn a_move_alone_is_not_empty() {
+        let (before, after) = (client("0x1", "foot", 1), client("0x1", "foot", 2));
+        let diff = diff_clients(&[&before], &[&after]);
+        assert_eq!(diff.moved, vec!["0x1"]);
+        assert!(diff.changed.is_empty());
+        assert!(!diff.is_empty());
+    }
+
+    #[test]
+    fn layout_orders_by_mode() {
+        let clients = [
+            client("0x1", "foot", 2),
+            client("0x2", "firefox", 1),
+            client("0x3", "alacritty", 2),
+        ];
+        let shown: Vec<&HyprClient> = clients.iter().collect();
+        let path = state_file();
+        let mut order = TaskOrderState::load_from(Some(path.clone()), "out");
+        order.observe(&shown);
+
+        assert_eq!(
+            order.layout(TaskOrder::Workspace, &shown),
+            vec![TaskbarSlot::Workspace(1), task("0x2"), TaskbarSlot::Workspace(2), task("0x1"), task("0x3")]
+        );
+        assert_eq!(order.layout(TaskOrder::OpenTime, &shown), vec![task("0x1"), task("0x2"), task("0x3")]);
+        assert_eq!(order.layout(TaskOrder::App, &shown), vec![task("0x3"), task("0x2"), task("0x1")]);
+
+        // Manual: firefox first, the rest keep opening order.
+        assert!(order.move_before("0x2", "0x1", &shown).is_ok());
+        assert_eq!(order.layout(TaskOrder::Manual, &shown), vec![task("0x2"), task("0x1"), task("0x3")]);
+
+        // The manual order survives a restart.
+        let mut reloaded = TaskOrderState::load_from(Some(path.clone()), "out");
+        assert_eq!(reloaded.manual, vec!["firefox", "foot", "alacritty"]);
+        reloaded.observe(&shown);
+        assert_eq!(reloaded.layout(TaskOrder::Manual, &shown), vec![task("0x2"), task("0x1"), task("0x3")]);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn open_time_survives_closed_windows() {
+        let (a, b, c) = (client("0x1", "foot", 1), client("0x2", "foot", 1), client("0x3", "mpv", 1));
+        let mut order = TaskOrderState::default();
+        order.observe(&[&a, &b]);
+        order.observe(&[&b]);
+        order.observe(&[&c, &b]);
+        assert_eq!(order.layout(TaskOrder::OpenTime, &[&c, &b]), vec![task("0x2"), task("0x3")]);
+    }
+
+    #[test]
+    fn layout_grouped_collapses_apps() {
+        let clients = [
+            client("0x1", "foot", 1),
+            client("0x2", "firefox", 1),
+            client("0x3", "foot", 1),
+            client("0x4", "foot", 2),
+        ];
+        let shown: Vec<&HyprClient> = clients.iter().collect();
+        let mut order = TaskOrderState::default();
+        order.observe(&shown);
+
+        // Per app: one foot group where the first foot window is.
+        let (slots, members) = order.layout_grouped(TaskOrder::OpenTime, &shown, "regular");
+        assert_eq!(slots, vec![TaskbarSlot::Group("regular:foot".to_string()), task("0x2")]);
+        assert_eq!(members.get("regular:foot"), Some(&vec!["0x1".to_string(), "0x3".to_string(), "0x4".to_string()]));
+        assert_eq!(members.len(), 1);
+
+        // Per workspace: the lone foot on workspace 2 stays a plain task.
+        let (slots, members) = order.layout_grouped(TaskOrder::Workspace, &shown, "regular");
+        assert_eq!(
+            slots,
+            vec![
+                TaskbarSlot::Workspace(1),
+                TaskbarSlot::Group("regular:1:foot".to_string()),
+                task("0x2"),
+                TaskbarSlot::Workspace(2),
+                task("0x4"),
+            ]
+        );
+        assert_eq!(members.keys().collect::<Vec<_>>(), vec!["regular:1:foot"]);
+
+        // The same app in another list gets its own group id.
+        let (slots, _) = order.layout_grouped(TaskOrder::OpenTime, &shown, "special");
+        assert_eq!(slots[0], TaskbarSlot::Group("special:foot".to_string()));
+    }
+
+}
@@ -1,23 +1,243 @@
-use super::config::{PanelConfig, ClockConfig};
+use super::app_info::resolve_app;
+use super::taskbar_model::{diff_clients, ClientDiff, TaskOrder, TaskOrderState, TaskbarSlot};
+use super::config::{PanelConfig, ClockConfig, DwlTagsConfig, TaskbarConfig};
+use super::dwl::{dwl_view_tag, DwlOutput, MAX_TAGS};
 use super::hyprland::{HyprWorkspace, HyprClient, TrayItem, TrayIconPayload};
-use super::hyprland::{hyprctl_dispatch_workspace, hyprctl_dispatch_focus_address, hyprctl_dispatch_close_address};
+use super::hyprland::{hyprctl_dispatch_move_address_to_special, hyprctl_toggle_special_workspace, special_workspace_name, workspace_nav_order};
+use super::hyprland::{hyprctl_dispatch_exec, hyprctl_dispatch_move_address_to_monitor, hyprctl_dispatch_move_address_to_workspace};
+use super::hyprland::{hyprctl_dispatch_close_address, hyprctl_dispatch_pin_address, terminate_pid};
+use super::hyprland::{hyprctl_dispatch_toggle_floating_address, hyprctl_dispatch_toggle_fullscreen_address};
+use super::compositor::Compositor;
+use super::sway::swaymsg_scratchpad_show_con;
+use super::wlr::{wlr_close, wlr_toggle_fullscreen, wlr_toggle_maximized, wlr_toggle_minimized};
 use super::hypr_config::HyprConfig;
 use super::controls::ControlsUi;
 use super::tray::activate_sni_item;
+use super::pinned::{is_pinned, pin_app, unpin_app, PinnedUi};
+use super::menu_start::MenuStartUi;
+use super::playerctl::PlayerctlUi;
+use super::system_ui::ResourceUis;
+use super::network_ui::NetworkUi;
+use super::brightness_slider::BrightnessSliderUi;
+use super::random_wallpaper::RandomWallpaperUi;
 use gdk4 as gdk;
 use gtk4 as gtk;
 use gtk::prelude::*;
-use std::cell::RefCell;
-use std::collections::HashMap;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use std::thread;
+use std::time::{Duration, Instant};
+
+/// Icon for a client's app: the desktop entry's `Icon` (a theme name or an absolute path),
+/// then the class as an icon name, then a generic executable icon.
+fn app_icon_name(client: &HyprClient) -> String {
+    let theme = gtk::IconTheme::default();
+    if let Some(app) = resolve_app(&client.class, &client.initial_class, client.pid) {
+        if app.icon.starts_with('/') || (!app.icon.is_empty() && theme.has_icon(&app.icon)) {
+            return app.icon;
+        }
+    }
+    for candidate in [client.class.as_str(), client.initial_class.as_str()] {
+        if !candidate.is_empty() && theme.has_icon(candidate) {
+            return candidate.to_string();
+        }
+    }
+    "application-x-executable".to_string()
+}
+
+fn set_app_icon(image: &gtk::Image, client: &HyprClient) {
+    let icon = app_icon_name(client);
+    if icon.starts_with('/') {
+        image.set_from_file(Some(&icon));
+    } else {
+        image.set_icon_name(Some(&icon));
+    }
+}
+
+fn app_image(client: &HyprClient) -> gtk::Image {
+    let image = gtk::Image::new();
+    set_app_icon(&image, client);
+    image
+}
+
+/// Localized app name from the desktop entry, falling back to the window class.
+fn app_display_name(client: &HyprClient) -> String {
+    match resolve_app(&client.class, &client.initial_class, client.pid) {
+        Some(app) => app.name,
+        None => client.class.clone(),
+    }
+}
+
+/// Prefix of the drag payload carried by task buttons, so stray text drops are ignored.
+const WINDOW_DND_PREFIX: &str = "nwg-panel-window:";
+
+/// Make a task button draggable; the payload is the window address.
+fn attach_window_drag_source(btn: &gtk::Button, address: &str) {
+    let source = gtk::DragSource::new();
+    source.set_actions(gdk::DragAction::MOVE);
+    let payload = format!("{}{}", WINDOW_DND_PREFIX, address);
+    source.set_content(Some(&gdk::ContentProvider::for_value(&payload.to_value())));
+    btn.add_controller(source);
+}
+
+/// Take a taskbar widget out of the box it's in. Box children have to go through
+/// `gtk::Box::remove`; a bare `unparent` leaves the box's bookkeeping stale.
+fn remove_from_box(widget: &impl IsA<gtk::Widget>) {
+    if let Some(parent) = widget.parent().and_downcast::<gtk::Box>() {
+        parent.remove(widget);
+    }
+}
+
+/// Accept task buttons dropped on `widget` and move the window to the workspace given by
+/// `id`, or to the Hyprland special workspace `special` when set.
+fn attach_workspace_drop_target(widget: &impl IsA<gtk::Widget>, compositor: Compositor, id: i32, special: Option<String>) {
+    let target = gtk::DropTarget::new(String::static_type(), gdk::DragAction::MOVE);
+    target.connect_drop(move |_, value, _, _| {
+        let address = match value.get::<String>() {
+            Ok(v) => match v.strip_prefix(WINDOW_DND_PREFIX) {
+                Some(a) => a.to_string(),
+                None => return false,
+            },
+            Err(_) => return false,
+        };
+        let special = special.clone();
+        thread::spawn(move || {
+            let _ = match special {
+                Some(name) => hyprctl_dispatch_move_address_to_special(&address, &name),
+                None => compositor.move_address_to_workspace(&address, id),
+            };
+        });
+        true
+    });
+    target.connect_enter(|target, _, _| {
+        if let Some(widget) = target.widget() {
+            widget.add_css_class("drop-target");
+        }
+        gdk::DragAction::MOVE
+    });
+    target.connect_leave(|target| {
+        if let Some(widget) = target.widget() {
+            widget.remove_css_class("drop-target");
+        }
+    });
+    widget.add_controller(target);
+}
+
+/// Desktop entry of a client's app, via the shared resolver.
+fn desktop_app_info(class: &str, initial_class: &str, pid: i32) -> Option<gio::DesktopAppInfo> {
+    let app = resolve_app(class, initial_class, pid)?;
+    gio::DesktopAppInfo::from_filename(&app.path)
+}
+
+#[derive(Clone)]
+struct WorkspaceButton {
+    button: gtk::Button,
+    icon: Option<gtk::Image>,
+    window_icons: gtk::Box,
+}
+
+/// What the scroll/key handlers need to switch workspaces, without holding the widgets.
+#[derive(Clone)]
+struct WorkspaceNav {
+    config: super::config::HyprlandWorkspacesConfig,
+    compositor: Compositor,
+    monitor_name: String,
+    workspaces: Rc<RefCell<Vec<HyprWorkspace>>>,
+    ws_nums: Rc<RefCell<Vec<i32>>>,
+    active_id: Rc<Cell<i32>>,
+    last_switch: Rc<Cell<Option<Instant>>>,
+}
+
+impl WorkspaceNav {
+    fn candidates(&self) -> Vec<i32> {
+        let monitor_only = match self.config.scroll_mode.as_str() {
+            "existing" => false,
+            "monitor" => true,
+            // `ws_nums` is already in button order.
+            _ => return self.ws_nums.try_borrow().map(|nums| nums.clone()).unwrap_or_default(),
+        };
+        let workspaces = match self.workspaces.try_borrow() {
+            Ok(ws) => ws,
+            Err(_) => return Vec::new(),
+        };
+        let monitor = if monitor_only { Some(self.monitor_name.as_str()) } else { None };
+        workspace_nav_order(&workspaces, monitor)
+    }
+
+    fn target(&self, delta: i32) -> Option<i32> {
+        let ids = self.candidates();
+        if ids.is_empty() {
+            return None;
+        }
+        let active = self.active_id.get();
+        let pos = ids.iter().position(|id| *id == active);
+        let next = match (pos, delta > 0) {
+            (Some(p), true) if p + 1 < ids.len() => p + 1,
+            (Some(_), true) if self.config.scroll_wrap => 0,
+            (Some(p), false) if p > 0 => p - 1,
+            (Some(_), false) if self.config.scroll_wrap => ids.len() - 1,
+            (Some(_), _) => return None,
+            // Active workspace isn't a candidate: go to the nearest one in that direction.
+            (None, true) => ids.iter().position(|id| *id > active).unwrap_or(0),
+            (None, false) => ids.iter().rposition(|id| *id < active).unwrap_or(ids.len() - 1),
+        };
+        ids.get(next).copied()
+    }
+
+    fn switch_to(&self, id: i32) {
+        if id == self.active_id.get() {
+            return;
+        }
+        // Assume the switch succeeds so quick repeated input keeps moving in one direction.
+        self.active_id.set(id);
+        let compositor = self.compositor;
+        thread::spawn(move || {
+            let _ = compositor.dispatch_workspace(id);
+        });
+    }
+
+    fn switch_relative(&self, delta: i32, debounce: bool) {
+        if debounce {
+            let min_gap = Duration::from_millis(self.config.scroll_debounce_ms);
+            if let Some(last) = self.last_switch.get() {
+                if last.elapsed() < min_gap {
+                    return;
+                }
+            }
+            self.last_switch.set(Some(Instant::now()));
+        }
+        if let Some(id) = self.target(delta) {
+            self.switch_to(id);
+        }
+    }
+}
+
+fn run_workspace_click_action(compositor: Compositor, action: &str, id: i32) {
+    let action = action.trim().to_string();
+    if action.is_empty() {
+        return;
+    }
+    thread::spawn(move || {
+        let _ = match action.as_str() {
+            "focus" => compositor.dispatch_workspace(id),
+            "movetoworkspace" => compositor.move_active_to_workspace(id, false),
+            "movetoworkspacesilent" => compositor.move_active_to_workspace(id, true),
+            cmd => std::process::Command::new("sh")
+                .args(["-c", &cmd.replace("{id}", &id.to_string())])
+                .spawn()
+                .map(|_| ())
+                .map_err(anyhow::Error::from),
+        };
+    });
+}
 
 #[derive(Clone)]
 #[allow(dead_code)]
 pub struct WorkspacesUi {
     root: gtk::Box,
     num_box: gtk::Box,
-    buttons: Rc<RefCell<HashMap<i32, gtk::Button>>>,
+    buttons: Rc<RefCell<HashMap<i32, WorkspaceButton>>>,
     name_label: gtk::Label,
     icon: gtk::Image,
     floating_icon: gtk::Image,
@@ -27,28 +247,106 @@ pub struct WorkspacesUi {
     workspace_rules: Rc<RefCell<Vec<super::config::HyprWorkspaceRule>>>,
     ws_nums: Rc<RefCell<Vec<i32>>>,
     hypr_config: HyprConfig,
+    compositor: Compositor,
+    workspaces: Rc<RefCell<Vec<HyprWorkspace>>>,
+    clients: Rc<RefCell<Vec<HyprClient>>>,
+    active_address: Rc<RefCell<String>>,
+    /// Addresses of windows that asked for attention and haven't been focused since.
+    urgent: Rc<RefCell<HashSet<String>>>,
+    special_indicator: gtk::Button,
+    special_open: Rc<RefCell<String>>,
+    nav: WorkspaceNav,
 }
 
 impl WorkspacesUi {
-    pub fn new(config: super::config::HyprlandWorkspacesConfig, monitor_name: String) -> Self {
+    pub fn new(
+        config: super::config::HyprlandWorkspacesConfig,
+        monitor_name: String,
+        compositor: Compositor,
+    ) -> Self {
+        // wlr-foreign-toplevel doesn't say which workspace a window is on, so window counts
+        // are unknown there: never hide "empty" workspaces or show per-workspace window icons.
+        let config = if compositor == Compositor::Wlr {
+            super::config::HyprlandWorkspacesConfig {
+                show_empty: true,
+                show_window_icons: false,
+                ..config
+            }
+        } else {
+            config
+        };
+        let prefix = compositor.css_prefix();
         let root = gtk::Box::new(gtk::Orientation::Horizontal, 6);
-        root.set_widget_name("hyprland-workspaces");
+        root.set_widget_name(&format!("{}-workspaces", prefix));
         
         let num_box = gtk::Box::new(gtk::Orientation::Horizontal, 0);
-        num_box.set_widget_name("hyprland-workspaces");
+        num_box.set_widget_name(&format!("{}-workspaces", prefix));
+        root.append(&num_box);
+
+        // Shown while a special workspace is open on this monitor; click hides it again.
+        let special_indicator = gtk::Button::new();
+        special_indicator.set_widget_name(&format!("{}-workspaces-special", prefix));
+        special_indicator.set_visible(false);
+        let special_open = Rc::new(RefCell::new(String::new()));
+        {
+            let special_open = special_open.clone();
+            special_indicator.connect_clicked(move |_| {
+                let name = match special_open.try_borrow() {
+                    Ok(n) => n.clone(),
+                    Err(_) => return,
+                };
+                if name.is_empty() {
+                    return;
+                }
+                thread::spawn(move || {
+                    let _ = hyprctl_toggle_special_workspace(&name);
+                });
+            });
+        }
+        root.append(&special_indicator);
         
         let name_label = gtk::Label::new(None);
-        name_label.set_widget_name("hyprland-workspaces-name");
+        name_label.set_widget_name(&format!("{}-workspaces-name", prefix));
+        name_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+        name_label.set_max_width_chars(config.name_length as i32);
         
         let icon = gtk::Image::new();
-        icon.set_widget_name("hyprland-workspaces-icon");
+        icon.set_widget_name(&format!("{}-workspaces-icon", prefix));
+        icon.set_pixel_size(config.image_size);
         
-        let floating_icon = gtk::Image::new();
+        let floating_icon = gtk::Image::from_icon_name("focus-windows-symbolic");
+        floating_icon.set_widget_name(&format!("{}-workspaces-floating", prefix));
+        floating_icon.set_pixel_size(config.image_size);
+        floating_icon.set_visible(false);
+
+        // Upstream layout: workspace numbers, then the focused window's icon and title.
+        if config.show_icon {
+            root.append(&icon);
+        }
+        root.append(&floating_icon);
+        if config.show_name {
+            root.append(&name_label);
+        }
         
         let mut hypr_config = HyprConfig::new();
-        // Try to load Hyprland config, but don't fail if it doesn't exist
-        let _ = hypr_config.parse_config("hyprland.conf");
+        if compositor == Compositor::Hyprland {
+            // Try to load Hyprland config, but don't fail if it doesn't exist
+            let _ = hypr_config.parse_config("hyprland.conf");
+        }
         
+        let workspaces = Rc::new(RefCell::new(Vec::new()));
+        let ws_nums = Rc::new(RefCell::new(Vec::new()));
+        let nav = WorkspaceNav {
+            config: config.clone(),
+            compositor,
+            monitor_name: monitor_name.clone(),
+            workspaces: workspaces.clone(),
+            ws_nums: ws_nums.clone(),
+            active_id: Rc::new(Cell::new(0)),
+            last_switch: Rc::new(Cell::new(None)),
+        };
+        Self::attach_navigation(&num_box, &nav);
+
         Self {
             root,
             num_box,
@@ -60,30 +358,205 @@ impl WorkspacesUi {
             ws_id2name: Rc::new(RefCell::new(HashMap::new())),
             monitor_name,
             workspace_rules: Rc::new(RefCell::new(hypr_config.get_workspace_rules())),
-            ws_nums: Rc::new(RefCell::new(Vec::new())),
+            ws_nums,
             hypr_config,
+            compositor,
+            workspaces,
+            clients: Rc::new(RefCell::new(Vec::new())),
+            active_address: Rc::new(RefCell::new(String::new())),
+            urgent: Rc::new(RefCell::new(HashSet::new())),
+            special_indicator,
+            special_open,
+            nav,
         }
     }
 
+    fn attach_navigation(num_box: &gtk::Box, nav: &WorkspaceNav) {
+        if nav.config.scroll_switch {
+            // DISCRETE accumulates smooth (trackpad) deltas into whole steps.
+            let scroll = gtk::EventControllerScroll::new(
+                gtk::EventControllerScrollFlags::BOTH_AXES | gtk::EventControllerScrollFlags::DISCRETE,
+            );
+            let nav_for_scroll = nav.clone();
+            scroll.connect_scroll(move |_, dx, dy| {
+                let delta = if dy != 0.0 { dy } else { dx };
+                if delta > 0.0 {
+                    nav_for_scroll.switch_relative(1, true);
+                } else if delta < 0.0 {
+                    nav_for_scroll.switch_relative(-1, true);
+                }
+                glib::Propagation::Stop
+            });
+            num_box.add_controller(scroll);
+        }
+
+        let keys = gtk::EventControllerKey::new();
+        let nav_for_keys = nav.clone();
+        keys.connect_key_pressed(move |_, key, _, _| {
+            match key {
+                gdk::Key::Left | gdk::Key::Up => nav_for_keys.switch_relative(-1, false),
+                gdk::Key::Right | gdk::Key::Down => nav_for_keys.switch_relative(1, false),
+                gdk::Key::Home => {
+                    if let Some(id) = nav_for_keys.candidates().first() {
+                        nav_for_keys.switch_to(*id);
+                    }
+                }
+                gdk::Key::End => {
+                    if let Some(id) = nav_for_keys.candidates().last() {
+                        nav_for_keys.switch_to(*id);
+                    }
+                }
+                _ => return glib::Propagation::Proceed,
+            }
+            glib::Propagation::Stop
+        });
+        num_box.add_controller(keys);
+    }
+
     pub fn widget(&self) -> gtk::Widget {
         self.root.clone().upcast()
     }
 
+    pub fn set_clients(&self, clients: Vec<HyprClient>) {
+        if let Ok(mut urgent) = self.urgent.try_borrow_mut() {
+            urgent.retain(|address| clients.iter().any(|c| c.address == *address));
+        }
+        match self.clients.try_borrow_mut() {
+            Ok(mut c) => *c = clients,
+            Err(_) => return,
+        }
+        self.update_icons();
+        self.update_urgent();
+    }
+
+    pub fn set_active_address(&self, address: String) {
+        if let Ok(mut urgent) = self.urgent.try_borrow_mut() {
+            urgent.remove(&address);
+        }
+        match self.active_address.try_borrow_mut() {
+            Ok(mut a) => {
+                if *a == address {
+                    return;
+                }
+                *a = address;
+            }
+            Err(_) => return,
+        }
+        self.update_icons();
+        self.update_urgent();
+    }
+
+    pub fn mark_urgent(&self, address: String) {
+        let is_active = self.active_address.try_borrow().map(|a| *a == address).unwrap_or(false);
+        if is_active {
+            return;
+        }
+        match self.urgent.try_borrow_mut() {
+            Ok(mut urgent) => {
+                if !urgent.insert(address) {
+                    return;
+                }
+            }
+            Err(_) => return,
+        }
+        self.update_urgent();
+    }
+
+    /// Replace the urgent set with a backend's full list (sway, niri), so hints the
+    /// window dropped are cleared too. Returns true when a window became urgent.
+    pub fn set_urgent(&self, addresses: Vec<String>) -> bool {
+        let active = self.active_address.try_borrow().map(|a| a.clone()).unwrap_or_default();
+        let next: HashSet<String> = addresses.into_iter().filter(|a| *a != active).collect();
+        let newly_urgent = match self.urgent.try_borrow_mut() {
+            Ok(mut urgent) => {
+                if *urgent == next {
+                    return false;
+                }
+                let newly_urgent = next.iter().any(|a| !urgent.contains(a));
+                *urgent = next;
+                newly_urgent
+            }
+            Err(_) => return false,
+        };
+        self.update_urgent();
+        newly_urgent
+    }
+
+    /// Workspace buttons get the `urgent` class while any of their windows is urgent.
+    fn update_urgent(&self) {
+        let (buttons, clients, urgent) = match (
+            self.buttons.try_borrow(),
+            self.clients.try_borrow(),
+            self.urgent.try_borrow(),
+        ) {
+            (Ok(b), Ok(c), Ok(u)) => (b, c, u),
+            _ => return,
+        };
+        for (id, entry) in buttons.iter() {
+            let has_urgent = clients
+                .iter()
+                .any(|c| c.workspace.id == *id && urgent.contains(&c.address));
+            if has_urgent {
+                entry.button.add_css_class("urgent");
+            } else {
+                entry.button.remove_css_class("urgent");
+            }
+        }
+    }
+
     pub fn set_workspaces(&self, workspaces: Vec<HyprWorkspace>, active_id: i32) {
         // Don't show workspaces if disabled in config
         if !self.config.show_workspaces {
             return;
         }
 
+        self.nav.active_id.set(active_id);
+
         let mut buttons = match self.buttons.try_borrow_mut() {
             Ok(b) => b,
             Err(_) => return,
         };
 
+        // Offer workspaces 1..=num_ws, like upstream; existing ones are ordered by id,
+        // with special workspaces (negative ids) after the regular ones.
+        let mut workspaces = self.with_persistent_workspaces(workspaces);
+        // Named sway workspaces (`idx` 0) go after the numbered ones.
+        workspaces.sort_by_key(|w| (w.id < 0, w.idx == 0, w.idx, w.id));
+
+        let visible: Vec<&HyprWorkspace> = workspaces
+            .iter()
+            .filter(|ws| {
+                // Filter workspaces by monitor if configured. Persistent placeholders without a
+                // `monitor:` rule are shown everywhere.
+                if !self.config.show_workspaces_from_all_outputs
+                    && !ws.monitor.is_empty()
+                    && ws.monitor != self.monitor_name
+                {
+                    return false;
+                }
+
+                if special_workspace_name(&ws.name).is_some() && ws.id < 0 {
+                    return self.config.show_special;
+                }
+
+                // Skip empty workspaces if config says so
+                if !self.config.show_empty && ws.windows == 0 {
+                    return false;
+                }
+
+                // Skip inactive workspaces if config says so
+                if !self.config.show_inactive_workspaces && ws.id != active_id {
+                    return false;
+                }
+
+                true
+            })
+            .collect();
+
         let mut to_remove = Vec::new();
-        for (id, btn) in buttons.iter() {
-            if !workspaces.iter().any(|w| w.id == *id) {
-                self.root.remove(btn);
+        for (id, entry) in buttons.iter() {
+            if !visible.iter().any(|w| w.id == *id) {
+                self.num_box.remove(&entry.button);
                 to_remove.push(*id);
             }
         }
@@ -91,89 +564,314 @@ impl WorkspacesUi {
             buttons.remove(&id);
         }
 
-        // Limit number of workspaces if configured
-        let mut workspaces = workspaces;
-        if workspaces.len() > self.config.num_ws {
-            workspaces.sort_by_key(|w| w.id);
-            workspaces.truncate(self.config.num_ws);
+        let mut prev: Option<gtk::Widget> = None;
+        for ws in visible.iter() {
+            let btn = if let Some(entry) = buttons.get(&ws.id) {
+                entry.button.clone()
+            } else {
+                let entry = self.create_workspace_button(ws);
+                self.num_box.append(&entry.button);
+                let btn = entry.button.clone();
+                buttons.insert(ws.id, entry);
+                btn
+            };
+
+            // Keep the on-screen order equal to the sorted order.
+            self.num_box.reorder_child_after(&btn, prev.as_ref());
+            prev = Some(btn.clone().upcast());
+
+            if ws.id == active_id {
+                btn.set_widget_name("task-box-focused");
+                btn.add_css_class("active");
+            } else {
+                btn.set_widget_name("task-box");
+                btn.remove_css_class("active");
+            }
+
+            if ws.windows == 0 && self.compositor != Compositor::Wlr {
+                btn.add_css_class("empty");
+            } else {
+                btn.remove_css_class("empty");
+            }
+        }
+
+        if let Ok(mut nums) = self.ws_nums.try_borrow_mut() {
+            // Scrolling/keyboard navigation skips special workspaces.
+            *nums = visible.iter().filter(|w| w.id > 0).map(|w| w.id).collect();
         }
+        drop(buttons);
 
-        for ws in workspaces {
-            // Filter workspaces by monitor if configured
-            if !self.config.show_workspaces_from_all_outputs && ws.monitor != self.monitor_name {
-                continue;
+        if let Ok(mut stored) = self.workspaces.try_borrow_mut() {
+            *stored = workspaces;
+        }
+        self.update_icons();
+        self.update_special();
+        self.update_urgent();
+    }
+
+    /// Record which special workspace is open on this panel's monitor.
+    pub fn set_special_workspaces(&self, open: &HashMap<String, String>) {
+        let name = open
+            .get(&self.monitor_name)
+            .and_then(|n| special_workspace_name(n))
+            .unwrap_or("")
+            .to_string();
+        match self.special_open.try_borrow_mut() {
+            Ok(mut current) => {
+                if *current == name {
+                    return;
+                }
+                *current = name;
             }
+            Err(_) => return,
+        }
+        self.update_special();
+    }
 
-            // Skip empty workspaces if config says so
-            if !self.config.show_empty && ws.windows == 0 {
-                continue;
+    fn update_special(&self) {
+        let (open, buttons, workspaces) = match (
+            self.special_open.try_borrow(),
+            self.buttons.try_borrow(),
+            self.workspaces.try_borrow(),
+        ) {
+            (Ok(o), Ok(b), Ok(w)) => (o, b, w),
+            _ => return,
+        };
+
+        for ws in workspaces.iter().filter(|w| w.id < 0) {
+            let name = match special_workspace_name(&ws.name) {
+                Some(n) => n,
+                None => continue,
+            };
+            if let Some(entry) = buttons.get(&ws.id) {
+                if !open.is_empty() && name == open.as_str() {
+                    entry.button.add_css_class("special-open");
+                } else {
+                    entry.button.remove_css_class("special-open");
+                }
             }
+        }
 
-            // Skip inactive workspaces if config says so
-            if !self.config.show_inactive_workspaces && ws.id != active_id {
+        let show = self.config.show_special_indicator && !open.is_empty();
+        if show {
+            self.special_indicator.set_label(&open);
+            self.special_indicator
+                .set_tooltip_text(Some(&format!("Hide special workspace {}", open)));
+        }
+        self.special_indicator.set_visible(show);
+    }
+
+    /// Add placeholders for workspaces 1..=num_ws that don't exist yet, placed on the monitor
+    /// given by a matching `workspace = N, monitor:X` rule.
+    ///
+    /// Only Hyprland and Sway: niri and ext-workspace ids aren't numbers the user picks, so a
+    /// placeholder for "workspace 3" would dispatch to an unrelated workspace.
+    fn with_persistent_workspaces(&self, mut workspaces: Vec<HyprWorkspace>) -> Vec<HyprWorkspace> {
+        if !matches!(self.compositor, Compositor::Hyprland | Compositor::Sway) {
+            return workspaces;
+        }
+        let rules = match self.workspace_rules.try_borrow() {
+            Ok(r) => r.clone(),
+            Err(_) => Vec::new(),
+        };
+
+        for id in 1..=self.config.num_ws as i32 {
+            if workspaces.iter().any(|w| w.id == id) {
                 continue;
             }
+            let monitor = rules
+                .iter()
+                .find(|r| r.workspace_string == id.to_string())
+                .map(|r| r.monitor.clone())
+                .unwrap_or_default();
+            workspaces.push(HyprWorkspace {
+                id,
+                idx: id,
+                name: id.to_string(),
+                monitor,
+                windows: 0,
+                hasfullscreen: false,
+                lastwindow: String::new(),
+                lastwindowtitle: String::new(),
+                ispersistent: true,
+            });
+        }
+
+        workspaces
+    }
+
+    fn create_workspace_button(&self, ws: &HyprWorkspace) -> WorkspaceButton {
+        let prefix = self.compositor.css_prefix();
+        let btn = gtk::Button::new();
+        btn.set_widget_name(&format!("{}-workspace", prefix));
+
+        // Create button content based on config
+        let content = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        
+        // Icon of the workspace's last focused window, if enabled
+        let icon = if self.config.show_icon {
+            let icon = gtk::Image::new();
+            icon.set_widget_name(&format!("{}-workspace-icon", prefix));
+            icon.set_pixel_size(self.config.image_size);
+            icon.set_icon_size(gtk::IconSize::Normal);
+            icon.set_visible(false);
+            content.append(&icon);
+            Some(icon)
+        } else {
+            None
+        };
 
-            let btn = if let Some(btn) = buttons.get(&ws.id) {
-                btn.clone()
+        let special = if ws.id < 0 { special_workspace_name(&ws.name) } else { None };
+
+        // Add name/number label
+        let number = if ws.idx == 0 && !ws.name.trim().is_empty() {
+            ws.name.clone()
+        } else {
+            ws.idx.to_string()
+        };
+        let label_text = if let Some(name) = special {
+            name.to_string()
+        } else if self.config.show_name {
+            if ws.name.trim().is_empty() {
+                number
             } else {
-                let btn = gtk::Button::new();
-                btn.set_widget_name("hyprland-workspace");
-
-                // Create button content based on config
-                let content = gtk::Box::new(gtk::Orientation::Horizontal, 4);
-                
-                // Add icon if enabled
-                if self.config.show_icon {
-                    let icon = gtk::Image::new();
-                    icon.set_widget_name("hyprland-workspace-icon");
-                    icon.set_pixel_size(self.config.image_size);
-                    icon.set_icon_size(gtk::IconSize::Normal);
-                    // TODO: Set actual workspace icon when available
-                    content.append(&icon);
-                }
-
-                // Add name/number label
-                let label_text = if self.config.show_name {
-                    if ws.name.trim().is_empty() {
-                        ws.id.to_string()
-                    } else {
-                        // Truncate name if it exceeds configured length
-                        let name = ws.name.clone();
-                        if name.len() > self.config.name_length {
-                            name.chars().take(self.config.name_length).collect::<String>() + "..."
-                        } else {
-                            name
-                        }
-                    }
+                // Truncate name if it exceeds configured length
+                let name = ws.name.clone();
+                if name.len() > self.config.name_length {
+                    name.chars().take(self.config.name_length).collect::<String>() + "..."
                 } else {
-                    ws.id.to_string()
-                };
+                    name
+                }
+            }
+        } else {
+            number
+        };
 
-                let label = gtk::Label::new(Some(&label_text));
-                label.set_widget_name("hyprland-workspace-name");
-                content.append(&label);
+        let label = gtk::Label::new(Some(&label_text));
+        label.set_widget_name(&format!("{}-workspace-name", prefix));
+        content.append(&label);
 
-                btn.set_child(Some(&content));
+        // Optional icons for every window on the workspace
+        let window_icons = gtk::Box::new(gtk::Orientation::Horizontal, 2);
+        window_icons.set_widget_name(&format!("{}-workspace-windows", prefix));
+        window_icons.set_visible(self.config.show_window_icons);
+        content.append(&window_icons);
 
-                let id_for_click = ws.id;
-                btn.connect_clicked(move |_| {
-                    thread::spawn(move || {
-                        let _ = hyprctl_dispatch_workspace(id_for_click);
-                    });
+        btn.set_child(Some(&content));
+
+        let id_for_click = ws.id;
+        let compositor = self.compositor;
+        if compositor != Compositor::Wlr {
+            attach_workspace_drop_target(&btn, compositor, ws.id, special.map(str::to_string));
+        }
+        if let Some(name) = special {
+            btn.add_css_class("special");
+            let name = name.to_string();
+            btn.connect_clicked(move |_| {
+                let name = name.clone();
+                thread::spawn(move || {
+                    let _ = hyprctl_toggle_special_workspace(&name);
+                });
+            });
+        } else {
+            btn.connect_clicked(move |_| {
+                thread::spawn(move || {
+                    let _ = compositor.dispatch_workspace(id_for_click);
                 });
+            });
+        }
 
-                self.root.append(&btn);
-                buttons.insert(ws.id, btn.clone());
-                btn
+        for (button, action) in [(2, &self.config.middle_click), (3, &self.config.right_click)] {
+            if action.trim().is_empty() {
+                continue;
+            }
+            let gesture = gtk::GestureClick::new();
+            gesture.set_button(button);
+            let action = action.clone();
+            gesture.connect_released(move |_, _, _, _| {
+                run_workspace_click_action(compositor, &action, id_for_click);
+            });
+            btn.add_controller(gesture);
+        }
+
+        WorkspaceButton {
+            button: btn,
+            icon,
+            window_icons,
+        }
+    }
+
+    fn update_icons(&self) {
+        let (buttons, workspaces, clients, active_address) = match (
+            self.buttons.try_borrow(),
+            self.workspaces.try_borrow(),
+            self.clients.try_borrow(),
+            self.active_address.try_borrow(),
+        ) {
+            (Ok(b), Ok(w), Ok(c), Ok(a)) => (b, w, c, a),
+            _ => return,
+        };
+
+        for ws in workspaces.iter() {
+            let entry = match buttons.get(&ws.id) {
+                Some(e) => e,
+                None => continue,
             };
+            let on_ws: Vec<&HyprClient> = clients.iter().filter(|c| c.workspace.id == ws.id).collect();
+
+            if let Some(icon) = entry.icon.as_ref() {
+                let last = on_ws
+                    .iter()
+                    .find(|c| c.address == ws.lastwindow)
+                    .or_else(|| on_ws.first());
+                match last {
+                    Some(client) => {
+                        set_app_icon(&icon, client);
+                        icon.set_visible(true);
+                    }
+                    None => icon.set_visible(false),
+                }
+            }
 
-            if ws.id == active_id {
-                btn.set_widget_name("task-box-focused");
-                btn.add_css_class("active");
-            } else {
-                btn.set_widget_name("task-box");
-                btn.remove_css_class("active");
+            if self.config.show_window_icons {
+                while let Some(child) = entry.window_icons.first_child() {
+                    entry.window_icons.remove(&child);
+                }
+                for client in on_ws.iter() {
+                    let img = app_image(client);
+                    img.set_pixel_size(self.config.image_size);
+                    img.set_tooltip_text(Some(&client.title));
+                    if self.config.mark_floating && client.floating {
+                        img.add_css_class("floating");
+                    }
+                    if self.config.mark_xwayland && client.xwayland {
+                        img.add_css_class("xwayland");
+                    }
+                    entry.window_icons.append(&img);
+                }
+            }
+        }
+
+        // Focused window: icon, title and markers next to the workspace numbers.
+        match clients.iter().find(|c| c.address == *active_address) {
+            Some(client) => {
+                set_app_icon(&self.icon, client);
+                self.icon.set_visible(self.config.show_icon);
+
+                let mut title = client.title.clone();
+                if title.chars().count() > self.config.name_length {
+                    title = title.chars().take(self.config.name_length).collect::<String>() + "...";
+                }
+                if self.config.mark_xwayland && client.xwayland {
+                    title = format!("X|{}", title);
+                }
+                self.name_label.set_text(&title);
+                self.floating_icon.set_visible(self.config.mark_floating && client.floating);
+            }
+            None => {
+                self.icon.set_visible(false);
+                self.name_label.set_text("");
+                self.floating_icon.set_visible(false);
             }
         }
     }
@@ -183,24 +881,106 @@ impl WorkspacesUi {
 #[allow(dead_code)]
 pub struct TaskbarUi {
     root: gtk::Box,
+    /// Regular workspaces' labels and tasks.
+    tasks_box: gtk::Box,
+    /// Windows on Hyprland special (scratchpad) workspaces, kept after the regular ones.
+    special_box: gtk::Box,
     buttons: Rc<RefCell<HashMap<String, gtk::Button>>>,
     workspace_labels: Rc<RefCell<HashMap<i32, gtk::Label>>>,
+    /// `group-apps` mode: one button per group id, and each group's window addresses.
+    group_buttons: Rc<RefCell<HashMap<String, gtk::Button>>>,
+    group_members: Rc<RefCell<HashMap<String, Vec<String>>>>,
     clients: Rc<RefCell<Vec<HyprClient>>>,
+    /// The clients the buttons currently reflect, for diffing against the next update.
+    shown: Rc<RefCell<Vec<HyprClient>>>,
     active_address: Rc<RefCell<String>>,
+    urgent: Rc<RefCell<HashSet<String>>>,
     last_workspace_count: Rc<RefCell<i32>>,
+    /// Workspace id -> monitor name, used to keep only this panel's windows.
+    workspace_monitors: Rc<RefCell<HashMap<i32, String>>>,
+    order: Rc<RefCell<TaskOrderState>>,
+    /// Set by a manual-order drop (or a layout that couldn't run); the main loop then
+    /// re-lays out (`apply_order_change`).
+    order_changed: Rc<Cell<bool>>,
+    order_mode: TaskOrder,
+    config: TaskbarConfig,
+    monitor_name: String,
+    compositor: Compositor,
+    /// `num_ws` of the workspaces block, for the "Move to workspace" menu.
+    num_ws: usize,
 }
 
 impl TaskbarUi {
-    pub fn new() -> Self {
-        let root = gtk::Box::new(gtk::Orientation::Horizontal, 4);
-        root.set_widget_name("hyprland-taskbar");
+    pub fn new(config: TaskbarConfig, monitor_name: String, compositor: Compositor, num_ws: usize) -> Self {
+        // GTK4 labels can't be rotated; on vertical panels stack the tasks instead.
+        let angle = config.angle.rem_euclid(360.0);
+        let orientation = if angle == 90.0 || angle == 270.0 {
+            gtk::Orientation::Vertical
+        } else {
+            gtk::Orientation::Horizontal
+        };
+        let root = gtk::Box::new(orientation, 4);
+        root.set_widget_name(&format!("{}-taskbar", compositor.css_prefix()));
+        let tasks_box = gtk::Box::new(orientation, 4);
+        root.append(&tasks_box);
+        let special_box = gtk::Box::new(orientation, 4);
+        special_box.set_widget_name(&format!("{}-taskbar-special", compositor.css_prefix()));
+        special_box.set_visible(false);
+        root.append(&special_box);
         Self {
             root,
+            tasks_box,
+            special_box,
             buttons: Rc::new(RefCell::new(HashMap::new())),
             workspace_labels: Rc::new(RefCell::new(HashMap::new())),
+            group_buttons: Rc::new(RefCell::new(HashMap::new())),
+            group_members: Rc::new(RefCell::new(HashMap::new())),
             clients: Rc::new(RefCell::new(Vec::new())),
+            shown: Rc::new(RefCell::new(Vec::new())),
             active_address: Rc::new(RefCell::new(String::new())),
+            urgent: Rc::new(RefCell::new(HashSet::new())),
             last_workspace_count: Rc::new(RefCell::new(0)),
+            workspace_monitors: Rc::new(RefCell::new(HashMap::new())),
+            order: Rc::new(RefCell::new(TaskOrderState::load(&monitor_name))),
+            order_changed: Rc::new(Cell::new(false)),
+            order_mode: match TaskOrder::from_config(&config.sort_by) {
+                // No workspace membership on wlr-foreign-toplevel, hence no workspace headers.
+                TaskOrder::Workspace if compositor == Compositor::Wlr => TaskOrder::OpenTime,
+                mode => mode,
+            },
+            config,
+            monitor_name,
+            compositor,
+            num_ws,
+        }
+    }
+
+    pub fn set_workspaces(&self, workspaces: &[HyprWorkspace]) {
+        let monitors: HashMap<i32, String> = workspaces
+            .iter()
+            .map(|w| (w.id, w.monitor.clone()))
+            .collect();
+        match self.workspace_monitors.try_borrow_mut() {
+            Ok(mut current) => {
+                if *current == monitors {
+                    return;
+                }
+                *current = monitors;
+            }
+            Err(_) => return,
+        }
+        self.update_taskbar();
+    }
+
+    /// Whether a client belongs on this panel. Windows whose workspace monitor is unknown
+    /// (e.g. on the generic Wayland backend) are always shown.
+    fn on_this_output(&self, client: &HyprClient, monitors: &HashMap<i32, String>) -> bool {
+        if self.config.all_outputs || self.monitor_name.is_empty() {
+            return true;
+        }
+        match monitors.get(&client.workspace.id) {
+            Some(monitor) if !monitor.is_empty() => *monitor == self.monitor_name,
+            _ => true,
         }
     }
 
@@ -209,26 +989,61 @@ impl TaskbarUi {
     }
 
     pub fn set_clients(&self, clients: Vec<HyprClient>) {
-        let mut clients_ref = match self.clients.try_borrow_mut() {
-            Ok(c) => c,
+        if let Ok(mut urgent) = self.urgent.try_borrow_mut() {
+            urgent.retain(|address| clients.iter().any(|c| c.address == *address));
+        }
+        match self.clients.try_borrow_mut() {
+            Ok(mut c) => *c = clients,
             Err(_) => return,
-        };
-        
-        *clients_ref = clients;
+        }
         self.update_taskbar();
     }
 
     pub fn set_active_address(&self, address: String) {
-        let mut active_ref = match self.active_address.try_borrow_mut() {
-            Ok(a) => a,
+        if let Ok(mut urgent) = self.urgent.try_borrow_mut() {
+            urgent.remove(&address);
+        }
+        match self.active_address.try_borrow_mut() {
+            Ok(mut a) => {
+                if *a == address {
+                    return;
+                }
+                *a = address;
+            }
             Err(_) => return,
-        };
-        
-        if *active_ref == address {
+        }
+        self.update_taskbar();
+    }
+
+    pub fn mark_urgent(&self, address: String) {
+        let is_active = self.active_address.try_borrow().map(|a| *a == address).unwrap_or(false);
+        if is_active {
             return;
         }
-        
-        *active_ref = address.clone();
+        match self.urgent.try_borrow_mut() {
+            Ok(mut urgent) => {
+                if !urgent.insert(address) {
+                    return;
+                }
+            }
+            Err(_) => return,
+        }
+        self.update_taskbar();
+    }
+
+    /// Replace the urgent set with a backend's full list (see `WorkspacesUi::set_urgent`).
+    pub fn set_urgent(&self, addresses: Vec<String>) {
+        let active = self.active_address.try_borrow().map(|a| a.clone()).unwrap_or_default();
+        let next: HashSet<String> = addresses.into_iter().filter(|a| *a != active).collect();
+        match self.urgent.try_borrow_mut() {
+            Ok(mut urgent) => {
+                if *urgent == next {
+                    return;
+                }
+                *urgent = next;
+            }
+            Err(_) => return,
+        }
         self.update_taskbar();
     }
 
@@ -243,153 +1058,790 @@ impl TaskbarUi {
             Err(_) => return,
         };
 
-        // Group clients by workspace
-        let mut workspace_groups: HashMap<i32, Vec<HyprClient>> = HashMap::new();
-        for client in clients.iter() {
-            workspace_groups.entry(client.workspace.id).or_insert_with(Vec::new).push(client.clone());
+        let monitors = match self.workspace_monitors.try_borrow() {
+            Ok(m) => m,
+            Err(_) => return,
+        };
+
+        let visible: Vec<&HyprClient> = clients
+            .iter()
+            .filter(|c| self.on_this_output(c, &monitors))
+            .collect();
+        let diff = match self.update_client_buttons(&visible, &active_address) {
+            Some(d) => d,
+            None => return,
+        };
+        // The layout only changes when windows come, go, move between workspaces or change
+        // class, or after a manual-order drop. Otherwise just refresh the group buttons.
+        if diff.is_empty() && !self.order_changed.replace(false) {
+            let members = self.group_members.try_borrow().map(|m| m.clone()).unwrap_or_default();
+            self.update_group_buttons(members, &visible, &active_address);
+            return;
         }
 
-        // Sort workspaces
-        let mut workspace_ids: Vec<i32> = workspace_groups.keys().copied().collect();
-        workspace_ids.sort();
+        let (special, regular): (Vec<&HyprClient>, Vec<&HyprClient>) =
+            visible.iter().copied().partition(|c| Self::is_special(c));
+
+        let (regular_slots, special_slots, members) = match self.order.try_borrow_mut() {
+            Ok(mut order) => {
+                order.observe(&visible);
+                if self.config.group_apps {
+                    let (regular_slots, mut members) = order.layout_grouped(self.order_mode, &regular, "regular");
+                    let (special_slots, special_members) = order.layout_grouped(self.order_mode, &special, "special");
+                    members.extend(special_members);
+                    (regular_slots, special_slots, members)
+                } else {
+                    (
+                        order.layout(self.order_mode, &regular),
+                        order.layout(self.order_mode, &special),
+                        HashMap::new(),
+                    )
+                }
+            }
+            Err(_) => {
+                // The diff is already consumed; have the main loop retry the layout.
+                self.order_changed.set(true);
+                return;
+            }
+        };
+
+        self.special_box.set_visible(!special.is_empty());
 
-        self.update_workspace_labels(&workspace_ids, &workspace_groups);
-        self.update_client_buttons(&workspace_groups, &active_address);
+        self.update_group_buttons(members, &visible, &active_address);
+        self.update_workspace_labels(&regular_slots, &special_slots, &visible);
+        self.apply_layout(&self.tasks_box, &regular_slots);
+        self.apply_layout(&self.special_box, &special_slots);
     }
 
-    fn update_workspace_labels(&self, workspace_ids: &[i32], groups: &HashMap<i32, Vec<HyprClient>>) {
+    /// Create labels for the workspace headers in the layout and drop the others. Labels are
+    /// kept between updates so they don't jump around.
+    fn update_workspace_labels(&self, regular: &[TaskbarSlot], special: &[TaskbarSlot], clients: &[&HyprClient]) {
         let mut labels = match self.workspace_labels.try_borrow_mut() {
             Ok(l) => l,
             Err(_) => return,
         };
 
-        // Clear existing labels
-        for label in labels.values() {
-            self.root.remove(label);
-        }
-        labels.clear();
+        let wanted: Vec<i32> = regular
+            .iter()
+            .chain(special.iter())
+            .filter_map(|slot| match slot {
+                TaskbarSlot::Workspace(id) => Some(*id),
+                TaskbarSlot::Task(_) | TaskbarSlot::Group(_) => None,
+            })
+            .collect();
+
+        labels.retain(|ws_id, label| {
+            let keep = wanted.contains(ws_id);
+            if !keep {
+                remove_from_box(label);
+            }
+            keep
+        });
 
-        for &ws_id in workspace_ids {
-            let label = gtk::Label::new(Some(&ws_id.to_string()));
-            label.set_widget_name("hyprland-task-workspace");
-            
-            // Add styling for workspace with clients
-            if let Some(tasks) = groups.get(&ws_id) {
-                if !tasks.is_empty() {
-                    label.add_css_class("has-clients");
-                }
+        for ws_id in wanted {
+            if labels.contains_key(&ws_id) {
+                continue;
+            }
+            let client = clients.iter().find(|c| c.workspace.id == ws_id);
+            let special = client
+                .filter(|c| Self::is_special(c))
+                .and_then(|c| special_workspace_name(&c.workspace.name));
+            // The name, not the id: sway's named workspaces and niri use ids that aren't
+            // meant to be shown.
+            let text = special
+                .map(str::to_string)
+                .or_else(|| client.map(|c| c.workspace.name.clone()).filter(|n| !n.is_empty()))
+                .unwrap_or_else(|| ws_id.to_string());
+            let label = gtk::Label::new(Some(&text));
+            label.set_widget_name(&format!("{}-task-workspace", self.compositor.css_prefix()));
+            label.set_margin_start(self.config.workspaces_spacing);
+            // Headers only exist for workspaces with windows
+            label.add_css_class("has-clients");
+
+            if self.compositor != Compositor::Wlr {
+                attach_workspace_drop_target(&label, self.compositor, ws_id, special.map(str::to_string));
+            }
+
+            if special.is_some() {
+                label.add_css_class("special");
             }
-            
-            self.root.append(&label);
             labels.insert(ws_id, label);
         }
     }
 
-    fn update_client_buttons(&self, groups: &HashMap<i32, Vec<HyprClient>>, active_address: &str) {
+    /// Create, update and drop task buttons for `clients`; returns what changed since the
+    /// last call, or `None` when the state is busy.
+    fn update_client_buttons(&self, clients: &[&HyprClient], active_address: &str) -> Option<ClientDiff> {
         let mut buttons = match self.buttons.try_borrow_mut() {
             Ok(b) => b,
+            Err(_) => return None,
+        };
+
+        let diff = match self.shown.try_borrow_mut() {
+            Ok(mut shown) => {
+                let previous: Vec<&HyprClient> = shown.iter().collect();
+                let diff = diff_clients(&previous, clients);
+                *shown = clients.iter().map(|c| (*c).clone()).collect();
+                diff
+            }
+            Err(_) => return None,
+        };
+
+        // Windows that closed or left this panel's output
+        for address in &diff.removed {
+            if let Some(btn) = buttons.remove(address) {
+                remove_from_box(&btn);
+            }
+        }
+
+        if !diff.is_empty() {
+            for c in clients {
+                match buttons.get(&c.address) {
+                    Some(btn) => {
+                        if diff.changed.contains(&c.address) {
+                            self.update_button_content(btn, c);
+                            if self.compositor == Compositor::Hyprland {
+                                // The menu's actions capture pid/class.
+                                btn.insert_action_group("task", Some(&Self::hyprland_task_actions(c)));
+                            }
+                        }
+                    }
+                    None => {
+                        let btn = self.create_client_button(c, active_address);
+                        buttons.insert(c.address.clone(), btn);
+                    }
+                }
+            }
+        }
+        // Moves between workspaces need no button changes; the caller re-lays out.
+
+        let urgent = self.urgent.try_borrow().map(|u| u.clone()).unwrap_or_default();
+        for (address, btn) in buttons.iter() {
+            Self::set_button_active(btn, address == active_address);
+            if urgent.contains(address) {
+                btn.add_css_class("urgent");
+            } else {
+                btn.remove_css_class("urgent");
+            }
+        }
+        Some(diff)
+    }
+
+    fn set_button_active(btn: &gtk::Button, active: bool) {
+        if active {
+            btn.add_css_class("active");
+        } else {
+            btn.remove_css_class("active");
+        }
+    }
+
+    /// Put the widgets for `slots` into `container` in exactly that order, and take out
+    /// anything else (e.g. task buttons now folded into a group).
+    fn apply_layout(&self, container: &gtk::Box, slots: &[TaskbarSlot]) {
+        let (buttons, labels, groups) = match (
+            self.buttons.try_borrow(),
+            self.workspace_labels.try_borrow(),
+            self.group_buttons.try_borrow(),
+        ) {
+            (Ok(b), Ok(l), Ok(g)) => (b, l, g),
+            _ => return,
+        };
+
+        let mut placed: Vec<gtk::Widget> = Vec::with_capacity(slots.len());
+        let mut prev: Option<gtk::Widget> = None;
+        for slot in slots {
+            let widget: gtk::Widget = match slot {
+                TaskbarSlot::Workspace(id) => match labels.get(id) {
+                    Some(l) => l.clone().upcast(),
+                    None => continue,
+                },
+                TaskbarSlot::Task(address) => match buttons.get(address) {
+                    Some(b) => b.clone().upcast(),
+                    None => continue,
+                },
+                TaskbarSlot::Group(group) => match groups.get(group) {
+                    Some(b) => b.clone().upcast(),
+                    None => continue,
+                },
+            };
+            // Windows move between the regular and special boxes.
+            if widget.parent().as_ref() != Some(container.upcast_ref()) {
+                remove_from_box(&widget);
+                container.append(&widget);
+            }
+            container.reorder_child_after(&widget, prev.as_ref());
+            prev = Some(widget.clone());
+            placed.push(widget);
+        }
+
+        let mut child = container.first_child();
+        while let Some(widget) = child {
+            child = widget.next_sibling();
+            if !placed.contains(&widget) {
+                container.remove(&widget);
+            }
+        }
+    }
+
+    /// Create/refresh one button per app group; its content shows the app and a count badge.
+    fn update_group_buttons(&self, members: HashMap<String, Vec<String>>, clients: &[&HyprClient], active_address: &str) {
+        let mut groups = match self.group_buttons.try_borrow_mut() {
+            Ok(g) => g,
+            Err(_) => return,
+        };
+
+        groups.retain(|group, btn| {
+            let keep = members.contains_key(group);
+            if !keep {
+                remove_from_box(btn);
+            }
+            keep
+        });
+
+        let urgent = self.urgent.try_borrow().map(|u| u.clone()).unwrap_or_default();
+        for (group, addresses) in members.iter() {
+            let first = match clients.iter().find(|c| addresses.first() == Some(&c.address)) {
+                Some(c) => c,
+                None => continue,
+            };
+            let btn = groups
+                .entry(group.clone())
+                .or_insert_with(|| self.create_group_button(group));
+
+            let content = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+            content.set_margin_start(self.config.task_padding);
+            content.set_margin_end(self.config.task_padding);
+            content.set_margin_top(self.config.task_padding);
+            content.set_margin_bottom(self.config.task_padding);
+            if self.config.show_app_icon {
+                let icon = app_image(first);
+                icon.set_widget_name(&format!("{}-task-icon", self.compositor.css_prefix()));
+                icon.set_pixel_size(self.config.image_size);
+                content.append(&icon);
+            }
+            let app_name = app_display_name(first);
+            if self.config.show_app_name {
+                let name: String = app_name.chars().take(self.config.name_max_len).collect();
+                let label = gtk::Label::new(Some(&name));
+                label.set_widget_name(&format!("{}-task-title", self.compositor.css_prefix()));
+                content.append(&label);
+            }
+            let badge = gtk::Label::new(Some(&addresses.len().to_string()));
+            badge.set_widget_name(&format!("{}-task-count", self.compositor.css_prefix()));
+            content.append(&badge);
+            btn.set_child(Some(&content));
+            btn.set_tooltip_text(Some(&format!("{} ({} windows)", app_name, addresses.len())));
+
+            Self::set_button_active(btn, addresses.iter().any(|a| a == active_address));
+            if addresses.iter().any(|a| urgent.contains(a)) {
+                btn.add_css_class("urgent");
+            } else {
+                btn.remove_css_class("urgent");
+            }
+        }
+        drop(groups);
+
+        if let Ok(mut stored) = self.group_members.try_borrow_mut() {
+            *stored = members;
+        }
+    }
+
+    fn create_group_button(&self, group: &str) -> gtk::Button {
+        let compositor = self.compositor;
+        let btn = gtk::Button::new();
+        btn.set_widget_name(&format!("{}-task", compositor.css_prefix()));
+        btn.add_css_class("group");
+
+        // Click cycles focus through the group's windows
+        let group_members = self.group_members.clone();
+        let active_address = self.active_address.clone();
+        let group_for_click = group.to_string();
+        btn.connect_clicked(move |_| {
+            let next = match (group_members.try_borrow(), active_address.try_borrow()) {
+                (Ok(members), Ok(active)) => {
+                    let addresses = match members.get(&group_for_click) {
+                        Some(a) if !a.is_empty() => a,
+                        _ => return,
+                    };
+                    let next = match addresses.iter().position(|a| *a == *active) {
+                        Some(i) => (i + 1) % addresses.len(),
+                        None => 0,
+                    };
+                    addresses[next].clone()
+                }
+                _ => return,
+            };
+            thread::spawn(move || {
+                let _ = compositor.focus_address(&next);
+            });
+        });
+
+        // Right-click lists the windows for direct selection or close
+        let group_members = self.group_members.clone();
+        let clients = self.clients.clone();
+        let group_for_menu = group.to_string();
+        let right = gtk::GestureClick::new();
+        right.set_button(3);
+        right.connect_released(move |gesture, _, _, _| {
+            let widget = match gesture.widget() {
+                Some(w) => w,
+                None => return,
+            };
+            let popover = match (group_members.try_borrow(), clients.try_borrow()) {
+                (Ok(members), Ok(clients)) => {
+                    let addresses = members.get(&group_for_menu).cloned().unwrap_or_default();
+                    Self::build_group_popover(compositor, &addresses, &clients)
+                }
+                _ => return,
+            };
+            popover.set_parent(&widget);
+            popover.connect_closed(|popover| {
+                let popover = popover.clone();
+                glib::idle_add_local_once(move || popover.unparent());
+            });
+            popover.popup();
+        });
+        btn.add_controller(right);
+
+        btn
+    }
+
+    fn build_group_popover(compositor: Compositor, addresses: &[String], clients: &[HyprClient]) -> gtk::Popover {
+        let popover = gtk::Popover::new();
+        popover.set_has_arrow(false);
+
+        let list = gtk::Box::new(gtk::Orientation::Vertical, 2);
+        for address in addresses {
+            let client = match clients.iter().find(|c| c.address == *address) {
+                Some(c) => c,
+                None => continue,
+            };
+            let row = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+
+            let focus = gtk::Button::with_label(&client.title);
+            focus.set_has_frame(false);
+            focus.set_hexpand(true);
+            if let Some(label) = focus.child().and_downcast::<gtk::Label>() {
+                label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+                label.set_max_width_chars(40);
+                label.set_xalign(0.0);
+            }
+            let close = gtk::Button::from_icon_name("window-close-symbolic");
+            close.set_has_frame(false);
+            close.set_tooltip_text(Some("Close"));
+
+            for (button, close_window) in [(&focus, false), (&close, true)] {
+                let address = address.clone();
+                let popover_for_click = popover.clone();
+                button.connect_clicked(move |_| {
+                    popover_for_click.popdown();
+                    let address = address.clone();
+                    thread::spawn(move || {
+                        let _ = if close_window {
+                            compositor.close_address(&address)
+                        } else {
+                            compositor.focus_address(&address)
+                        };
+                    });
+                });
+            }
+
+            row.append(&focus);
+            row.append(&close);
+            list.append(&row);
+        }
+
+        popover.set_child(Some(&list));
+        popover
+    }
+
+    /// Manual mode: dropping a task on another task moves its app in front of the target.
+    fn reorder_before(order: &RefCell<TaskOrderState>, clients: &RefCell<Vec<HyprClient>>, dragged: &str, target: &str) {
+        let clients = match clients.try_borrow() {
+            Ok(c) => c,
+            Err(_) => return,
+        };
+        let shown: Vec<&HyprClient> = clients.iter().collect();
+        let mut order = match order.try_borrow_mut() {
+            Ok(o) => o,
             Err(_) => return,
         };
+        if let Err(e) = order.move_before(dragged, target, &shown) {
+            eprintln!("taskbar: failed to save the task order: {e:#}");
+        }
+    }
+
+    /// Re-lay out after a manual-order drop. Called from the main loop.
+    pub fn apply_order_change(&self) {
+        // `update_taskbar` clears the flag once it has re-laid out.
+        if self.order_changed.get() {
+            self.update_taskbar();
+        }
+    }
+
+    fn is_special(client: &HyprClient) -> bool {
+        client.workspace.id < 0 && special_workspace_name(&client.workspace.name).is_some()
+    }
+
+    fn create_client_button(&self, client: &HyprClient, active_address: &str) -> gtk::Button {
+        let compositor = self.compositor;
+        let btn = gtk::Button::new();
+        btn.set_widget_name(&format!("{}-task", compositor.css_prefix()));
         
-        for (ws_id, tasks) in groups {
-            for c in tasks {
-                let _btn = if let Some(btn) = buttons.get(&c.address) {
-                    // Update existing button content
-                    self.update_button_content(btn, c, active_address);
-                    btn.clone()
-                } else {
-                    // Create new button
-                    let btn = self.create_client_button(c, active_address);
-                    buttons.insert(c.address.clone(), btn.clone());
-                    
-                    // Find the right position to insert this button
-                    self.insert_button_at_position(*ws_id, &c.address, &btn);
-                    btn
+        // Set up click handlers
+        let address_for_click = client.address.clone();
+        btn.connect_clicked(move |_| {
+            let address_for_click = address_for_click.clone();
+            thread::spawn(move || {
+                let _ = compositor.focus_address(&address_for_click);
+            });
+        });
+
+        // Middle-click closes the window
+        let address_for_close = client.address.clone();
+        let middle = gtk::GestureClick::new();
+        middle.set_button(2);
+        middle.connect_released(move |_, _, _, _| {
+            let address_for_close = address_for_close.clone();
+            thread::spawn(move || {
+                let _ = compositor.close_address(&address_for_close);
+            });
+        });
+        btn.add_controller(middle);
+
+        // Drag onto a workspace button or taskbar workspace label to move the window there
+        if compositor != Compositor::Wlr || self.order_mode == TaskOrder::Manual {
+            attach_window_drag_source(&btn, &client.address);
+        }
+
+        // ...or onto another task to reorder, in manual mode. The handler holds only the order
+        // state: a taskbar clone would keep the taskbar alive through its own buttons.
+        if self.order_mode == TaskOrder::Manual {
+            let target = gtk::DropTarget::new(String::static_type(), gdk::DragAction::MOVE);
+            let order = self.order.clone();
+            let clients = self.clients.clone();
+            let order_changed = self.order_changed.clone();
+            let address = client.address.clone();
+            target.connect_drop(move |_, value, _, _| {
+                let dragged = match value.get::<String>() {
+                    Ok(v) => match v.strip_prefix(WINDOW_DND_PREFIX) {
+                        Some(a) => a.to_string(),
+                        None => return false,
+                    },
+                    Err(_) => return false,
+                };
+                Self::reorder_before(&order, &clients, &dragged, &address);
+                // The buttons are rearranged later: the drop handler runs while GTK is still
+                // dispatching on this button.
+                order_changed.set(true);
+                true
+            });
+            btn.add_controller(target);
+        }
+
+        // Right-click opens the window management menu
+        if compositor == Compositor::Hyprland {
+            btn.insert_action_group("task", Some(&Self::hyprland_task_actions(client)));
+            let workspace_monitors = self.workspace_monitors.clone();
+            let num_ws = self.num_ws;
+            let clients = self.clients.clone();
+            let address = client.address.clone();
+            let right = gtk::GestureClick::new();
+            right.set_button(3);
+            right.connect_released(move |gesture, _, _, _| {
+                let widget = match gesture.widget() {
+                    Some(w) => w,
+                    None => return,
+                };
+                let (pid, app) = match clients.try_borrow() {
+                    Ok(clients) => match clients.iter().find(|c| c.address == address) {
+                        Some(c) => (c.pid, resolve_app(&c.class, &c.initial_class, c.pid)),
+                        None => return,
+                    },
+                    Err(_) => return,
                 };
+                // Built on demand so the workspace/monitor lists and pin state are current.
+                let launcher_pinned = app.map(|a| is_pinned(&a.id));
+                let menu = Self::hyprland_task_menu(&workspace_monitors, num_ws, pid, launcher_pinned);
+                let popover = gtk::PopoverMenu::from_model(Some(&menu));
+                popover.set_has_arrow(false);
+                popover.set_parent(&widget);
+                popover.connect_closed(|popover| {
+                    let popover = popover.clone();
+                    glib::idle_add_local_once(move || popover.unparent());
+                });
+                popover.popup();
+            });
+            btn.add_controller(right);
+        }
+
+        // Right-click exposes the wlr-foreign-toplevel window state requests
+        if compositor == Compositor::Wlr {
+            let popover = Self::build_wlr_popover(&client.address);
+            popover.set_parent(&btn);
+            btn.connect_destroy({
+                let popover = popover.clone();
+                move |_| popover.unparent()
+            });
+            let right = gtk::GestureClick::new();
+            right.set_button(3);
+            right.connect_released(move |_, _, _, _| {
+                popover.popup();
+            });
+            btn.add_controller(right);
+        }
+        
+        // Set initial content and state
+        self.update_button_content(&btn, client);
+        Self::set_button_active(&btn, client.address == active_address);
+        
+        btn
+    }
+
+    /// `launcher_pinned` is whether the window's app is in the pin cache (`None` when it has
+    /// no desktop entry).
+    fn hyprland_task_menu(
+        workspace_monitors: &Rc<RefCell<HashMap<i32, String>>>,
+        num_ws: usize,
+        pid: i32,
+        launcher_pinned: Option<bool>,
+    ) -> gio::Menu {
+        let menu = gio::Menu::new();
+
+        // The same numbers the workspaces widget offers, plus any other existing workspace.
+        let mut ids: Vec<i32> = (1..=num_ws as i32).collect();
+        if let Ok(known) = workspace_monitors.try_borrow() {
+            ids.extend(known.keys().copied().filter(|id| *id > 0));
+        }
+        ids.sort();
+        ids.dedup();
+        let workspaces = gio::Menu::new();
+        for id in ids {
+            workspaces.append(Some(&id.to_string()), Some(&format!("task.move-to-workspace({})", id)));
+        }
+        menu.append_submenu(Some("Move to workspace"), &workspaces);
+
+        let monitors = gio::Menu::new();
+        if let Some(display) = gdk::Display::default() {
+            let list = display.monitors();
+            for i in 0..list.n_items() {
+                let connector = list
+                    .item(i)
+                    .and_downcast::<gdk::Monitor>()
+                    .and_then(|m| m.connector());
+                if let Some(connector) = connector {
+                    monitors.append(Some(&connector), Some(&format!("task.move-to-monitor('{}')", connector)));
+                }
             }
         }
-    }
+        menu.append_submenu(Some("Move to monitor"), &monitors);
+
+        let state = gio::Menu::new();
+        state.append(Some("Toggle floating"), Some("task.toggle-floating"));
+        state.append(Some("Toggle fullscreen"), Some("task.toggle-fullscreen"));
+        state.append(Some("Pin"), Some("task.pin"));
+        menu.append_section(None, &state);
+
+        let lifecycle = gio::Menu::new();
+        match launcher_pinned {
+            Some(false) => lifecycle.append(Some("Pin to launchers"), Some("task.pin-launcher")),
+            Some(true) => lifecycle.append(Some("Unpin from launchers"), Some("task.unpin-launcher")),
+            None => {}
+        }
+        lifecycle.append(Some("New instance"), Some("task.new-instance"));
+        lifecycle.append(Some("Close"), Some("task.close"));
+        lifecycle.append(Some(&format!("Kill (pid {})", pid)), Some("task.kill"));
+        menu.append_section(None, &lifecycle);
 
-    fn insert_button_at_position(&self, _workspace_id: i32, _client_address: &str, button: &gtk::Button) {
-        // Simplified insertion - just append at the end for now
-        // TODO: Implement proper positioning when needed
-        self.root.append(button);
+        menu
     }
 
-    fn create_client_button(&self, client: &HyprClient, active_address: &str) -> gtk::Button {
-        let btn = gtk::Button::new();
-        btn.set_widget_name("hyprland-task");
-        
-        // Set up click handlers
-        let address_for_click = client.address.clone();
-        btn.connect_clicked(move |_| {
-            let address_for_click = address_for_click.clone();
+    fn hyprland_task_actions(client: &HyprClient) -> gio::SimpleActionGroup {
+        let group = gio::SimpleActionGroup::new();
+
+        let simple: [(&str, fn(&str) -> anyhow::Result<()>); 4] = [
+            ("toggle-floating", hyprctl_dispatch_toggle_floating_address),
+            ("toggle-fullscreen", hyprctl_dispatch_toggle_fullscreen_address),
+            ("pin", hyprctl_dispatch_pin_address),
+            ("close", hyprctl_dispatch_close_address),
+        ];
+        for (name, dispatch) in simple {
+            let action = gio::SimpleAction::new(name, None);
+            let address = client.address.clone();
+            action.connect_activate(move |_, _| {
+                let address = address.clone();
+                thread::spawn(move || {
+                    let _ = dispatch(&address);
+                });
+            });
+            group.add_action(&action);
+        }
+
+        let to_workspace = gio::SimpleAction::new("move-to-workspace", Some(glib::VariantTy::INT32));
+        let address = client.address.clone();
+        to_workspace.connect_activate(move |_, param| {
+            let id = match param.and_then(|p| p.get::<i32>()) {
+                Some(id) => id,
+                None => return,
+            };
+            let address = address.clone();
+            thread::spawn(move || {
+                let _ = hyprctl_dispatch_move_address_to_workspace(&address, id);
+            });
+        });
+        group.add_action(&to_workspace);
+
+        let to_monitor = gio::SimpleAction::new("move-to-monitor", Some(glib::VariantTy::STRING));
+        let address = client.address.clone();
+        to_monitor.connect_activate(move |_, param| {
+            let monitor = match param.and_then(|p| p.get::<String>()) {
+                Some(m) => m,
+                None => return,
+            };
+            let address = address.clone();
             thread::spawn(move || {
-                let _ = hyprctl_dispatch_focus_address(&address_for_click);
+                let _ = hyprctl_dispatch_move_address_to_monitor(&address, &monitor);
             });
         });
+        group.add_action(&to_monitor);
 
-        // Middle-click closes the window
-        let address_for_close = client.address.clone();
-        let middle = gtk::GestureClick::new();
-        middle.set_button(2);
-        middle.connect_released(move |_, _, _, _| {
-            let address_for_close = address_for_close.clone();
+        let kill = gio::SimpleAction::new("kill", None);
+        let pid = client.pid;
+        kill.connect_activate(move |_, _| {
             thread::spawn(move || {
-                let _ = hyprctl_dispatch_close_address(&address_for_close);
+                if let Err(err) = terminate_pid(pid) {
+                    eprintln!("taskbar: {err:#}");
+                }
             });
         });
-        btn.add_controller(middle);
-        
-        // Set initial content and state
-        self.update_button_content(&btn, client, active_address);
-        
-        btn
+        group.add_action(&kill);
+
+        let new_instance = gio::SimpleAction::new("new-instance", None);
+        let (class, initial_class) = (client.class.clone(), client.initial_class.clone());
+        new_instance.set_enabled(desktop_app_info(&class, &initial_class, pid).is_some());
+        new_instance.connect_activate(move |_, _| {
+            let info = match desktop_app_info(&class, &initial_class, pid) {
+                Some(i) => i,
+                None => return,
+            };
+            // Drop %U/%f-style field codes; the new instance gets no files to open.
+            let command = match info.commandline() {
+                Some(c) => c
+                    .to_string_lossy()
+                    .split_whitespace()
+                    .filter(|arg| !arg.starts_with('%'))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                None => return,
+            };
+            thread::spawn(move || {
+                let _ = hyprctl_dispatch_exec(&command);
+            });
+        });
+        group.add_action(&new_instance);
+
+        // The `pinned` module follows the cache file, so writing it is enough.
+        let app_id = resolve_app(&client.class, &client.initial_class, client.pid).map(|a| a.id);
+        let pin_actions: [(&str, fn(&str) -> anyhow::Result<()>); 2] =
+            [("pin-launcher", pin_app), ("unpin-launcher", unpin_app)];
+        for (name, update) in pin_actions {
+            let action = gio::SimpleAction::new(name, None);
+            action.set_enabled(app_id.is_some());
+            let app_id = app_id.clone();
+            action.connect_activate(move |_, _| {
+                if let Some(id) = app_id.as_deref() {
+                    if let Err(e) = update(id) {
+                        eprintln!("taskbar: failed to update the pin cache: {e:#}");
+                    }
+                }
+            });
+            group.add_action(&action);
+        }
+
+        group
     }
 
-    fn update_button_content(&self, btn: &gtk::Button, client: &HyprClient, active_address: &str) {
+    fn build_wlr_popover(address: &str) -> gtk::Popover {
+        let popover = gtk::Popover::new();
+        popover.set_has_arrow(false);
+
+        let root = gtk::Box::new(gtk::Orientation::Vertical, 2);
+        let actions: [(&str, fn(&str) -> anyhow::Result<()>); 4] = [
+            ("Minimize", wlr_toggle_minimized),
+            ("Maximize", wlr_toggle_maximized),
+            ("Fullscreen", wlr_toggle_fullscreen),
+            ("Close", wlr_close),
+        ];
+        for (label, action) in actions {
+            let item = gtk::Button::with_label(label);
+            item.set_has_frame(false);
+            let address = address.to_string();
+            let popover_for_click = popover.clone();
+            item.connect_clicked(move |_| {
+                popover_for_click.popdown();
+                let address = address.clone();
+                thread::spawn(move || {
+                    let _ = action(&address);
+                });
+            });
+            root.append(&item);
+        }
+
+        popover.set_child(Some(&root));
+        popover
+    }
+
+    fn update_button_content(&self, btn: &gtk::Button, client: &HyprClient) {
+        let xwayland = self.config.mark_xwayland && client.xwayland;
+        if xwayland {
+            btn.add_css_class("xwayland");
+        } else {
+            btn.remove_css_class("xwayland");
+        }
+
         // Update tooltip
-        let tooltip = if client.class.trim().is_empty() {
+        let app_name = app_display_name(client);
+        let tooltip = if app_name.trim().is_empty() {
             client.title.clone()
         } else if client.title.trim().is_empty() {
-            client.class.clone()
+            app_name
         } else {
-            format!("{} - {}", client.class, client.title)
+            format!("{} - {}", app_name, client.title)
         };
         btn.set_tooltip_text(Some(&tooltip));
 
-        // Update active state
-        if client.address == active_address {
-            btn.add_css_class("active");
-        } else {
-            btn.remove_css_class("active");
-        }
-
         // Create button content
         let content = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        content.set_margin_start(self.config.task_padding);
+        content.set_margin_end(self.config.task_padding);
+        content.set_margin_top(self.config.task_padding);
+        content.set_margin_bottom(self.config.task_padding);
         
         // Add application icon
-        let icon = gtk::Image::new();
-        icon.set_widget_name("hyprland-task-icon");
-        icon.set_pixel_size(16);
-        icon.set_icon_size(gtk::IconSize::Normal);
-        
-        // Try to get icon from theme
-        if gtk::IconTheme::default().has_icon(&client.class) {
-            icon.set_icon_name(Some(&client.class));
-        } else {
-            // Fallback to a generic icon
-            icon.set_icon_name(Some("application-x-executable"));
+        if self.config.show_app_icon {
+            let icon = gtk::Image::new();
+            icon.set_widget_name(&format!("{}-task-icon", self.compositor.css_prefix()));
+            icon.set_pixel_size(self.config.image_size);
+            icon.set_icon_size(gtk::IconSize::Normal);
+
+            set_app_icon(&icon, client);
+
+            content.append(&icon);
         }
-        
-        content.append(&icon);
 
         // Add title label
-        let label = gtk::Label::new(Some(&client.title));
-        label.set_widget_name("hyprland-task-title");
-        label.set_ellipsize(gtk::pango::EllipsizeMode::End);
-        label.set_max_width_chars(20);
-        content.append(&label);
+        if self.config.show_app_name {
+            let mut text: String = client.title.chars().take(self.config.name_max_len).collect();
+            if client.title.chars().count() > self.config.name_max_len {
+                text.push('…');
+            }
+            if xwayland {
+                text = format!("X|{}", text);
+            }
+            let label = gtk::Label::new(Some(&text));
+            label.set_widget_name(&format!("{}-task-title", self.compositor.css_prefix()));
+            content.append(&label);
+        }
 
         btn.set_child(Some(&content));
     }
@@ -545,6 +1997,333 @@ impl TrayUi {
     }
 }
 
+#[derive(Clone)]
+pub struct SwayModeUi {
+    root: gtk::Label,
+}
+
+impl SwayModeUi {
+    pub fn new() -> Self {
+        let root = gtk::Label::new(None);
+        root.set_widget_name("sway-mode");
+        root.set_visible(false);
+        Self { root }
+    }
+
+    pub fn widget(&self) -> gtk::Widget {
+        self.root.clone().upcast()
+    }
+
+    pub fn set_mode(&self, mode: &str) {
+        // Like upstream, the indicator is only shown outside of the default binding mode.
+        if mode.is_empty() || mode == "default" {
+            self.root.set_visible(false);
+            self.root.set_text("");
+        } else {
+            self.root.set_text(mode);
+            self.root.set_visible(true);
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ScratchpadUi {
+    root: gtk::Box,
+    buttons: Rc<RefCell<HashMap<String, gtk::Button>>>,
+}
+
+impl ScratchpadUi {
+    pub fn new() -> Self {
+        let root = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        root.set_widget_name("scratchpad");
+        root.set_visible(false);
+        Self {
+            root,
+            buttons: Rc::new(RefCell::new(HashMap::new())),
+        }
+    }
+
+    pub fn widget(&self) -> gtk::Widget {
+        self.root.clone().upcast()
+    }
+
+    pub fn set_clients(&self, clients: Vec<HyprClient>) {
+        let mut buttons = match self.buttons.try_borrow_mut() {
+            Ok(b) => b,
+            Err(_) => return,
+        };
+
+        let mut to_remove = Vec::new();
+        for (address, btn) in buttons.iter() {
+            if !clients.iter().any(|c| &c.address == address) {
+                self.root.remove(btn);
+                to_remove.push(address.clone());
+            }
+        }
+        for address in to_remove {
+            buttons.remove(&address);
+        }
+
+        for client in &clients {
+            let app_name = app_display_name(client);
+            let tooltip = if app_name.trim().is_empty() {
+                client.title.clone()
+            } else {
+                format!("{} - {}", app_name, client.title)
+            };
+
+            if let Some(btn) = buttons.get(&client.address) {
+                btn.set_tooltip_text(Some(&tooltip));
+                continue;
+            }
+
+            let btn = gtk::Button::new();
+            btn.set_widget_name("scratchpad-item");
+            btn.set_tooltip_text(Some(&tooltip));
+
+            let img = app_image(client);
+            img.set_pixel_size(16);
+            img.set_icon_size(gtk::IconSize::Normal);
+            btn.set_child(Some(&img));
+
+            let address_for_click = client.address.clone();
+            btn.connect_clicked(move |_| {
+                let address_for_click = address_for_click.clone();
+                thread::spawn(move || {
+                    let _ = swaymsg_scratchpad_show_con(&address_for_click);
+                });
+            });
+
+            self.root.append(&btn);
+            buttons.insert(client.address.clone(), btn);
+        }
+
+        self.root.set_visible(!clients.is_empty());
+    }
+}
+
+#[derive(Clone)]
+pub struct DwlTagsUi {
+    root: gtk::Box,
+    tag_buttons: Vec<gtk::Button>,
+    layout_label: gtk::Label,
+    title_label: gtk::Label,
+    config: DwlTagsConfig,
+    output_name: String,
+}
+
+impl DwlTagsUi {
+    pub fn new(config: DwlTagsConfig, output_name: String) -> Self {
+        let root = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        root.set_widget_name("dwl-tags");
+
+        let tags_box = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+        let mut tag_buttons = Vec::new();
+        // Extra names beyond the 32 bits of dwl's tag masks are ignored.
+        for (idx, name) in config.tag_names.iter().take(MAX_TAGS).enumerate() {
+            let btn = gtk::Button::with_label(name);
+            btn.set_widget_name("dwl-tag");
+            let command = config.tag_command.clone();
+            let output = output_name.clone();
+            btn.connect_clicked(move |_| {
+                let command = command.clone();
+                let output = output.clone();
+                thread::spawn(move || {
+                    if let Err(err) = dwl_view_tag(&command, &output, idx) {
+                        eprintln!("dwl-tags: {err:#}");
+                    }
+                });
+            });
+            tags_box.append(&btn);
+            tag_buttons.push(btn);
+        }
+        root.append(&tags_box);
+
+        let layout_label = gtk::Label::new(None);
+        layout_label.set_widget_name("dwl-layout");
+        layout_label.set_visible(config.show_layout);
+        root.append(&layout_label);
+
+        let title_label = gtk::Label::new(None);
+        title_label.set_widget_name("dwl-title");
+        title_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+        title_label.set_max_width_chars(config.title_limit as i32);
+        title_label.set_visible(config.show_title);
+        root.append(&title_label);
+
+        Self {
+            root,
+            tag_buttons,
+            layout_label,
+            title_label,
+            config,
+            output_name,
+        }
+    }
+
+    pub fn widget(&self) -> gtk::Widget {
+        self.root.clone().upcast()
+    }
+
+    pub fn set_status(&self, outputs: &[DwlOutput]) {
+        // Panels without an explicit output follow dwl's selected monitor.
+        let output = if self.output_name.trim().is_empty() {
+            outputs.iter().find(|o| o.selected_monitor)
+        } else {
+            outputs.iter().find(|o| o.name == self.output_name)
+        };
+        let output = match output {
+            Some(o) => o,
+            None => return,
+        };
+
+        for (idx, btn) in self.tag_buttons.iter().enumerate() {
+            let bit = 1u32 << idx;
+            for (class, on) in [
+                ("occupied", output.occupied & bit != 0),
+                ("selected", output.selected & bit != 0),
+                ("urgent", output.urgent & bit != 0),
+            ] {
+                if on {
+                    btn.add_css_class(class);
+                } else {
+                    btn.remove_css_class(class);
+                }
+            }
+        }
+
+        if self.config.show_layout {
+            self.layout_label.set_text(&output.layout);
+        }
+        if self.config.show_title {
+            self.title_label.set_text(&output.title);
+            self.title_label.set_tooltip_text(if output.appid.is_empty() {
+                None
+            } else {
+                Some(output.appid.as_str())
+            });
+        }
+    }
+}
+
+/// `America/New_York` -> `New York`.
+fn time_zone_label(id: &str) -> String {
+    id.rsplit('/').next().unwrap_or(id).replace('_', " ")
+}
+
+/// Current time in each of `zones`, as `(label, formatted)`. Unknown zones are skipped.
+fn extra_zone_times(zones: &[String], format: &str) -> Vec<(String, String)> {
+    zones
+        .iter()
+        .filter_map(|id| {
+            // `from_identifier` needs GLib 2.68; `new` falls back to UTC for unknown ids, and
+            // then reports "UTC" as its identifier. Zones such as `Etc/UTC` keep their own.
+            #[allow(deprecated)]
+            let tz = glib::TimeZone::new(Some(id));
+            if tz.identifier().as_str() != id {
+                return None;
+            }
+            let now = glib::DateTime::now(&tz).ok()?;
+            let text = now.format(format).ok()?;
+            Some((time_zone_label(id), text.to_string()))
+        })
+        .collect()
+}
+
+/// Seconds between clock ticks: 1 when `format` shows seconds, otherwise 60.
+fn clock_step(format: &str) -> u32 {
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+        // Skip chrono padding/width flags such as `%-S`, `%3f` or `%:z`.
+        let spec = chars.by_ref().find(|c| !matches!(c, '-' | '_' | '0'..='9' | '.' | ':' | '#'));
+        if matches!(spec, Some('S' | 'T' | 'X' | 'r' | 'c' | 's' | 'f' | '+')) {
+            return 1;
+        }
+    }
+    60
+}
+
+/// Milliseconds until local wall-clock time reaches the next multiple of `step` seconds.
+fn ms_until_boundary(step: u32) -> u64 {
+    let step = u64::from(step.max(1));
+    let now = chrono::Local::now();
+    let local_secs = now.timestamp() + i64::from(now.offset().local_minus_utc());
+    let into = local_secs.rem_euclid(step as i64) as u64 * 1000 + u64::from(now.timestamp_subsec_millis() % 1000);
+    step * 1000 - into
+}
+
+/// Call `tick` on each wall-clock multiple of `step` seconds. A fresh one-shot timeout is
+/// scheduled every time, so ticks don't drift; the pending one is kept in `source`.
+fn schedule_clock_tick(tick: Rc<dyn Fn()>, step: u32, source: Rc<RefCell<Option<glib::SourceId>>>) {
+    let tick_for_timer = tick.clone();
+    let source_for_timer = source.clone();
+    let id = glib::timeout_add_local_once(Duration::from_millis(ms_until_boundary(step)), move || {
+        // This source is finished; it must not be removed again.
+        source_for_timer.borrow_mut().take();
+        tick_for_timer();
+        schedule_clock_tick(tick_for_timer, step, source_for_timer);
+    });
+    if let Some(old) = source.borrow_mut().replace(id) {
+        old.remove();
+    }
+}
+
+type ClockSubscriptions = Rc<RefCell<Option<(gio::DBusConnection, Vec<gio::SignalSubscriptionId>)>>>;
+
+/// Call `resync` after resume (logind `PrepareForSleep(false)`) and when timedated reports
+/// a property change such as the time zone. Without a system bus the clock still ticks.
+fn subscribe_clock_resync(resync: Rc<dyn Fn()>) -> ClockSubscriptions {
+    let connection = match gio::bus_get_sync(gio::BusType::System, None::<&gio::Cancellable>) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("clock: no system bus, suspend/time zone resync disabled: {}", e);
+            return Rc::new(RefCell::new(None));
+        }
+    };
+
+    let resync_for_sleep = resync.clone();
+    #[allow(deprecated)]
+    let sleep = connection.signal_subscribe(
+        Some("org.freedesktop.login1"),
+        Some("org.freedesktop.login1.Manager"),
+        Some("PrepareForSleep"),
+        Some("/org/freedesktop/login1"),
+        None,
+        gio::DBusSignalFlags::NONE,
+        move |_, _, _, _, _, params| {
+            if params.get::<(bool,)>() == Some((false,)) {
+                resync_for_sleep();
+            }
+        },
+    );
+
+    #[allow(deprecated)]
+    let timedate = connection.signal_subscribe(
+        Some("org.freedesktop.timedate1"),
+        Some("org.freedesktop.DBus.Properties"),
+        Some("PropertiesChanged"),
+        Some("/org/freedesktop/timedate1"),
+        None,
+        gio::DBusSignalFlags::NONE,
+        move |_, _, _, _, _, _| resync(),
+    );
+
+    Rc::new(RefCell::new(Some((connection, vec![sleep, timedate]))))
+}
+
+fn run_clock_command(command: &str) {
+    if command.is_empty() {
+        return;
+    }
+    let command = command.to_string();
+    thread::spawn(move || {
+        let _ = std::process::Command::new("sh").args(["-c", &command]).spawn();
+    });
+}
+
 pub fn build_clock(cfg: &ClockConfig) -> gtk::Box {
     let root = gtk::Box::new(gtk::Orientation::Horizontal, 0);
     if !cfg.root_css_name.is_empty() {
@@ -564,44 +2343,181 @@ pub fn build_clock(cfg: &ClockConfig) -> gtk::Box {
     label.set_halign(gtk::Align::Center);
 
     let format = cfg.format.clone();
-    let interval = cfg.interval;
+    let step = clock_step(&cfg.format).max(cfg.interval);
+    let tooltip_format = cfg.tooltip_format.clone();
+    let time_zones = cfg.time_zones.clone();
+    let time_zone_format = cfg.time_zone_format.clone();
 
     let update_label = move |lbl: &gtk::Label| {
         let now = chrono::Local::now();
         lbl.set_text(&now.format(&format).to_string());
+
+        let mut tooltip: Vec<String> = Vec::new();
+        if !tooltip_format.is_empty() {
+            tooltip.push(now.format(&tooltip_format).to_string());
+        }
+        for (zone, time) in extra_zone_times(&time_zones, &time_zone_format) {
+            tooltip.push(format!("{}: {}", zone, time));
+        }
+        let tooltip = tooltip.join("\n");
+        lbl.set_tooltip_text((!tooltip.is_empty()).then_some(tooltip.as_str()));
     };
 
-    update_label(&label);
     let label_for_timer = label.clone();
+    let tick: Rc<dyn Fn()> = Rc::new(move || update_label(&label_for_timer));
+    tick();
+
+    let source: Rc<RefCell<Option<glib::SourceId>>> = Rc::new(RefCell::new(None));
+    schedule_clock_tick(tick.clone(), step, source.clone());
+
+    // After resume or a time zone change the pending timeout is off; tick now and realign.
+    let resync: Rc<dyn Fn()> = {
+        let source = source.clone();
+        Rc::new(move || {
+            tick();
+            schedule_clock_tick(tick.clone(), step, source.clone());
+        })
+    };
+    let subscriptions = subscribe_clock_resync(resync);
 
-    glib::timeout_add_seconds_local(interval, move || {
-        update_label(&label_for_timer);
-        glib::ControlFlow::Continue
+    root.connect_destroy(move |_| {
+        if let Some(id) = source.borrow_mut().take() {
+            id.remove();
+        }
+        if let Some((connection, ids)) = subscriptions.borrow_mut().take() {
+            for id in ids {
+                #[allow(deprecated)]
+                connection.signal_unsubscribe(id);
+            }
+        }
     });
 
     root.append(&label);
+
+    // Calendar popover, with the extra time zones below it
+    let popover = gtk::Popover::new();
+    popover.set_widget_name("clock-popover");
+    let details = gtk::Box::new(gtk::Orientation::Vertical, 4);
+    let calendar = gtk::Calendar::new();
+    details.append(&calendar);
+    let zones_box = gtk::Box::new(gtk::Orientation::Vertical, 2);
+    zones_box.set_widget_name("clock-time-zones");
+    details.append(&zones_box);
+    popover.set_child(Some(&details));
+    popover.set_parent(&root);
+    root.connect_destroy({
+        let popover = popover.clone();
+        move |_| popover.unparent()
+    });
+
+    let time_zones = cfg.time_zones.clone();
+    let time_zone_format = cfg.time_zone_format.clone();
+    let open_calendar = move || {
+        if let Ok(today) = glib::DateTime::now_local() {
+            calendar.select_day(&today);
+        }
+        while let Some(child) = zones_box.first_child() {
+            zones_box.remove(&child);
+        }
+        for (zone, time) in extra_zone_times(&time_zones, &time_zone_format) {
+            let row = gtk::Box::new(gtk::Orientation::Horizontal, 12);
+            let name = gtk::Label::new(Some(&zone));
+            name.set_hexpand(true);
+            name.set_xalign(0.0);
+            row.append(&name);
+            row.append(&gtk::Label::new(Some(&time)));
+            zones_box.append(&row);
+        }
+        zones_box.set_visible(zones_box.first_child().is_some());
+        popover.popup();
+    };
+
+    let click = gtk::GestureClick::new();
+    click.set_button(0);
+    let (on_left, on_middle, on_right) = (
+        cfg.on_left_click.clone(),
+        cfg.on_middle_click.clone(),
+        cfg.on_right_click.clone(),
+    );
+    click.connect_released(move |gesture, _, _, _| match gesture.current_button() {
+        1 if on_left.is_empty() => open_calendar(),
+        1 => run_clock_command(&on_left),
+        2 => run_clock_command(&on_middle),
+        3 => run_clock_command(&on_right),
+        _ => {}
+    });
+    root.add_controller(click);
+
+    if !cfg.on_scroll_up.is_empty() || !cfg.on_scroll_down.is_empty() {
+        let scroll = gtk::EventControllerScroll::new(gtk::EventControllerScrollFlags::VERTICAL);
+        let (on_up, on_down) = (cfg.on_scroll_up.clone(), cfg.on_scroll_down.clone());
+        scroll.connect_scroll(move |_, _, dy| {
+            if dy < 0.0 {
+                run_clock_command(&on_up);
+            } else if dy > 0.0 {
+                run_clock_command(&on_down);
+            }
+            glib::Propagation::Stop
+        });
+        root.add_controller(scroll);
+    }
+
     root
 }
 
-pub fn instantiate_module(
-    panel: &PanelConfig,
-    name: &str,
-    tray: Option<&TrayUi>,
-    workspaces: Option<&WorkspacesUi>,
-    taskbar: Option<&TaskbarUi>,
-    controls: Option<&ControlsUi>,
-) -> gtk::Widget {
+/// The module widgets a panel builds up front (they get `AppMsg` updates), for
+/// `instantiate_module` to place by name. `None` when the panel doesn't use the module.
+#[derive(Clone, Default)]
+pub struct ModuleUis {
+    pub tray: Option<TrayUi>,
+    pub workspaces: Option<WorkspacesUi>,
+    pub taskbar: Option<TaskbarUi>,
+    pub controls: Option<ControlsUi>,
+    pub sway_mode: Option<SwayModeUi>,
+    pub scratchpad: Option<ScratchpadUi>,
+    pub dwl_tags: Option<DwlTagsUi>,
+    pub pinned: Option<PinnedUi>,
+    pub menu_start: Option<MenuStartUi>,
+    pub playerctl: Option<PlayerctlUi>,
+    pub resources: Option<ResourceUis>,
+    pub network: Option<NetworkUi>,
+}
+
+pub fn instantiate_module(panel: &PanelConfig, name: &str, uis: &ModuleUis) -> gtk::Widget {
+    let ModuleUis {
+        tray,
+        workspaces,
+        taskbar,
+        controls,
+        sway_mode,
+        scratchpad,
+        dwl_tags,
+        pinned,
+        menu_start,
+        playerctl,
+        resources,
+        network,
+    } = uis;
+
     if name == "clock" {
         return build_clock(&panel.clock).upcast();
     }
 
-    if name == "hyprland-workspaces" {
+    if name == "hyprland-workspaces"
+        || name == "sway-workspaces"
+        || name == "niri-workspaces"
+        || name == "workspaces"
+    {
         if let Some(workspaces) = workspaces {
             return workspaces.widget();
         }
     }
 
-    if name == "hyprland-taskbar" {
+    if name == "hyprland-taskbar"
+        || name == "sway-taskbar"
+        || name == "niri-taskbar"
+        || name == "taskbar"
+    {
         if let Some(taskbar) = taskbar {
             return taskbar.widget();
         }
@@ -610,6 +2526,65 @@ pub fn instantiate_module(
         }
     }
 
+    if name == "sway-mode" {
+        if let Some(sway_mode) = sway_mode {
+            return sway_mode.widget();
+        }
+    }
+
+    if name == "scratchpad" {
+        if let Some(scratchpad) = scratchpad {
+            return scratchpad.widget();
+        }
+    }
+
+    if name == "dwl-tags" {
+        if let Some(dwl_tags) = dwl_tags {
+            return dwl_tags.widget();
+        }
+    }
+
+    if name == "menu-start" {
+        if let Some(menu_start) = menu_start {
+            return menu_start.widget();
+        }
+    }
+
+    if let Some(widget) = resources.as_ref().and_then(|r| r.widget(name)) {
+        return widget;
+    }
+
+    #[cfg(feature = "openweather")]
+    if name == "openweather" {
+        return super::openweather::OpenWeatherUi::new(panel.openweather.clone()).widget();
+    }
+
+    if name == "brightness-slider" {
+        return BrightnessSliderUi::new(panel.brightness_slider.clone()).widget();
+    }
+
+    if name == "random-wallpaper" {
+        return RandomWallpaperUi::new(panel.random_wallpaper.clone()).widget();
+    }
+
+    if name == "network" {
+        if let Some(network) = network {
+            return network.widget();
+        }
+    }
+
+    if name == "playerctl" {
+        if let Some(playerctl) = playerctl {
+            return playerctl.widget();
+        }
+    }
+
+    if name == "pinned" {
+        if let Some(pinned) = pinned {
+            return pinned.widget();
+        }
+    }
+
     if name == "tray" {
         if let Some(tray) = tray {
             return tray.widget();
@@ -667,3 +2642,15 @@ pub fn instantiate_module(
     label.set_widget_name(name);
     label.upcast()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extra_zones_keep_utc_aliases_and_skip_unknown_ids() {
+        let zones = ["Etc/UTC".to_string(), "Not/A_Zone".to_string(), "Europe/Warsaw".to_string()];
+        let labels: Vec<String> = extra_zone_times(&zones, "%H:%M").into_iter().map(|(label, _)| label).collect();
+        assert_eq!(labels, vec!["UTC", "Warsaw"]);
+    }
+}
@@ -0,0 +1,634 @@
+use super::hyprland::{AppMsg, HyprClient, HyprWorkspace, HyprWorkspaceInfo};
+use anyhow::Context;
+use crossbeam_channel as cb;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use wayland_client::globals::{registry_queue_init, GlobalListContents};
+use wayland_client::protocol::wl_output::{self, WlOutput};
+use wayland_client::protocol::wl_registry::{self, WlRegistry};
+use wayland_client::protocol::wl_seat::{self, WlSeat};
+use wayland_client::{event_created_child, Connection, Dispatch, Proxy, QueueHandle, WEnum};
+use wayland_protocols::ext::workspace::v1::client::ext_workspace_group_handle_v1::{
+    self, ExtWorkspaceGroupHandleV1,
+};
+use wayland_protocols::ext::workspace::v1::client::ext_workspace_handle_v1::{
+    self, ExtWorkspaceHandleV1,
+};
+use wayland_protocols::ext::workspace::v1::client::ext_workspace_manager_v1::{
+    self, ExtWorkspaceManagerV1,
+};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::zwlr_foreign_toplevel_handle_v1::{
+    self, ZwlrForeignToplevelHandleV1,
+};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::zwlr_foreign_toplevel_manager_v1::{
+    self, ZwlrForeignToplevelManagerV1,
+};
+
+// zwlr_foreign_toplevel_handle_v1.state values.
+const TOPLEVEL_STATE_MAXIMIZED: u32 = 0;
+const TOPLEVEL_STATE_MINIMIZED: u32 = 1;
+const TOPLEVEL_STATE_ACTIVATED: u32 = 2;
+const TOPLEVEL_STATE_FULLSCREEN: u32 = 3;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ToplevelFlags {
+    pub maximized: bool,
+    pub minimized: bool,
+    pub activated: bool,
+    pub fullscreen: bool,
+}
+
+/// Proxies shared with the GTK side so click handlers can issue requests.
+///
+/// Wayland proxies are `Send + Sync`; requests are queued on the shared connection and
+/// flushed right away.
+struct WlrShared {
+    conn: Connection,
+    seat: Mutex<Option<WlSeat>>,
+    toplevels: Mutex<HashMap<String, (ZwlrForeignToplevelHandleV1, ToplevelFlags)>>,
+    workspaces: Mutex<HashMap<i32, ExtWorkspaceHandleV1>>,
+    workspace_manager: Mutex<Option<ExtWorkspaceManagerV1>>,
+    /// The messages of the last update, replayed for panels created later.
+    last_snapshot: Mutex<Vec<AppMsg>>,
+}
+
+static SHARED: OnceLock<WlrShared> = OnceLock::new();
+
+#[derive(Debug, Clone, Default)]
+struct Toplevel {
+    title: String,
+    app_id: String,
+    outputs: Vec<u32>,
+    flags: ToplevelFlags,
+    ready: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Workspace {
+    name: String,
+    coordinates: Vec<u32>,
+    active: bool,
+    urgent: bool,
+    hidden: bool,
+    group: Option<u32>,
+}
+
+struct WlrState {
+    sender: cb::Sender<AppMsg>,
+    outputs: HashMap<u32, String>,
+    toplevels: HashMap<u32, Toplevel>,
+    workspaces: HashMap<u32, Workspace>,
+    group_outputs: HashMap<u32, Vec<u32>>,
+    dirty: bool,
+}
+
+impl WlrState {
+    fn output_name(&self, id: u32) -> String {
+        self.outputs.get(&id).cloned().unwrap_or_default()
+    }
+
+    fn send(&mut self) {
+        self.dirty = false;
+
+        let mut workspaces: Vec<(&u32, &Workspace)> =
+            self.workspaces.iter().filter(|(_, w)| !w.hidden).collect();
+        workspaces.sort_by(|a, b| a.1.coordinates.cmp(&b.1.coordinates).then(a.0.cmp(b.0)));
+
+        let mut active_id = 0;
+        let ws_out: Vec<HyprWorkspace> = workspaces
+            .iter()
+            .map(|(id, ws)| {
+                if ws.active && active_id == 0 {
+                    active_id = **id as i32;
+                }
+                let monitor = ws
+                    .group
+                    .and_then(|g| self.group_outputs.get(&g))
+                    .and_then(|outputs| outputs.first())
+                    .map(|o| self.output_name(*o))
+                    .unwrap_or_default();
+                HyprWorkspace {
+                    id: **id as i32,
+                    idx: **id as i32,
+                    name: ws.name.clone(),
+                    monitor,
+                    // Unknown, see the client mapping below.
+                    windows: 0,
+                    hasfullscreen: false,
+                    lastwindow: String::new(),
+                    lastwindowtitle: String::new(),
+                    ispersistent: false,
+                }
+            })
+            .collect();
+
+        let mut toplevels: Vec<(&u32, &Toplevel)> =
+            self.toplevels.iter().filter(|(_, t)| t.ready).collect();
+        toplevels.sort_by_key(|(id, _)| **id);
+
+        let clients: Vec<HyprClient> = toplevels
+            .iter()
+            .map(|(id, t)| HyprClient {
+                address: id.to_string(),
+                mapped: true,
+                hidden: t.flags.minimized,
+                at: (0, 0),
+                size: (0, 0),
+                // Neither wlr-foreign-toplevel nor ext-workspace says which workspace a
+                // toplevel is on, so every client is on "workspace 0", named after its output.
+                workspace: HyprWorkspaceInfo {
+                    id: 0,
+                    name: t.outputs.first().map(|o| self.output_name(*o)).unwrap_or_default(),
+                },
+                floating: false,
+                fullscreen: t.flags.fullscreen,
+                fullscreen_mode: 0,
+                maximized: t.flags.maximized,
+                focus_history_id: 0,
+                pid: 0,
+                xwayland: false,
+                title: t.title.clone(),
+                class: t.app_id.clone(),
+                initial_class: t.app_id.clone(),
+            })
+            .collect();
+
+        let mut messages = Vec::with_capacity(4);
+        if let Some((id, t)) = toplevels.iter().find(|(_, t)| t.flags.activated) {
+            messages.push(AppMsg::HyprActiveWindow(t.title.clone()));
+            messages.push(AppMsg::HyprActiveWindowAddress(id.to_string()));
+        }
+        if !ws_out.is_empty() {
+            messages.push(AppMsg::HyprWorkspaces {
+                workspaces: ws_out,
+                active_id,
+            });
+        }
+        messages.push(AppMsg::HyprClients { clients });
+
+        for msg in &messages {
+            let _ = self.sender.send(msg.clone());
+        }
+        if let Some(shared) = SHARED.get() {
+            if let Ok(mut last) = shared.last_snapshot.lock() {
+                *last = messages;
+            }
+        }
+    }
+}
+
+impl Dispatch<WlRegistry, ()> for WlrState {
+    fn event(
+        _state: &mut Self,
+        registry: &WlRegistry,
+        event: wl_registry::Event,
+        _: &(),
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        if let wl_registry::Event::Global {
+            name,
+            interface,
+            version,
+        } = event
+        {
+            match interface.as_str() {
+                "wl_seat" => {
+                    let seat = registry.bind::<WlSeat, _, _>(name, version.min(1), qh, ());
+                    if let Some(shared) = SHARED.get() {
+                        if let Ok(mut slot) = shared.seat.lock() {
+                            slot.get_or_insert(seat);
+                        }
+                    }
+                }
+                "wl_output" => {
+                    // Output names need wl_output v4.
+                    registry.bind::<WlOutput, _, _>(name, version.min(4), qh, ());
+                }
+                "zwlr_foreign_toplevel_manager_v1" => {
+                    registry.bind::<ZwlrForeignToplevelManagerV1, _, _>(name, version.min(3), qh, ());
+                }
+                "ext_workspace_manager_v1" => {
+                    let manager = registry.bind::<ExtWorkspaceManagerV1, _, _>(name, 1, qh, ());
+                    if let Some(shared) = SHARED.get() {
+                        if let Ok(mut slot) = shared.workspace_manager.lock() {
+                            *slot = Some(manager);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Dispatch<WlSeat, ()> for WlrState {
+    fn event(_: &mut Self, _: &WlSeat, _: wl_seat::Event, _: &(), _: &Connection, _: &QueueHandle<Self>) {}
+}
+
+impl Dispatch<WlOutput, ()> for WlrState {
+    fn event(
+        state: &mut Self,
+        output: &WlOutput,
+        event: wl_output::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let wl_output::Event::Name { name } = event {
+            state.outputs.insert(output.id().protocol_id(), name);
+            state.dirty = true;
+        }
+    }
+}
+
+impl Dispatch<ZwlrForeignToplevelManagerV1, ()> for WlrState {
+    fn event(
+        state: &mut Self,
+        _: &ZwlrForeignToplevelManagerV1,
+        event: zwlr_foreign_toplevel_manager_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let zwlr_foreign_toplevel_manager_v1::Event::Toplevel { toplevel } = event {
+            let id = toplevel.id().protocol_id();
+            state.toplevels.insert(id, Toplevel::default());
+            if let Some(shared) = SHARED.get() {
+                if let Ok(mut map) = shared.toplevels.lock() {
+                    map.insert(id.to_string(), (toplevel, ToplevelFlags::default()));
+                }
+            }
+        }
+    }
+
+    event_created_child!(WlrState, ZwlrForeignToplevelManagerV1, [
+        zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE => (ZwlrForeignToplevelHandleV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrForeignToplevelHandleV1, ()> for WlrState {
+    fn event(
+        state: &mut Self,
+        handle: &ZwlrForeignToplevelHandleV1,
+        event: zwlr_foreign_toplevel_handle_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let id = handle.id().protocol_id();
+        let toplevel = match state.toplevels.get_mut(&id) {
+            Some(t) => t,
+            None => return,
+        };
+
+        match event {
+            zwlr_foreign_toplevel_handle_v1::Event::Title { title } => toplevel.title = title,
+            zwlr_foreign_toplevel_handle_v1::Event::AppId { app_id } => toplevel.app_id = app_id,
+            zwlr_foreign_toplevel_handle_v1::Event::OutputEnter { output } => {
+                toplevel.outputs.push(output.id().protocol_id());
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::OutputLeave { output } => {
+                let output_id = output.id().protocol_id();
+                toplevel.outputs.retain(|o| *o != output_id);
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::State { state: raw } => {
+                let values: Vec<u32> = raw
+                    .chunks_exact(4)
+                    .map(|c| u32::from_ne_bytes([c[0], c[1], c[2], c[3]]))
+                    .collect();
+                toplevel.flags = ToplevelFlags {
+                    maximized: values.contains(&TOPLEVEL_STATE_MAXIMIZED),
+                    minimized: values.contains(&TOPLEVEL_STATE_MINIMIZED),
+                    activated: values.contains(&TOPLEVEL_STATE_ACTIVATED),
+                    fullscreen: values.contains(&TOPLEVEL_STATE_FULLSCREEN),
+                };
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::Done => {
+                toplevel.ready = true;
+                let flags = toplevel.flags;
+                if let Some(shared) = SHARED.get() {
+                    if let Ok(mut map) = shared.toplevels.lock() {
+                        if let Some(entry) = map.get_mut(&id.to_string()) {
+                            entry.1 = flags;
+                        }
+                    }
+                }
+                state.dirty = true;
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::Closed => {
+                state.toplevels.remove(&id);
+                if let Some(shared) = SHARED.get() {
+                    if let Ok(mut map) = shared.toplevels.lock() {
+                        map.remove(&id.to_string());
+                    }
+                }
+                handle.destroy();
+                state.dirty = true;
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ExtWorkspaceManagerV1, ()> for WlrState {
+    fn event(
+        state: &mut Self,
+        _: &ExtWorkspaceManagerV1,
+        event: ext_workspace_manager_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            ext_workspace_manager_v1::Event::WorkspaceGroup { workspace_group } => {
+                state.group_outputs.insert(workspace_group.id().protocol_id(), Vec::new());
+            }
+            ext_workspace_manager_v1::Event::Workspace { workspace } => {
+                let id = workspace.id().protocol_id();
+                state.workspaces.insert(id, Workspace::default());
+                if let Some(shared) = SHARED.get() {
+                    if let Ok(mut map) = shared.workspaces.lock() {
+                        map.insert(id as i32, workspace);
+                    }
+                }
+            }
+            ext_workspace_manager_v1::Event::Done => {
+                state.dirty = true;
+            }
+            _ => {}
+        }
+    }
+
+    event_created_child!(WlrState, ExtWorkspaceManagerV1, [
+        ext_workspace_manager_v1::EVT_WORKSPACE_GROUP_OPCODE => (ExtWorkspaceGroupHandleV1, ()),
+        ext_workspace_manager_v1::EVT_WORKSPACE_OPCODE => (ExtWorkspaceHandleV1, ()),
+    ]);
+}
+
+impl Dispatch<ExtWorkspaceGroupHandleV1, ()> for WlrState {
+    fn event(
+        state: &mut Self,
+        group: &ExtWorkspaceGroupHandleV1,
+        event: ext_workspace_group_handle_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let group_id = group.id().protocol_id();
+        match event {
+            ext_workspace_group_handle_v1::Event::OutputEnter { output } => {
+                state
+                    .group_outputs
+                    .entry(group_id)
+                    .or_default()
+                    .push(output.id().protocol_id());
+            }
+            ext_workspace_group_handle_v1::Event::OutputLeave { output } => {
+                let output_id = output.id().protocol_id();
+                if let Some(outputs) = state.group_outputs.get_mut(&group_id) {
+                    outputs.retain(|o| *o != output_id);
+                }
+            }
+            ext_workspace_group_handle_v1::Event::WorkspaceEnter { workspace } => {
+                if let Some(ws) = state.workspaces.get_mut(&workspace.id().protocol_id()) {
+                    ws.group = Some(group_id);
+                }
+            }
+            ext_workspace_group_handle_v1::Event::WorkspaceLeave { workspace } => {
+                if let Some(ws) = state.workspaces.get_mut(&workspace.id().protocol_id()) {
+                    if ws.group == Some(group_id) {
+                        ws.group = None;
+                    }
+                }
+            }
+            ext_workspace_group_handle_v1::Event::Removed => {
+                state.group_outputs.remove(&group_id);
+                group.destroy();
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ExtWorkspaceHandleV1, ()> for WlrState {
+    fn event(
+        state: &mut Self,
+        handle: &ExtWorkspaceHandleV1,
+        event: ext_workspace_handle_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let id = handle.id().protocol_id();
+        if let ext_workspace_handle_v1::Event::Removed = event {
+            state.workspaces.remove(&id);
+            if let Some(shared) = SHARED.get() {
+                if let Ok(mut map) = shared.workspaces.lock() {
+                    map.remove(&(id as i32));
+                }
+            }
+            handle.destroy();
+            return;
+        }
+
+        let ws = match state.workspaces.get_mut(&id) {
+            Some(ws) => ws,
+            None => return,
+        };
+        match event {
+            ext_workspace_handle_v1::Event::Name { name } => ws.name = name,
+            ext_workspace_handle_v1::Event::Coordinates { coordinates } => {
+                ws.coordinates = coordinates
+                    .chunks_exact(4)
+                    .map(|c| u32::from_ne_bytes([c[0], c[1], c[2], c[3]]))
+                    .collect();
+            }
+            ext_workspace_handle_v1::Event::State { state: WEnum::Value(flags) } => {
+                ws.active = flags.contains(ext_workspace_handle_v1::State::Active);
+                ws.urgent = flags.contains(ext_workspace_handle_v1::State::Urgent);
+                ws.hidden = flags.contains(ext_workspace_handle_v1::State::Hidden);
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn spawn_wlr_listener(sender: cb::Sender<AppMsg>) {
+    thread::spawn(move || {
+        if let Err(err) = run_wlr_listener(sender) {
+            eprintln!("wlr: Wayland listener stopped: {err:#}");
+        }
+    });
+}
+
+fn run_wlr_listener(sender: cb::Sender<AppMsg>) -> anyhow::Result<()> {
+    let conn = Connection::connect_to_env().context("Failed to connect to the Wayland display")?;
+    let _ = SHARED.set(WlrShared {
+        conn: conn.clone(),
+        seat: Mutex::new(None),
+        toplevels: Mutex::new(HashMap::new()),
+        workspaces: Mutex::new(HashMap::new()),
+        workspace_manager: Mutex::new(None),
+        last_snapshot: Mutex::new(Vec::new()),
+    });
+
+    let mut event_queue = conn.new_event_queue::<WlrState>();
+    let qh = event_queue.handle();
+    conn.display().get_registry(&qh, ());
+
+    let mut state = WlrState {
+        sender,
+        outputs: HashMap::new(),
+        toplevels: HashMap::new(),
+        workspaces: HashMap::new(),
+        group_outputs: HashMap::new(),
+        dirty: false,
+    };
+
+    loop {
+        event_queue.blocking_dispatch(&mut state)?;
+        if state.dirty {
+            state.send();
+        }
+    }
+}
+
+/// There is nothing to re-query: the listener keeps the state, so replay its last update.
+pub fn send_wlr_snapshot(sender: &cb::Sender<AppMsg>) {
+    let messages = SHARED
+        .get()
+        .and_then(|s| s.last_snapshot.lock().ok().map(|last| last.clone()))
+        .unwrap_or_default();
+    for msg in messages {
+        let _ = sender.send(msg);
+    }
+}
+
+/// Registry state for `wlr_protocols_available`; only the initial global list is needed.
+struct GlobalProbe;
+
+impl Dispatch<WlRegistry, GlobalListContents> for GlobalProbe {
+    fn event(
+        _: &mut Self,
+        _: &WlRegistry,
+        _: wl_registry::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+/// Whether the compositor advertises wlr-foreign-toplevel or ext-workspace, i.e. whether this
+/// backend would have anything to show.
+pub fn wlr_protocols_available() -> bool {
+    let conn = match Connection::connect_to_env() {
+        Ok(conn) => conn,
+        Err(_) => return false,
+    };
+    match registry_queue_init::<GlobalProbe>(&conn) {
+        Ok((globals, _queue)) => globals.contents().with_list(|list| {
+            list.iter().any(|g| {
+                g.interface == "zwlr_foreign_toplevel_manager_v1" || g.interface == "ext_workspace_manager_v1"
+            })
+        }),
+        Err(_) => false,
+    }
+}
+
+fn with_toplevel<F>(address: &str, f: F) -> anyhow::Result<()>
+where
+    F: FnOnce(&WlrShared, &ZwlrForeignToplevelHandleV1, ToplevelFlags) -> anyhow::Result<()>,
+{
+    let shared = SHARED.get().context("wlr backend is not running")?;
+    let (handle, flags) = {
+        let map = shared.toplevels.lock().map_err(|_| anyhow::anyhow!("wlr state poisoned"))?;
+        map.get(address).cloned().context("Unknown toplevel")?
+    };
+    f(shared, &handle, flags)?;
+    shared.conn.flush()?;
+    Ok(())
+}
+
+pub fn wlr_activate(address: &str) -> anyhow::Result<()> {
+    with_toplevel(address, |shared, handle, _| {
+        let seat = shared.seat.lock().ok().and_then(|s| s.clone()).context("No wl_seat")?;
+        handle.activate(&seat);
+        Ok(())
+    })
+}
+
+pub fn wlr_close(address: &str) -> anyhow::Result<()> {
+    with_toplevel(address, |_, handle, _| {
+        handle.close();
+        Ok(())
+    })
+}
+
+pub fn wlr_toggle_minimized(address: &str) -> anyhow::Result<()> {
+    with_toplevel(address, |_, handle, flags| {
+        if flags.minimized {
+            handle.unset_minimized();
+        } else {
+            handle.set_minimized();
+        }
+        Ok(())
+    })
+}
+
+pub fn wlr_toggle_maximized(address: &str) -> anyhow::Result<()> {
+    with_toplevel(address, |_, handle, flags| {
+        if flags.maximized {
+            handle.unset_maximized();
+        } else {
+            handle.set_maximized();
+        }
+        Ok(())
+    })
+}
+
+pub fn wlr_toggle_fullscreen(address: &str) -> anyhow::Result<()> {
+    with_toplevel(address, |_, handle, flags| {
+        if flags.fullscreen {
+            handle.unset_fullscreen();
+        } else {
+            handle.set_fullscreen(None);
+        }
+        Ok(())
+    })
+}
+
+/// Taskbar-style click: minimize the focused window, otherwise raise and focus it.
+pub fn wlr_activate_or_minimize(address: &str) -> anyhow::Result<()> {
+    let activated = SHARED
+        .get()
+        .and_then(|s| s.toplevels.lock().ok().and_then(|m| m.get(address).map(|e| e.1.activated)))
+        .unwrap_or(false);
+    if activated {
+        wlr_toggle_minimized(address)
+    } else {
+        wlr_activate(address)
+    }
+}
+
+pub fn wlr_activate_workspace(id: i32) -> anyhow::Result<()> {
+    let shared = SHARED.get().context("wlr backend is not running")?;
+    let workspace = shared
+        .workspaces
+        .lock()
+        .ok()
+        .and_then(|m| m.get(&id).cloned())
+        .context("Unknown workspace")?;
+    let manager = shared
+        .workspace_manager
+        .lock()
+        .ok()
+        .and_then(|m| m.clone())
+        .context("Compositor has no ext_workspace_manager_v1")?;
+    workspace.activate();
+    manager.commit();
+    shared.conn.flush()?;
+    Ok(())
+}